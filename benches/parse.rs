@@ -0,0 +1,66 @@
+//! Parse throughput, run with `cargo bench --bench parse`
+//!
+//! Compares reading byte aligned fields bit by bit with [`BitReader`] against reading whole bytes
+//! with [`ByteReader`], and measures the full message decoders built on top of them.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use bitreader::{BitReader, ByteReader};
+use ldns::message::{Message, MessageRef};
+
+/// Response for www.example. with a CNAME and two A records
+const RESPONSE: &[u8] = &[
+    0, 1, 0x81, 0x80, 0, 1, 0, 3, 0, 0, 0, 0, //
+    3, b'w', b'w', b'w', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0, 0, 1, 0, 1, //
+    0xc0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0, 6, 3, b'c', b'd', b'n', 0xc0, 16, //
+    0xc0, 41, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1, //
+    0xc0, 41, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 2,
+];
+
+/// Run `f` repeatedly for about a second and print the time per iteration
+fn bench(name: &str, bytes_per_iteration: usize, mut f: impl FnMut()) {
+    let mut iterations = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        for _ in 0..1000 {
+            f();
+        }
+        iterations += 1000;
+    }
+
+    let elapsed = start.elapsed();
+    let nanos = elapsed.as_nanos() as f64 / iterations as f64;
+    let throughput =
+        (bytes_per_iteration as f64 * iterations as f64) / elapsed.as_secs_f64() / 1_000_000.0;
+    println!("{name:<32} {nanos:>10.1} ns/iter {throughput:>10.1} MB/s");
+}
+
+fn main() {
+    let fields: Vec<u8> = (0..=255).collect();
+
+    bench("u16 fields, per bit", fields.len(), || {
+        let mut reader = BitReader::from_bytes(black_box(&fields));
+        for _ in 0..fields.len() / 2 {
+            black_box(reader.next_u16(16).unwrap());
+        }
+    });
+    bench("u16 fields, byte aligned", fields.len(), || {
+        let mut reader = ByteReader::from_bytes(black_box(&fields));
+        for _ in 0..fields.len() / 2 {
+            black_box(reader.next_u16().unwrap());
+        }
+    });
+
+    bench("Message::from_bytes", RESPONSE.len(), || {
+        black_box(Message::from_bytes(black_box(RESPONSE)).unwrap());
+    });
+    bench("MessageRef answers", RESPONSE.len(), || {
+        let message = MessageRef::from_bytes(black_box(RESPONSE)).unwrap();
+        for answer in message.answers().unwrap() {
+            black_box(answer.unwrap().rdata());
+        }
+    });
+}
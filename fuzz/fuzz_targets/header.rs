@@ -0,0 +1,14 @@
+#![no_main]
+
+use ldns::message::Header;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = Header::from_bytes(data) {
+        // Every decodable header encodes back to the bytes it was read from, except for the
+        // reserved Z bit which is not kept
+        let mut expected = data[..12].to_vec();
+        expected[3] &= !0x40;
+        assert_eq!(header.as_bytes(), expected);
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use ldns::message::{Message, MessageRef};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(message) = Message::from_bytes(data) {
+        // Whatever was decoded has valid names and can be encoded again
+        let bytes = message.as_bytes().unwrap();
+        message.as_bytes_with_limit(bytes.len()).unwrap();
+    }
+
+    if let Ok(message) = MessageRef::from_bytes(data) {
+        let _ = message.header();
+        for question in message.questions().flatten() {
+            let _ = question.qname.to_name();
+        }
+        for section in [message.answers(), message.authority(), message.additional()] {
+            for record in section.into_iter().flatten().flatten() {
+                let _ = record.to_record();
+            }
+        }
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use ldns::message::{Message, MessageRef};
+use libfuzzer_sys::fuzz_target;
+
+/// Header of a message with a single question, the input is placed right after it so
+/// compression pointers in the input can point back into the header and the input itself
+const HEADER: [u8; 12] = [0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+
+fuzz_target!(|data: &[u8]| {
+    let mut bytes = HEADER.to_vec();
+    bytes.extend(data);
+
+    let owned = Message::from_bytes(&bytes).map(|message| message.questions()[0].qname.clone());
+    let borrowed = MessageRef::from_bytes(&bytes)
+        .and_then(|message| message.questions().next().unwrap())
+        .and_then(|question| question.qname.to_name());
+
+    // Both decoders have to agree on names they both accept
+    if let (Ok(owned), Ok(borrowed)) = (owned, borrowed) {
+        assert_eq!(owned, borrowed);
+    }
+});
@@ -0,0 +1,188 @@
+/// The counterpart of [`crate::BitReader`], writes values bit by bit into a vector of bytes
+///
+/// Writes of whole bytes at a byte aligned position skip the per bit loop. The pointer can be
+/// moved back with [`BitWriter::set_pointer`] to overwrite earlier values, for example a length
+/// which is only known after writing what follows it.
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    pointer: usize,
+    bytes: Vec<u8>,
+}
+
+impl BitWriter {
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        self.pointer += count;
+        self.grow();
+    }
+
+    pub fn set_pointer(&mut self, offset: usize) {
+        self.pointer = offset;
+        self.grow();
+    }
+
+    /// Position in bits of the next write
+    pub fn get_pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Make sure all bytes up to the pointer exist
+    fn grow(&mut self) {
+        let length = self.pointer.div_ceil(8);
+        if self.bytes.len() < length {
+            self.bytes.resize(length, 0);
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        let index = self.pointer / 8;
+        if index == self.bytes.len() {
+            self.bytes.push(0);
+        }
+
+        let mask = 1 << (7 - (self.pointer % 8));
+        if bit {
+            self.bytes[index] |= mask;
+        } else {
+            self.bytes[index] &= !mask;
+        }
+        self.pointer += 1;
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.pointer.is_multiple_of(8) && self.pointer / 8 == self.bytes.len() {
+            self.bytes.extend(bytes);
+            self.pointer += bytes.len() * 8;
+            return;
+        }
+
+        for byte in bytes {
+            self.write_bits(*byte as u128, 8, 8);
+        }
+    }
+
+    /// Write the lowest `bit_count` bits of `value` in big-endian order
+    ///
+    /// # Panics
+    ///
+    /// When `bit_count` is larger than `max_bits`
+    fn write_bits(&mut self, value: u128, bit_count: usize, max_bits: usize) {
+        assert!(
+            bit_count <= max_bits,
+            "Bit count {bit_count} is too big for a {max_bits} bit value"
+        );
+
+        // Whole bytes appended at the end can be copied directly
+        if bit_count.is_multiple_of(8)
+            && self.pointer.is_multiple_of(8)
+            && self.pointer / 8 == self.bytes.len()
+        {
+            let bytes = value.to_be_bytes();
+            self.bytes.extend(&bytes[16 - bit_count / 8..]);
+            self.pointer += bit_count;
+            return;
+        }
+
+        for i in (0..bit_count).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    /// Write the lowest `bit_count` bits of `value`, panics when `bit_count` is more than 8
+    pub fn write_u8(&mut self, value: u8, bit_count: usize) {
+        self.write_bits(value as u128, bit_count, 8)
+    }
+
+    /// Write the lowest `bit_count` bits of `value`, panics when `bit_count` is more than 16
+    pub fn write_u16(&mut self, value: u16, bit_count: usize) {
+        self.write_bits(value as u128, bit_count, 16)
+    }
+
+    /// Write the lowest `bit_count` bits of `value`, panics when `bit_count` is more than 32
+    pub fn write_u32(&mut self, value: u32, bit_count: usize) {
+        self.write_bits(value as u128, bit_count, 32)
+    }
+
+    /// Write the lowest `bit_count` bits of `value`, panics when `bit_count` is more than 64
+    pub fn write_u64(&mut self, value: u64, bit_count: usize) {
+        self.write_bits(value as u128, bit_count, 64)
+    }
+
+    /// Write the lowest `bit_count` bits of `value`, panics when `bit_count` is more than 128
+    pub fn write_u128(&mut self, value: u128, bit_count: usize) {
+        self.write_bits(value, bit_count, 128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitReader, BitWriter};
+
+    #[test]
+    fn write_bits_works() {
+        let mut w = BitWriter::new();
+        w.write_bit(true);
+        w.write_u8(0b0010, 4);
+        w.write_u16(0b101, 3);
+        w.write_u32(0xabcdef, 24);
+
+        assert_eq!(w.get_pointer(), 32);
+        assert_eq!(w.as_bytes(), &[0b1001_0101, 0xab, 0xcd, 0xef]);
+    }
+
+    #[test]
+    fn overwriting_works() {
+        let mut w = BitWriter::new();
+        w.write_u16(0, 16);
+        w.write_bytes(&[1, 2, 3]);
+        let end = w.get_pointer();
+
+        w.set_pointer(0);
+        w.write_u16(3, 16);
+        w.set_pointer(end);
+        w.write_u8(0xff, 8);
+
+        assert_eq!(w.into_bytes(), vec![0, 3, 1, 2, 3, 0xff]);
+    }
+
+    #[test]
+    fn unaligned_bytes_work() {
+        let mut w = BitWriter::new();
+        w.write_u8(0, 4);
+        w.write_bytes(&[0xff, 0x0f]);
+        w.skip(4);
+
+        assert_eq!(w.as_bytes(), &[0x0f, 0xf0, 0xf0]);
+    }
+
+    #[test]
+    fn reads_back_what_was_written() {
+        let mut w = BitWriter::new();
+        w.write_u8(5, 3);
+        w.write_u128(u128::MAX - 1, 128);
+        w.write_u64(1 << 40, 41);
+
+        let bytes = w.into_bytes();
+        let mut r = BitReader::from_bytes(&bytes);
+        assert_eq!(r.next_u8(3).unwrap(), 5);
+        assert_eq!(r.next_u128(128).unwrap(), u128::MAX - 1);
+        assert_eq!(r.next_u64(41).unwrap(), 1 << 40);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_bits_panics() {
+        BitWriter::new().write_u16(0, 17);
+    }
+}
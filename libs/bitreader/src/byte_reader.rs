@@ -0,0 +1,112 @@
+use crate::error::BitError;
+
+/// A cursor over a slice of bytes for reading byte aligned big-endian values
+///
+/// Most fields in network protocols are whole bytes, reading these directly is a lot faster than
+/// going through them bit by bit with [`crate::BitReader`].
+pub struct ByteReader<'n> {
+    pointer: usize,
+    bytes: &'n [u8],
+}
+
+impl<'n> ByteReader<'n> {
+    pub fn from_bytes(bytes: &[u8]) -> ByteReader<'_> {
+        ByteReader { bytes, pointer: 0 }
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        self.pointer += count;
+    }
+
+    pub fn set_pointer(&mut self, offset: usize) {
+        self.pointer = offset;
+    }
+
+    /// Offset of the next byte to read
+    pub fn get_pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// Amount of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.pointer)
+    }
+
+    /// Return the next `count` bytes without copying them
+    pub fn next_bytes(&mut self, count: usize) -> Result<&'n [u8], BitError> {
+        let end = self
+            .pointer
+            .checked_add(count)
+            .ok_or(BitError::EndOfBytes)?;
+        let bytes = self
+            .bytes
+            .get(self.pointer..end)
+            .ok_or(BitError::EndOfBytes)?;
+        self.pointer = end;
+        Ok(bytes)
+    }
+
+    fn next_array<const N: usize>(&mut self) -> Result<[u8; N], BitError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.next_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn next_u8(&mut self) -> Result<u8, BitError> {
+        let byte = *self.bytes.get(self.pointer).ok_or(BitError::EndOfBytes)?;
+        self.pointer += 1;
+        Ok(byte)
+    }
+
+    pub fn next_u16(&mut self) -> Result<u16, BitError> {
+        Ok(u16::from_be_bytes(self.next_array()?))
+    }
+
+    pub fn next_u32(&mut self) -> Result<u32, BitError> {
+        Ok(u32::from_be_bytes(self.next_array()?))
+    }
+
+    pub fn next_u64(&mut self) -> Result<u64, BitError> {
+        Ok(u64::from_be_bytes(self.next_array()?))
+    }
+
+    pub fn next_u128(&mut self) -> Result<u128, BitError> {
+        Ok(u128::from_be_bytes(self.next_array()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteReader;
+
+    #[test]
+    fn next_values_work() {
+        let data: Vec<u8> = (1..=31).collect();
+        let mut r = ByteReader::from_bytes(&data);
+
+        assert_eq!(r.next_u8().unwrap(), 1);
+        assert_eq!(r.next_u16().unwrap(), 0x0203);
+        assert_eq!(r.next_u32().unwrap(), 0x04050607);
+        assert_eq!(r.next_u64().unwrap(), 0x08090a0b0c0d0e0f);
+        assert_eq!(
+            r.next_u128().unwrap(),
+            u128::from_be_bytes(data[15..31].try_into().unwrap())
+        );
+        assert_eq!(r.remaining(), 0);
+        assert!(r.next_u8().is_err());
+    }
+
+    #[test]
+    fn next_bytes_works() {
+        let data = vec![1, 2, 3];
+        let mut r = ByteReader::from_bytes(&data);
+
+        assert_eq!(r.next_bytes(2).unwrap(), &[1, 2]);
+        assert!(r.next_bytes(2).is_err());
+        assert_eq!(r.get_pointer(), 2);
+
+        r.set_pointer(usize::MAX);
+        assert!(r.next_bytes(1).is_err());
+        assert_eq!(r.remaining(), 0);
+    }
+}
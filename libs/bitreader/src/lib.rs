@@ -2,6 +2,12 @@ pub mod error;
 
 use error::BitError;
 
+mod bit_writer;
+pub use bit_writer::BitWriter;
+
+mod byte_reader;
+pub use byte_reader::ByteReader;
+
 pub struct BitReader<'n> {
     pointer: usize,
     bytes: &'n [u8],
@@ -9,7 +15,7 @@ pub struct BitReader<'n> {
 
 /// An abstraction over a vector of bytes to easily navigate over the bits within the vector of bytes.
 impl<'n> BitReader<'n> {
-    pub fn from_bytes(bytes: &[u8]) -> BitReader {
+    pub fn from_bytes(bytes: &[u8]) -> BitReader<'_> {
         BitReader { bytes, pointer: 0 }
     }
 
@@ -33,68 +39,33 @@ impl<'n> BitReader<'n> {
         };
 
         // Fetch first bit by ANDing a bitmask with the position of the pointer
-        let bit = (byte & 1 << 7 - ((self.pointer) % 8)) > 0;
+        let bit = (byte & 1 << (7 - (self.pointer % 8))) > 0;
         self.pointer += 1;
 
-        return Ok(bit);
+        Ok(bit)
     }
 
-    pub fn next_u8(&mut self, bit_count: usize) -> Result<u8, BitError> {
-        if bit_count > 16 {
-            return Err(BitError::InvalidCountSize);
-        }
-
-        let mut value = 0;
-        for i in self.pointer..self.pointer + bit_count {
-            let byte = match self.bytes.get(self.pointer / 8) {
-                Some(x) => x,
-                None => return Err(BitError::EndOfBytes),
-            };
-            let shift = 7 - (i % 8);
-            let bit = (byte >> shift) as u8 & 1;
-            value = (value << 1) | bit;
-        }
-
-        self.pointer += bit_count;
-
-        Ok(value)
+    /// Amount of bits left to read
+    pub fn remaining_bits(&self) -> usize {
+        (self.bytes.len() * 8).saturating_sub(self.pointer)
     }
 
-    pub fn next_u16(&mut self, bit_count: usize) -> Result<u16, BitError> {
-        if bit_count > 16 {
+    /// Read `bit_count` bits as a big-endian value of at most `max_bits` bits
+    ///
+    /// Nothing is read when an error is returned so the pointer stays where it was.
+    fn next_bits(&mut self, bit_count: usize, max_bits: usize) -> Result<u128, BitError> {
+        if bit_count > max_bits {
             return Err(BitError::InvalidCountSize);
         }
-
-        let mut value = 0;
-        for i in self.pointer..self.pointer + bit_count {
-            let byte = match self.bytes.get(self.pointer / 8) {
-                Some(x) => x,
-                None => return Err(BitError::EndOfBytes),
-            };
-            let shift = 7 - (i % 8);
-            let bit = (byte >> shift) as u16 & 1;
-            value = (value << 1) | bit;
-        }
-
-        self.pointer += bit_count;
-
-        Ok(value)
-    }
-
-    pub fn next_u32(&mut self, bit_count: usize) -> Result<u32, BitError> {
-        if bit_count > 16 {
-            return Err(BitError::InvalidCountSize);
+        if bit_count > self.remaining_bits() {
+            return Err(BitError::EndOfBytes);
         }
 
         let mut value = 0;
         for i in self.pointer..self.pointer + bit_count {
-            let byte = match self.bytes.get(self.pointer / 8) {
-                Some(x) => x,
-                None => return Err(BitError::EndOfBytes),
-            };
             let shift = 7 - (i % 8);
-            let bit = (byte >> shift) as u32 & 1;
-            value = (value << 1) | bit;
+            let bit = (self.bytes[i / 8] >> shift) & 1;
+            value = (value << 1) | bit as u128;
         }
 
         self.pointer += bit_count;
@@ -102,53 +73,32 @@ impl<'n> BitReader<'n> {
         Ok(value)
     }
 
-    // Not used but implented anyways
-    pub fn _next_u64(&mut self, bit_count: usize) -> Result<u64, BitError> {
-        if bit_count > 16 {
-            return Err(BitError::InvalidCountSize);
-        }
+    pub fn next_u8(&mut self, bit_count: usize) -> Result<u8, BitError> {
+        Ok(self.next_bits(bit_count, 8)? as u8)
+    }
 
-        let mut value = 0;
-        for i in self.pointer..self.pointer + bit_count {
-            let byte = match self.bytes.get(self.pointer / 8) {
-                Some(x) => x,
-                None => return Err(BitError::EndOfBytes),
-            };
-            let shift = 7 - (i % 8);
-            let bit = (byte >> shift) as u64 & 1;
-            value = (value << 1) | bit;
-        }
+    pub fn next_u16(&mut self, bit_count: usize) -> Result<u16, BitError> {
+        Ok(self.next_bits(bit_count, 16)? as u16)
+    }
 
-        self.pointer += bit_count;
+    pub fn next_u32(&mut self, bit_count: usize) -> Result<u32, BitError> {
+        Ok(self.next_bits(bit_count, 32)? as u32)
+    }
 
-        Ok(value)
+    pub fn next_u64(&mut self, bit_count: usize) -> Result<u64, BitError> {
+        Ok(self.next_bits(bit_count, 64)? as u64)
     }
 
     pub fn next_u128(&mut self, bit_count: usize) -> Result<u128, BitError> {
-        if bit_count > 16 {
-            return Err(BitError::InvalidCountSize);
-        }
-
-        let mut value = 0;
-        for i in self.pointer..self.pointer + bit_count {
-            let byte = match self.bytes.get(self.pointer / 8) {
-                Some(x) => x,
-                None => return Err(BitError::EndOfBytes),
-            };
-            let shift = 7 - (i % 8);
-            let bit = (byte >> shift) as u128 & 1;
-            value = (value << 1) | bit;
-        }
-
-        self.pointer += bit_count;
-
-        Ok(value)
+        self.next_bits(bit_count, 128)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::BitReader;
+    use rand::{Rng, XorShift};
+
+    use crate::{error::BitError, BitReader};
 
     #[test]
     fn next_bit_works() {
@@ -175,4 +125,102 @@ mod tests {
         let mut r = BitReader::from_bytes(&data);
         assert_eq!(r.next_u8(8).unwrap(), 20)
     }
+
+    #[test]
+    fn wide_reads_work() {
+        let data = vec![0, 0, 0x0e, 0x10, 0xc0, 0x00, 0x02, 0x01];
+        let mut r = BitReader::from_bytes(&data);
+        assert_eq!(r.next_u32(32).unwrap(), 3600);
+        assert_eq!(r.next_u16(16).unwrap(), 0xc000);
+        assert_eq!(r.next_u16(16).unwrap(), 0x0201);
+        assert!(r.next_u8(1).is_err());
+    }
+
+    #[test]
+    fn reads_spanning_bytes_work() {
+        let data = vec![0b1010_1100, 0b0011_0101, 0b1111_0000];
+        let mut r = BitReader::from_bytes(&data);
+
+        r.skip(4);
+        assert_eq!(r.next_u8(8).unwrap(), 0b1100_0011);
+        assert_eq!(r.next_u16(10).unwrap(), 0b01_0111_1100);
+        assert_eq!(r.remaining_bits(), 2);
+    }
+
+    #[test]
+    fn errors_do_not_move_the_pointer() {
+        let data = vec![1, 2, 3];
+        let mut r = BitReader::from_bytes(&data);
+        r.skip(4);
+
+        assert!(matches!(r.next_u8(9), Err(BitError::InvalidCountSize)));
+        assert!(matches!(r.next_u32(21), Err(BitError::EndOfBytes)));
+        assert_eq!(r.get_pointer(), 4);
+        assert_eq!(r.next_u32(20).unwrap(), 0x10203);
+        assert_eq!(r.remaining_bits(), 0);
+        assert!(matches!(r.next_bit(), Err(BitError::EndOfBytes)));
+
+        r.skip(8);
+        assert_eq!(r.remaining_bits(), 0);
+    }
+
+    /// Random buffers of 17 bytes so a full u128 can be read from any bit offset within the first byte
+    fn random_buffers() -> impl Iterator<Item = [u8; 17]> {
+        let mut rng = XorShift::new(500);
+        (0..1000).map(move |_| {
+            let mut buffer = [0; 17];
+            for byte in buffer.iter_mut() {
+                *byte = rng.next_u16() as u8;
+            }
+            buffer
+        })
+    }
+
+    #[test]
+    fn aligned_reads_match_from_be_bytes() {
+        for buffer in random_buffers() {
+            let mut r = BitReader::from_bytes(&buffer);
+            assert_eq!(r.next_u8(8).unwrap(), buffer[0]);
+            assert_eq!(
+                r.next_u16(16).unwrap(),
+                u16::from_be_bytes([buffer[1], buffer[2]])
+            );
+            assert_eq!(
+                r.next_u32(32).unwrap(),
+                u32::from_be_bytes(buffer[3..7].try_into().unwrap())
+            );
+            assert_eq!(
+                r.next_u64(64).unwrap(),
+                u64::from_be_bytes(buffer[7..15].try_into().unwrap())
+            );
+
+            let mut r = BitReader::from_bytes(&buffer);
+            assert_eq!(
+                r.next_u128(128).unwrap(),
+                u128::from_be_bytes(buffer[..16].try_into().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn unaligned_reads_match_shifted_values() {
+        for (i, buffer) in random_buffers().enumerate() {
+            let offset = i % 8;
+            let bit_count = 1 + i % 120;
+
+            let window = u128::from_be_bytes(buffer[..16].try_into().unwrap());
+            let expected = (window << offset) >> (128 - bit_count);
+
+            let mut r = BitReader::from_bytes(&buffer);
+            r.skip(offset);
+            assert_eq!(r.next_u128(bit_count).unwrap(), expected);
+            assert_eq!(r.get_pointer(), offset + bit_count);
+
+            if bit_count <= 32 {
+                let mut r = BitReader::from_bytes(&buffer);
+                r.skip(offset);
+                assert_eq!(r.next_u32(bit_count).unwrap() as u128, expected);
+            }
+        }
+    }
 }
@@ -0,0 +1,353 @@
+//! Arbitrary precision unsigned integers with the modular arithmetic needed to verify signatures
+//!
+//! Only public values are handled, so nothing here tries to run in constant time.
+
+use core::cmp::Ordering;
+
+/// An unsigned integer as little endian 32 bit limbs without trailing zero limbs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u32>) -> BigUint {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    pub fn zero() -> BigUint {
+        BigUint { limbs: vec![] }
+    }
+
+    pub fn from_u32(value: u32) -> BigUint {
+        BigUint::from_limbs(vec![value])
+    }
+
+    pub fn from_be_bytes(bytes: &[u8]) -> BigUint {
+        let limbs = bytes
+            .rchunks(4)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u32, |limb, &byte| (limb << 8) | byte as u32)
+            })
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    pub fn from_le_bytes(bytes: &[u8]) -> BigUint {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        BigUint::from_be_bytes(&bytes)
+    }
+
+    /// Big endian bytes left padded with zeros to `length`, which must fit the value
+    pub fn to_be_bytes(&self, length: usize) -> Vec<u8> {
+        let mut bytes = self.to_le_bytes(length);
+        bytes.reverse();
+        bytes
+    }
+
+    /// Little endian bytes right padded with zeros to `length`, which must fit the value
+    pub fn to_le_bytes(&self, length: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .limbs
+            .iter()
+            .flat_map(|limb| limb.to_le_bytes())
+            .collect();
+        assert!(bytes[length.min(bytes.len())..]
+            .iter()
+            .all(|&byte| byte == 0));
+        bytes.resize(length, 0);
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Amount of significant bits
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(last) => self.limbs.len() * 32 - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, index: usize) -> bool {
+        self.limbs
+            .get(index / 32)
+            .is_some_and(|limb| limb >> (index % 32) & 1 == 1)
+    }
+
+    pub fn shl(&self, bits: usize) -> BigUint {
+        let mut limbs = vec![0; bits / 32];
+        limbs.extend(shl_limbs(&self.limbs, (bits % 32) as u32));
+        BigUint::from_limbs(limbs)
+    }
+
+    pub fn shr(&self, bits: usize) -> BigUint {
+        let limbs = self.limbs.get(bits / 32..).unwrap_or(&[]);
+        BigUint::from_limbs(shr_limbs(limbs, (bits % 32) as u32))
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let length = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(length + 1);
+        let mut carry = 0u64;
+        for i in 0..length {
+            let sum = *self.limbs.get(i).unwrap_or(&0) as u64
+                + *other.limbs.get(i).unwrap_or(&0) as u64
+                + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::from_limbs(limbs)
+    }
+
+    /// `self - other`, which must not be negative
+    pub fn sub(&self, other: &BigUint) -> BigUint {
+        assert!(*self >= *other, "Subtraction underflow");
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let difference = limb as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            limbs.push(difference as u32);
+            borrow = (difference < 0) as i64;
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    /// `self % modulus` using long division: Knuth, TAOCP Vol. 2, 4.3.1, Algorithm D
+    pub fn rem(&self, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "Division by zero");
+        if *self < *modulus {
+            return self.clone();
+        }
+
+        if let [divisor] = modulus.limbs[..] {
+            let remainder = self.limbs.iter().rev().fold(0u64, |remainder, &limb| {
+                ((remainder << 32) | limb as u64) % divisor as u64
+            });
+            return BigUint::from_u32(remainder as u32);
+        }
+
+        // Normalize so the top bit of the divisor is set, which keeps the estimated quotient digits close
+        let shift = modulus.limbs.last().unwrap().leading_zeros();
+        let v = shl_limbs(&modulus.limbs, shift);
+        let mut u = shl_limbs(&self.limbs, shift);
+        u.push(0);
+        if u.len() == self.limbs.len() {
+            u.push(0);
+        }
+
+        let n = v.len();
+        for j in (0..u.len() - n).rev() {
+            let numerator = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+            let mut quotient = numerator / v[n - 1] as u64;
+            let mut remainder = numerator % v[n - 1] as u64;
+            while quotient > u32::MAX as u64
+                || quotient * v[n - 2] as u64 > (remainder << 32) | u[j + n - 2] as u64
+            {
+                quotient -= 1;
+                remainder += v[n - 1] as u64;
+                if remainder > u32::MAX as u64 {
+                    break;
+                }
+            }
+
+            let mut borrow = 0i64;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let product = quotient * v[i] as u64 + carry;
+                carry = product >> 32;
+                let difference = u[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+                u[i + j] = difference as u32;
+                borrow = (difference < 0) as i64;
+            }
+            let difference = u[j + n] as i64 - borrow - carry as i64;
+            u[j + n] = difference as u32;
+
+            // The estimate was one too large, add the divisor back
+            if difference < 0 {
+                let mut carry = 0u64;
+                for i in 0..n {
+                    let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                    u[i + j] = sum as u32;
+                    carry = sum >> 32;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u32);
+            }
+        }
+
+        BigUint::from_limbs(shr_limbs(&u[..n], shift))
+    }
+
+    /// `(self + other) % modulus` for operands already reduced
+    pub fn add_mod(&self, other: &BigUint, modulus: &BigUint) -> BigUint {
+        let sum = self.add(other);
+        if sum >= *modulus {
+            sum.sub(modulus)
+        } else {
+            sum
+        }
+    }
+
+    /// `(self - other) % modulus` for operands already reduced
+    pub fn sub_mod(&self, other: &BigUint, modulus: &BigUint) -> BigUint {
+        if self >= other {
+            self.sub(other)
+        } else {
+            self.add(modulus).sub(other)
+        }
+    }
+
+    pub fn mul_mod(&self, other: &BigUint, modulus: &BigUint) -> BigUint {
+        self.mul(other).rem(modulus)
+    }
+
+    /// `self ^ exponent % modulus` by square and multiply
+    pub fn pow_mod(&self, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        let base = self.rem(modulus);
+        let mut result = BigUint::from_u32(1).rem(modulus);
+        for i in (0..exponent.bits()).rev() {
+            result = result.mul_mod(&result, modulus);
+            if exponent.bit(i) {
+                result = result.mul_mod(&base, modulus);
+            }
+        }
+        result
+    }
+
+    /// Multiplicative inverse modulo a prime, from Fermat's little theorem
+    pub fn inv_mod(&self, prime: &BigUint) -> BigUint {
+        self.pow_mod(&prime.sub(&BigUint::from_u32(2)), prime)
+    }
+}
+
+fn shl_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = Vec::with_capacity(limbs.len() + 1);
+    let mut carry = 0;
+    for &limb in limbs {
+        shifted.push((limb << shift) | carry);
+        carry = limb >> (32 - shift);
+    }
+    if carry != 0 {
+        shifted.push(carry);
+    }
+    shifted
+}
+
+fn shr_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    (0..limbs.len())
+        .map(|i| (limbs[i] >> shift) | limbs.get(i + 1).map_or(0, |next| next << (32 - shift)))
+        .collect()
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::hex;
+
+    fn big(text: &str) -> BigUint {
+        BigUint::from_be_bytes(&hex(text))
+    }
+
+    #[test]
+    fn bytes_work() {
+        let value = big("0102030405");
+        assert_eq!(value.limbs, [0x02030405, 0x01]);
+        assert_eq!(value.to_be_bytes(7), hex("00000102030405"));
+        assert_eq!(BigUint::from_le_bytes(&hex("0504030201")), value);
+        assert_eq!(big("000000"), BigUint::zero());
+        assert_eq!(value.bits(), 33);
+        assert!(value.bit(32) && !value.bit(31) && value.bit(0));
+    }
+
+    #[test]
+    fn arithmetic_works() {
+        let a = big("ffffffffffffffffffffffff");
+        let b = big("01");
+
+        assert_eq!(a.add(&b), big("01000000000000000000000000"));
+        assert_eq!(a.add(&b).sub(&b), a);
+        assert_eq!(
+            a.mul(&a),
+            big("fffffffffffffffffffffffe000000000000000000000001")
+        );
+        assert!(a > b && big("0100000000") > a.rem(&big("0100000000")));
+        assert_eq!(b.shl(100), big("10000000000000000000000000"));
+        assert_eq!(b.shl(100).shr(99), big("02"));
+        assert_eq!(a.shr(96), BigUint::zero());
+    }
+
+    #[test]
+    fn rem_works() {
+        // Checked against Python's arbitrary precision integers
+        let dividend = big("d3a1c6f0b9e2847a5f1e3c9b7d6a2e4f8c1b3a5d7e9f0c2b4a6d8e1f3c5b7a9d");
+        assert_eq!(dividend.rem(&big("07")), BigUint::zero());
+        assert_eq!(
+            dividend.rem(&big("8000000000000001")),
+            big("11a1d44c65b1f7bf")
+        );
+        assert_eq!(
+            dividend.rem(&big("1234567890abcdef1234567890abcdef")),
+            big("0a83f9268f5ca4875452c29310a0bccf")
+        );
+
+        let prime = big("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+        assert_eq!(dividend.rem(&prime), dividend);
+        assert_eq!(
+            dividend.mul_mod(&dividend, &prime),
+            big("99c52c5b653e87074a61a332ccbf3ec1cd5ee09f3d314bd4f35b9752986483bb")
+        );
+    }
+
+    #[test]
+    fn pow_mod_works() {
+        let prime = big("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+        let value = big("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296");
+
+        assert_eq!(value.mul_mod(&value.inv_mod(&prime), &prime), big("01"));
+        assert_eq!(big("03").pow_mod(&big("05"), &big("07")), big("05"));
+    }
+}
@@ -0,0 +1,267 @@
+//! ECDSA signature verification on P-256 and P-384: https://www.secg.org/sec1-v2.pdf#subsubsection.4.1.4
+//!
+//! Keys are the uncompressed coordinates and signatures the integers r and s, each as big endian
+//! bytes of the size of the curve, as used by DNSSEC: https://datatracker.ietf.org/doc/html/rfc6605#section-4
+
+use crate::{bigint::BigUint, sha256, sha384};
+
+/// A short Weierstrass curve y² = x³ + ax + b over the prime field of `p`, with a generator of order `n`
+struct Curve {
+    p: BigUint,
+    a: BigUint,
+    b: BigUint,
+    n: BigUint,
+    generator: Point,
+    /// Bytes of a coordinate or scalar
+    size: usize,
+}
+
+/// A point in Jacobian coordinates (X / Z², Y / Z³), Z is zero for the point at infinity
+#[derive(Clone)]
+struct Point {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+}
+
+impl Point {
+    fn infinity() -> Point {
+        Point {
+            x: BigUint::from_u32(1),
+            y: BigUint::from_u32(1),
+            z: BigUint::zero(),
+        }
+    }
+
+    fn affine(x: BigUint, y: BigUint) -> Point {
+        Point {
+            x,
+            y,
+            z: BigUint::from_u32(1),
+        }
+    }
+}
+
+fn hex(text: &str) -> BigUint {
+    let bytes: Vec<u8> = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect();
+    BigUint::from_be_bytes(&bytes)
+}
+
+impl Curve {
+    /// https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-186.pdf#subsubsection.3.2.1.3
+    fn p256() -> Curve {
+        let p = hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+        Curve {
+            a: p.sub(&BigUint::from_u32(3)),
+            b: hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+            n: hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+            generator: Point::affine(
+                hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+                hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+            ),
+            p,
+            size: 32,
+        }
+    }
+
+    /// https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-186.pdf#subsubsection.3.2.1.4
+    fn p384() -> Curve {
+        let p = hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff");
+        Curve {
+            a: p.sub(&BigUint::from_u32(3)),
+            b: hex("b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef"),
+            n: hex("ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973"),
+            generator: Point::affine(
+                hex("aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7"),
+                hex("3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f"),
+            ),
+            p,
+            size: 48,
+        }
+    }
+
+    fn contains(&self, x: &BigUint, y: &BigUint) -> bool {
+        let p = &self.p;
+        let right = x
+            .mul_mod(x, p)
+            .mul_mod(x, p)
+            .add_mod(&self.a.mul_mod(x, p), p)
+            .add_mod(&self.b, p);
+        x < p && y < p && y.mul_mod(y, p) == right
+    }
+
+    /// https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html#doubling-dbl-1998-cmo-2
+    fn double(&self, point: &Point) -> Point {
+        let p = &self.p;
+        if point.z.is_zero() || point.y.is_zero() {
+            return Point::infinity();
+        }
+
+        let yy = point.y.mul_mod(&point.y, p);
+        let zz = point.z.mul_mod(&point.z, p);
+        let s = BigUint::from_u32(4).mul_mod(&point.x, p).mul_mod(&yy, p);
+        let m = BigUint::from_u32(3)
+            .mul_mod(&point.x.mul_mod(&point.x, p), p)
+            .add_mod(&self.a.mul_mod(&zz.mul_mod(&zz, p), p), p);
+        let x = m.mul_mod(&m, p).sub_mod(&s.add_mod(&s, p), p);
+        let y = m
+            .mul_mod(&s.sub_mod(&x, p), p)
+            .sub_mod(&BigUint::from_u32(8).mul_mod(&yy.mul_mod(&yy, p), p), p);
+        let z = BigUint::from_u32(2)
+            .mul_mod(&point.y, p)
+            .mul_mod(&point.z, p);
+
+        Point { x, y, z }
+    }
+
+    /// https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html#addition-add-1998-cmo-2
+    fn add(&self, a: &Point, b: &Point) -> Point {
+        let p = &self.p;
+        if a.z.is_zero() {
+            return b.clone();
+        }
+        if b.z.is_zero() {
+            return a.clone();
+        }
+
+        let za = a.z.mul_mod(&a.z, p);
+        let zb = b.z.mul_mod(&b.z, p);
+        let u1 = a.x.mul_mod(&zb, p);
+        let u2 = b.x.mul_mod(&za, p);
+        let s1 = a.y.mul_mod(&zb, p).mul_mod(&b.z, p);
+        let s2 = b.y.mul_mod(&za, p).mul_mod(&a.z, p);
+
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double(a)
+            } else {
+                Point::infinity()
+            };
+        }
+
+        let h = u2.sub_mod(&u1, p);
+        let r = s2.sub_mod(&s1, p);
+        let hh = h.mul_mod(&h, p);
+        let hhh = hh.mul_mod(&h, p);
+        let v = u1.mul_mod(&hh, p);
+        let x = r
+            .mul_mod(&r, p)
+            .sub_mod(&hhh, p)
+            .sub_mod(&v.add_mod(&v, p), p);
+        let y = r
+            .mul_mod(&v.sub_mod(&x, p), p)
+            .sub_mod(&s1.mul_mod(&hhh, p), p);
+        let z = a.z.mul_mod(&b.z, p).mul_mod(&h, p);
+
+        Point { x, y, z }
+    }
+
+    fn mul(&self, point: &Point, scalar: &BigUint) -> Point {
+        let mut result = Point::infinity();
+        for i in (0..scalar.bits()).rev() {
+            result = self.double(&result);
+            if scalar.bit(i) {
+                result = self.add(&result, point);
+            }
+        }
+        result
+    }
+
+    /// The affine x coordinate, none for the point at infinity
+    fn affine_x(&self, point: &Point) -> Option<BigUint> {
+        if point.z.is_zero() {
+            return None;
+        }
+        let z_inverse = point.z.inv_mod(&self.p);
+        Some(
+            point
+                .x
+                .mul_mod(&z_inverse.mul_mod(&z_inverse, &self.p), &self.p),
+        )
+    }
+
+    fn verify(&self, public_key: &[u8], digest: &[u8], signature: &[u8]) -> bool {
+        if public_key.len() != 2 * self.size || signature.len() != 2 * self.size {
+            return false;
+        }
+
+        let (x, y) = public_key.split_at(self.size);
+        let (x, y) = (BigUint::from_be_bytes(x), BigUint::from_be_bytes(y));
+        if !self.contains(&x, &y) {
+            return false;
+        }
+
+        let (r, s) = signature.split_at(self.size);
+        let (r, s) = (BigUint::from_be_bytes(r), BigUint::from_be_bytes(s));
+        if r.is_zero() || s.is_zero() || r >= self.n || s >= self.n {
+            return false;
+        }
+
+        // The hashes are as long as the order of the curves, so no bits have to be truncated
+        let e = BigUint::from_be_bytes(digest).rem(&self.n);
+        let w = s.inv_mod(&self.n);
+        let u1 = e.mul_mod(&w, &self.n);
+        let u2 = r.mul_mod(&w, &self.n);
+
+        let point = self.add(
+            &self.mul(&self.generator, &u1),
+            &self.mul(&Point::affine(x, y), &u2),
+        );
+        self.affine_x(&point).is_some_and(|x| x.rem(&self.n) == r)
+    }
+}
+
+/// Verify a signature over the SHA-256 hash of `message` with a P-256 key, used by algorithm 13
+pub fn verify_p256_sha256(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    Curve::p256().verify(public_key, &sha256(message), signature)
+}
+
+/// Verify a signature over the SHA-384 hash of `message` with a P-384 key, used by algorithm 14
+pub fn verify_p384_sha384(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    Curve::p384().verify(public_key, &sha384(message), signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::hex;
+
+    #[test]
+    fn generators_are_on_their_curves() {
+        for curve in [Curve::p256(), Curve::p384()] {
+            let generator = &curve.generator;
+            assert!(curve.contains(&generator.x, &generator.y));
+            assert!(curve.mul(generator, &curve.n).z.is_zero());
+        }
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc6979#appendix-A.2.5
+    #[test]
+    fn verify_p256_sha256_works() {
+        let public_key = hex("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299");
+        let signature = hex("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+
+        assert!(verify_p256_sha256(&public_key, b"sample", &signature));
+        assert!(!verify_p256_sha256(&public_key, b"samples", &signature));
+
+        let mut off_curve = public_key.clone();
+        off_curve[63] ^= 1;
+        assert!(!verify_p256_sha256(&off_curve, b"sample", &signature));
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc6979#appendix-A.2.6
+    #[test]
+    fn verify_p384_sha384_works() {
+        let public_key = hex("ec3a4e415b4e19a4568618029f427fa5da9a8bc4ae92e02e06aae5286b300c64def8f0ea9055866064a254515480bc138015d9b72d7d57244ea8ef9ac0c621896708a59367f9dfb9f54ca84b3f1c9db1288b231c3ae0d4fe7344fd2533264720");
+        let signature = hex("94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8");
+
+        assert!(verify_p384_sha384(&public_key, b"sample", &signature));
+
+        let mut signature = signature;
+        signature[0] ^= 1;
+        assert!(!verify_p384_sha384(&public_key, b"sample", &signature));
+    }
+}
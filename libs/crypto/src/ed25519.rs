@@ -0,0 +1,269 @@
+//! Ed25519 signatures: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1
+
+use crate::{bigint::BigUint, sha512};
+
+/// A point in extended coordinates (X / Z, Y / Z) with X * Y = T * Z
+#[derive(Clone)]
+struct Point {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+    t: BigUint,
+}
+
+/// The twisted Edwards curve -x² + y² = 1 + dx²y² over the field of 2^255 - 19
+struct Curve {
+    p: BigUint,
+    d: BigUint,
+    /// Order of the base point
+    l: BigUint,
+    base: Point,
+}
+
+impl Curve {
+    fn new() -> Curve {
+        let p = BigUint::from_u32(1).shl(255).sub(&BigUint::from_u32(19));
+        let d = p
+            .sub(&BigUint::from_u32(121665))
+            .mul_mod(&BigUint::from_u32(121666).inv_mod(&p), &p);
+        let l = BigUint::from_u32(1).shl(252).add(&BigUint::from_be_bytes(&[
+            0x14, 0xde, 0xf9, 0xde, 0xa2, 0xf7, 0x9c, 0xd6, 0x58, 0x12, 0x63, 0x1a, 0x5c, 0xf5,
+            0xd3, 0xed,
+        ]));
+
+        let mut curve = Curve {
+            base: Point {
+                x: BigUint::zero(),
+                y: BigUint::zero(),
+                z: BigUint::zero(),
+                t: BigUint::zero(),
+            },
+            p,
+            d,
+            l,
+        };
+
+        // The base point has y = 4/5 and a positive x
+        let y = BigUint::from_u32(4).mul_mod(&BigUint::from_u32(5).inv_mod(&curve.p), &curve.p);
+        curve.base = curve.decode(&y.to_le_bytes(32)).unwrap();
+        curve
+    }
+
+    fn identity() -> Point {
+        Point {
+            x: BigUint::zero(),
+            y: BigUint::from_u32(1),
+            z: BigUint::from_u32(1),
+            t: BigUint::zero(),
+        }
+    }
+
+    /// Decode a point from the little endian y coordinate and the sign of x: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.3
+    fn decode(&self, bytes: &[u8]) -> Option<Point> {
+        let p = &self.p;
+        let mut bytes = <[u8; 32]>::try_from(bytes).ok()?;
+        let sign = bytes[31] >> 7 == 1;
+        bytes[31] &= 0x7f;
+
+        let y = BigUint::from_le_bytes(&bytes);
+        if y >= *p {
+            return None;
+        }
+
+        // x² = (y² - 1) / (dy² + 1), the square root is found with a single exponentiation
+        let one = BigUint::from_u32(1);
+        let yy = y.mul_mod(&y, p);
+        let u = yy.sub_mod(&one, p);
+        let v = self.d.mul_mod(&yy, p).add_mod(&one, p);
+        let v3 = v.mul_mod(&v, p).mul_mod(&v, p);
+        let v7 = v3.mul_mod(&v3, p).mul_mod(&v, p);
+        let mut x = u.mul_mod(&v3, p).mul_mod(
+            &u.mul_mod(&v7, p)
+                .pow_mod(&p.sub(&BigUint::from_u32(5)).shr(3), p),
+            p,
+        );
+
+        let vxx = v.mul_mod(&x.mul_mod(&x, p), p);
+        if vxx != u {
+            if vxx != p.sub(&u).rem(p) {
+                return None;
+            }
+            let sqrt_minus_one = BigUint::from_u32(2).pow_mod(&p.sub(&one).shr(2), p);
+            x = x.mul_mod(&sqrt_minus_one, p);
+        }
+
+        if x.is_zero() && sign {
+            return None;
+        }
+        if x.bit(0) != sign {
+            x = p.sub(&x);
+        }
+
+        let t = x.mul_mod(&y, p);
+        Some(Point { x, y, z: one, t })
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.4
+    fn add(&self, a: &Point, b: &Point) -> Point {
+        let p = &self.p;
+        let two = BigUint::from_u32(2);
+
+        let pa = a.y.sub_mod(&a.x, p).mul_mod(&b.y.sub_mod(&b.x, p), p);
+        let pb = a.y.add_mod(&a.x, p).mul_mod(&b.y.add_mod(&b.x, p), p);
+        let c = a.t.mul_mod(&two, p).mul_mod(&self.d, p).mul_mod(&b.t, p);
+        let d = a.z.mul_mod(&two, p).mul_mod(&b.z, p);
+        let e = pb.sub_mod(&pa, p);
+        let f = d.sub_mod(&c, p);
+        let g = d.add_mod(&c, p);
+        let h = pb.add_mod(&pa, p);
+
+        Point {
+            x: e.mul_mod(&f, p),
+            y: g.mul_mod(&h, p),
+            z: f.mul_mod(&g, p),
+            t: e.mul_mod(&h, p),
+        }
+    }
+
+    fn mul(&self, point: &Point, scalar: &BigUint) -> Point {
+        let mut result = Curve::identity();
+        for i in (0..scalar.bits()).rev() {
+            result = self.add(&result, &result);
+            if scalar.bit(i) {
+                result = self.add(&result, point);
+            }
+        }
+        result
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.2
+    fn encode(&self, point: &Point) -> Vec<u8> {
+        let p = &self.p;
+        let z = point.z.inv_mod(p);
+        let x = point.x.mul_mod(&z, p);
+        let mut bytes = point.y.mul_mod(&z, p).to_le_bytes(32);
+        bytes[31] |= (x.bit(0) as u8) << 7;
+        bytes
+    }
+
+    fn equal(&self, a: &Point, b: &Point) -> bool {
+        let p = &self.p;
+        a.x.mul_mod(&b.z, p) == b.x.mul_mod(&a.z, p) && a.y.mul_mod(&b.z, p) == b.y.mul_mod(&a.z, p)
+    }
+}
+
+/// The scalar and the prefix derived from a 32 byte secret key: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.5
+fn expand(secret_key: &[u8; 32]) -> (BigUint, [u8; 32]) {
+    let hashed = sha512(secret_key);
+    let mut scalar = <[u8; 32]>::try_from(&hashed[..32]).unwrap();
+    scalar[0] &= 0xf8;
+    scalar[31] &= 0x7f;
+    scalar[31] |= 0x40;
+    let prefix = <[u8; 32]>::try_from(&hashed[32..]).unwrap();
+    (BigUint::from_le_bytes(&scalar), prefix)
+}
+
+/// The 32 byte public key of a secret key
+pub fn public_key(secret_key: &[u8; 32]) -> Vec<u8> {
+    let curve = Curve::new();
+    let (scalar, _) = expand(secret_key);
+    curve.encode(&curve.mul(&curve.base, &scalar))
+}
+
+/// Sign `message` with a 32 byte secret key: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.6
+pub fn sign(secret_key: &[u8; 32], message: &[u8]) -> Vec<u8> {
+    let curve = Curve::new();
+    let (scalar, prefix) = expand(secret_key);
+    let public_key = curve.encode(&curve.mul(&curve.base, &scalar));
+
+    let mut hashed = prefix.to_vec();
+    hashed.extend_from_slice(message);
+    let r = BigUint::from_le_bytes(&sha512(&hashed)).rem(&curve.l);
+    let mut signature = curve.encode(&curve.mul(&curve.base, &r));
+
+    let mut hashed = signature.clone();
+    hashed.extend_from_slice(&public_key);
+    hashed.extend_from_slice(message);
+    let k = BigUint::from_le_bytes(&sha512(&hashed)).rem(&curve.l);
+
+    let s = r.add(&k.mul(&scalar)).rem(&curve.l);
+    signature.extend_from_slice(&s.to_le_bytes(32));
+    signature
+}
+
+/// Verify a signature over `message` with a 32 byte public key
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let curve = Curve::new();
+    if signature.len() != 64 {
+        return false;
+    }
+    let (encoded_r, encoded_s) = signature.split_at(32);
+
+    let (Some(a), Some(r)) = (curve.decode(public_key), curve.decode(encoded_r)) else {
+        return false;
+    };
+    let s = BigUint::from_le_bytes(encoded_s);
+    if s >= curve.l {
+        return false;
+    }
+
+    let mut hashed = encoded_r.to_vec();
+    hashed.extend_from_slice(public_key);
+    hashed.extend_from_slice(message);
+    let k = BigUint::from_le_bytes(&sha512(&hashed)).rem(&curve.l);
+
+    // [S]B = R + [k]A
+    curve.equal(
+        &curve.mul(&curve.base, &s),
+        &curve.add(&r, &curve.mul(&a, &k)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::hex;
+
+    #[test]
+    fn base_point_works() {
+        let curve = Curve::new();
+        assert_eq!(
+            curve.base.x.to_be_bytes(32),
+            hex("216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a")
+        );
+        assert!(curve.equal(&curve.mul(&curve.base, &curve.l), &Curve::identity()));
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc8032#section-7.1
+    #[test]
+    fn verify_works() {
+        let public_key = hex("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a");
+        let signature = hex("e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b");
+        assert!(verify(&public_key, b"", &signature));
+        assert!(!verify(&public_key, b"\x00", &signature));
+
+        let public_key = hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+        let signature = hex("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00");
+        assert!(verify(&public_key, b"\x72", &signature));
+
+        let mut forged = signature.clone();
+        forged[63] |= 0x80;
+        assert!(!verify(&public_key, b"\x72", &forged));
+    }
+
+    #[test]
+    fn sign_works() {
+        let secret_key: [u8; 32] =
+            hex("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")
+                .try_into()
+                .unwrap();
+        let public_key = public_key(&secret_key);
+        assert_eq!(
+            public_key,
+            hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")
+        );
+        let signature = sign(&secret_key, b"\x72");
+        assert_eq!(signature, hex("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"));
+        assert!(verify(&public_key, b"\x72", &signature));
+    }
+}
@@ -0,0 +1,25 @@
+//! The hashes and signature algorithms used by DNSSEC
+//!
+//! Verification only, except for Ed25519 signing which lets zones be signed for tests.
+//! Nothing here runs in constant time.
+
+mod bigint;
+
+pub mod ecdsa;
+pub mod ed25519;
+pub mod rsa;
+
+mod sha1;
+mod sha2;
+pub use sha1::sha1;
+pub use sha2::{sha256, sha384, sha512};
+
+#[cfg(test)]
+mod tests {
+    pub fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,151 @@
+//! RSASSA-PKCS1-v1_5 signature verification: https://datatracker.ietf.org/doc/html/rfc8017#section-8.2.2
+
+use crate::{bigint::BigUint, sha256};
+
+/// DER encoded DigestInfo prefix of SHA-256 hashes: https://datatracker.ietf.org/doc/html/rfc8017#section-9.2
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// Smallest and largest moduli accepted, the range DNSSEC allows: https://datatracker.ietf.org/doc/html/rfc3110#section-2
+pub const MIN_MODULUS_BITS: usize = 512;
+pub const MAX_MODULUS_BITS: usize = 4096;
+
+/// Largest exponent accepted, the same bound as OpenSSL, so that verification stays cheap
+pub const MAX_EXPONENT_BITS: usize = 64;
+
+/// Verify a signature over the SHA-256 hash of `message` with the big endian `modulus` and `exponent`
+///
+/// Keys outside of [`MIN_MODULUS_BITS`]..=[`MAX_MODULUS_BITS`] or with exponents over
+/// [`MAX_EXPONENT_BITS`] never verify.
+pub fn verify_sha256(modulus: &[u8], exponent: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    verify(
+        modulus,
+        exponent,
+        &SHA256_DIGEST_INFO,
+        &sha256(message),
+        signature,
+    )
+}
+
+fn verify(
+    modulus: &[u8],
+    exponent: &[u8],
+    digest_info: &[u8],
+    digest: &[u8],
+    signature: &[u8],
+) -> bool {
+    let modulus = BigUint::from_be_bytes(modulus);
+    let exponent = BigUint::from_be_bytes(exponent);
+    if !(MIN_MODULUS_BITS..=MAX_MODULUS_BITS).contains(&modulus.bits())
+        || exponent.bits() > MAX_EXPONENT_BITS.min(modulus.bits())
+    {
+        return false;
+    }
+    let length = modulus.bits().div_ceil(8);
+    if exponent.is_zero() || signature.len() != length {
+        return false;
+    }
+
+    let signature = BigUint::from_be_bytes(signature);
+    if signature >= modulus {
+        return false;
+    }
+    let encoded = signature.pow_mod(&exponent, &modulus).to_be_bytes(length);
+
+    // 0x00 0x01 then at least 8 bytes of 0xff, 0x00 and the DigestInfo
+    let info_length = digest_info.len() + digest.len();
+    if length < info_length + 11 {
+        return false;
+    }
+    let mut expected = vec![0x00, 0x01];
+    expected.resize(length - info_length - 1, 0xff);
+    expected.push(0x00);
+    expected.extend_from_slice(digest_info);
+    expected.extend_from_slice(digest);
+
+    encoded == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::hex;
+
+    /// The data signed in https://datatracker.ietf.org/doc/html/rfc5702#section-6.1 with its 512 bit key
+    const MESSAGE: &str = "0001080300000e1070dbd880386d43802349076578616d706c65036e65740003777777076578616d706c65036e6574000001000100000e100004c000025b";
+    const MODULUS: &str = "c15c1ac6b1c5d822bae1a60a45489b2e21f7d0aa4fb8f0637a5ec4f19c9d416d476161dfa069a27730b6467870082dbdde10b3c3e4c54769ea9fc395498e6dd9";
+    const SIGNATURE: &str = "91108e1fabbb974406cbdaa90bd975b0b9dc25c38a14b27b1a18943a26eee2d798a79544f519dcae24a164dcfce66c2532034469c1582bf94fb4f89560fe1bc2";
+
+    #[test]
+    fn verify_sha256_works() {
+        let exponent = hex("010001");
+        assert!(verify_sha256(
+            &hex(MODULUS),
+            &exponent,
+            &hex(MESSAGE),
+            &hex(SIGNATURE)
+        ));
+
+        let mut message = hex(MESSAGE);
+        message[0] ^= 1;
+        assert!(!verify_sha256(
+            &hex(MODULUS),
+            &exponent,
+            &message,
+            &hex(SIGNATURE)
+        ));
+        assert!(!verify_sha256(
+            &hex(MODULUS),
+            &exponent,
+            &hex(MESSAGE),
+            &hex(SIGNATURE)[1..]
+        ));
+    }
+
+    /// With an exponent of one the encoded message is its own signature
+    fn unit_signature(length: usize, message: &[u8]) -> Vec<u8> {
+        let mut signature = vec![0x00, 0x01];
+        signature.resize(length - SHA256_DIGEST_INFO.len() - 32 - 1, 0xff);
+        signature.push(0x00);
+        signature.extend_from_slice(&SHA256_DIGEST_INFO);
+        signature.extend_from_slice(&sha256(message));
+        signature
+    }
+
+    #[test]
+    fn modulus_sizes_are_limited() {
+        let message = b"message";
+        for (length, valid) in [(63, false), (64, true), (512, true), (513, false)] {
+            assert_eq!(
+                verify_sha256(
+                    &vec![0xff; length],
+                    &[1],
+                    message,
+                    &unit_signature(length, message)
+                ),
+                valid,
+                "{length} byte modulus"
+            );
+        }
+    }
+
+    #[test]
+    fn exponent_sizes_are_limited() {
+        let exponent = hex("010001");
+        assert!(!verify_sha256(
+            &hex(MODULUS),
+            &[[0xff; 8].as_slice(), &exponent].concat(),
+            &hex(MESSAGE),
+            &hex(SIGNATURE)
+        ));
+        // Leading zeros don't count
+        assert!(verify_sha256(
+            &hex(MODULUS),
+            &[[0; 128].as_slice(), &exponent].concat(),
+            &hex(MESSAGE),
+            &hex(SIGNATURE)
+        ));
+    }
+}
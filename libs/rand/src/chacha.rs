@@ -0,0 +1,143 @@
+use crate::Rng;
+
+/// "expand 32-byte k" as little-endian words
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// The ChaCha20 block function: https://datatracker.ietf.org/doc/html/rfc8439#section-2.3
+fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let word = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let mut initial = [0; 16];
+    initial[..4].copy_from_slice(&CONSTANTS);
+    for (i, chunk) in key.chunks(4).enumerate() {
+        initial[4 + i] = word(chunk);
+    }
+    initial[12] = counter;
+    for (i, chunk) in nonce.chunks(4).enumerate() {
+        initial[13 + i] = word(chunk);
+    }
+
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0; 64];
+    for (i, chunk) in block.chunks_mut(4).enumerate() {
+        chunk.copy_from_slice(&state[i].wrapping_add(initial[i]).to_le_bytes());
+    }
+    block
+}
+
+/// A cryptographically secure generator producing the ChaCha20 keystream of a secret key
+///
+/// Every block is split into a new key and output, so earlier output can't be recovered from the
+/// state: https://blog.cr.yp.to/20170723-random.html
+pub struct ChaCha20 {
+    key: [u8; 32],
+    buffer: [u8; 32],
+    /// Bytes of `buffer` already handed out
+    used: usize,
+}
+
+impl ChaCha20 {
+    pub fn from_seed(seed: [u8; 32]) -> ChaCha20 {
+        ChaCha20 {
+            key: seed,
+            buffer: [0; 32],
+            used: 32,
+        }
+    }
+
+    pub fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        for byte in bytes.iter_mut() {
+            if self.used == self.buffer.len() {
+                let block = chacha20_block(&self.key, 0, &[0; 12]);
+                self.key.copy_from_slice(&block[..32]);
+                self.buffer.copy_from_slice(&block[32..]);
+                self.used = 0;
+            }
+            *byte = self.buffer[self.used];
+            self.buffer[self.used] = 0;
+            self.used += 1;
+        }
+    }
+}
+
+impl Rng for ChaCha20 {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chacha20_block_works() {
+        // https://datatracker.ietf.org/doc/html/rfc8439#section-2.3.2
+        let mut key = [0; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+
+        assert_eq!(
+            chacha20_block(&key, 1, &nonce),
+            [
+                0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+                0x71, 0xc4, 0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a,
+                0xc3, 0xd4, 0x6c, 0x4e, 0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2,
+                0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2, 0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9,
+                0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e,
+            ]
+        );
+
+        // https://datatracker.ietf.org/doc/html/rfc8439#appendix-A.1 test vector #1
+        assert_eq!(
+            chacha20_block(&[0; 32], 0, &[0; 12]),
+            [
+                0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86,
+                0xbd, 0x28, 0xbd, 0xd2, 0x19, 0xb8, 0xa0, 0x8d, 0xed, 0x1a, 0xa8, 0x36, 0xef, 0xcc,
+                0x8b, 0x77, 0x0d, 0xc7, 0xda, 0x41, 0x59, 0x7c, 0x51, 0x57, 0x48, 0x8d, 0x77, 0x24,
+                0xe0, 0x3f, 0xb8, 0xd8, 0x4a, 0x37, 0x6a, 0x43, 0xb8, 0xf4, 0x15, 0x18, 0xa1, 0x1c,
+                0xc3, 0x87, 0xb6, 0x69, 0xb2, 0xee, 0x65, 0x86,
+            ]
+        );
+    }
+
+    #[test]
+    fn generator_erases_its_keys() {
+        let mut rng = ChaCha20::from_seed([0; 32]);
+        let mut bytes = [0; 40];
+        rng.fill_bytes(&mut bytes);
+
+        // The second half of the first block is output, the first half the next key
+        let block = chacha20_block(&[0; 32], 0, &[0; 12]);
+        assert_eq!(bytes[..32], block[32..]);
+        let key: [u8; 32] = block[..32].try_into().unwrap();
+        assert_eq!(bytes[32..], chacha20_block(&key, 0, &[0; 12])[32..40]);
+        assert_eq!(rng.buffer[..8], [0; 8]);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::Rng;
+
+/// Returns the given values in order and starts over after the last, for tests needing exact numbers
+#[derive(Debug, Clone)]
+pub struct FixedSequence {
+    values: Vec<u32>,
+    next: usize,
+}
+
+impl FixedSequence {
+    pub fn new(values: impl Into<Vec<u32>>) -> FixedSequence {
+        let values = values.into();
+        assert!(!values.is_empty(), "A fixed sequence needs values");
+
+        FixedSequence { values, next: 0 }
+    }
+}
+
+impl Rng for FixedSequence {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.values[self.next];
+        self.next = (self.next + 1) % self.values.len();
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_sequence_repeats() {
+        let mut rng = FixedSequence::new([1, 0x10002]);
+
+        assert_eq!(rng.next_u16(), 1);
+        assert_eq!(rng.next_u16(), 2);
+        assert_eq!(rng.next_u32(), 1);
+    }
+}
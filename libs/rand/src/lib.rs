@@ -1,40 +1,201 @@
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, Read},
+};
+
+mod chacha;
+pub use chacha::ChaCha20;
+
+mod fixed;
+pub use fixed::FixedSequence;
+
+mod xorshift;
+pub use xorshift::XorShift;
+
+/// A source of random numbers, generic code takes one so tests can make it deterministic
+pub trait Rng {
+    fn next_u32(&mut self) -> u32;
+
+    fn next_u16(&mut self) -> u16 {
+        self.next_u32() as u16
+    }
+
+    /// Uniformly distributed value in `0..max`, `max` must not be zero
+    fn below(&mut self, max: u32) -> u32 {
+        // Values above the last whole multiple of `max` would favor the small results
+        let limit = u32::MAX - u32::MAX % max;
+        loop {
+            let value = self.next_u32();
+            if value < limit {
+                return value % max;
+            }
+        }
+    }
+
+    /// Shuffle `items` in place using Fisher-Yates
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Fill `bytes` with randomness from the operating system
+///
+/// Uses the system call where the platform has one, which works without file descriptors and
+/// inside a chroot, and `/dev/urandom` otherwise.
+pub fn os_random(bytes: &mut [u8]) -> io::Result<()> {
+    system_random(bytes).or_else(|_| File::open("/dev/urandom")?.read_exact(bytes))
+}
+
+/// https://man7.org/linux/man-pages/man2/getrandom.2.html
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn system_random(bytes: &mut [u8]) -> io::Result<()> {
+    extern "C" {
+        fn getrandom(buffer: *mut u8, length: usize, flags: u32) -> isize;
+    }
+
+    let mut filled = 0;
+    while filled < bytes.len() {
+        let rest = &mut bytes[filled..];
+        // SAFETY: `rest` is valid for writes of `rest.len()` bytes
+        let result = unsafe { getrandom(rest.as_mut_ptr(), rest.len(), 0) };
+        match usize::try_from(result) {
+            Ok(length) => filled += length,
+            Err(_) => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// https://man.openbsd.org/getentropy.2
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd"
+))]
+fn system_random(bytes: &mut [u8]) -> io::Result<()> {
+    extern "C" {
+        fn getentropy(buffer: *mut u8, length: usize) -> i32;
+    }
+
+    // At most 256 bytes per call
+    for chunk in bytes.chunks_mut(256) {
+        // SAFETY: `chunk` is valid for writes of `chunk.len()` bytes
+        if unsafe { getentropy(chunk.as_mut_ptr(), chunk.len()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
+fn system_random(_bytes: &mut [u8]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
 
 thread_local! {
-    // Thread local variable
-    pub static SEED_U16: RefCell<u16> = RefCell::new(500);
+    // Seeded from the OS once per thread
+    static RNG: RefCell<ChaCha20> = RefCell::new({
+        let mut seed = [0; 32];
+        os_random(&mut seed).expect("The operating system has no randomness to seed from");
+        ChaCha20::from_seed(seed)
+    });
 }
 
-/// An implementation of xorshift (https://en.wikipedia.org/wiki/Xorshift) for generating random numbers with a predefined seed
-pub fn xorshift_u16() -> u16 {
-    SEED_U16.with(|x| {
-        let mut x = x.borrow_mut();
-        *x ^= *x << 7;
-        *x ^= *x >> 9;
-        *x ^= *x << 8;
+/// Fill `bytes` with cryptographically secure random bytes
+pub fn fill_bytes(bytes: &mut [u8]) {
+    RNG.with(|rng| rng.borrow_mut().fill_bytes(bytes))
+}
+
+/// The cryptographically secure generator of the current thread, seeded by the operating system
+///
+/// It is not fork-safe: a forked child continues with a copy of its parent's state and repeats
+/// its numbers, processes have to fork before using it. Panics when the operating system provides
+/// no randomness to seed from, see [`os_random`] to handle that error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRng;
 
-        x.clone()
-    })
+impl Rng for OsRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        fill_bytes(&mut bytes);
+        u32::from_ne_bytes(bytes)
+    }
+
+    fn next_u16(&mut self) -> u16 {
+        let mut bytes = [0; 2];
+        fill_bytes(&mut bytes);
+        u16::from_ne_bytes(bytes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::xorshift_u16;
+    use super::*;
 
     #[test]
-    fn xorshift_u16_works() {
-        let a = xorshift_u16();
-        let b = xorshift_u16();
+    fn threads_get_different_sequences() {
+        let other = std::thread::spawn(|| OsRng.next_u32()).join().unwrap();
+
+        // Equal with a chance of 1 in 2^32
+        assert_ne!(OsRng.next_u32(), other);
+    }
+
+    #[test]
+    fn os_random_works() {
+        let mut a = [0; 16];
+        let mut b = [0; 16];
+        os_random(&mut a).unwrap();
+        os_random(&mut b).unwrap();
 
         assert_ne!(a, b);
+    }
+
+    #[test]
+    fn os_random_fills_large_buffers() {
+        let mut bytes = [0; 1000];
+        os_random(&mut bytes).unwrap();
+
+        // The last 32 bytes are all zero with a chance of 1 in 2^256
+        assert_ne!(bytes[968..], [0; 32]);
+    }
 
-        let a1 = xorshift_u16();
-        let b1 = xorshift_u16();
+    #[test]
+    fn below_rejects_biased_values() {
+        let mut rng = FixedSequence::new([u32::MAX, 7]);
 
-        assert_ne!(a, a1);
-        assert_ne!(b, b1);
-        assert_ne!(a1, b1);
+        assert_eq!(rng.below(3), 1);
+        assert_eq!(rng.below(u32::MAX), 7);
+    }
+
+    #[test]
+    fn shuffle_works() {
+        let mut items = [1, 2, 3, 4];
+        FixedSequence::new([0]).shuffle(&mut items);
+        assert_eq!(items, [2, 3, 4, 1]);
 
-        println!("{a}, {b}");
+        let mut items: Vec<u32> = (0..100).collect();
+        XorShift::new(500).shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
     }
 }
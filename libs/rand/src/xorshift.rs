@@ -0,0 +1,63 @@
+use crate::Rng;
+
+/// An implementation of xorshift (https://en.wikipedia.org/wiki/Xorshift) producing the same sequence for the same seed
+///
+/// Predictable by design, only meant for reproducible tests.
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u16,
+}
+
+impl XorShift {
+    /// A seed of zero would only ever produce zeros and is replaced by one
+    pub fn new(seed: u16) -> XorShift {
+        XorShift { state: seed.max(1) }
+    }
+}
+
+impl Rng for XorShift {
+    fn next_u32(&mut self) -> u32 {
+        ((self.next_u16() as u32) << 16) | self.next_u16() as u32
+    }
+
+    fn next_u16(&mut self) -> u16 {
+        let x = &mut self.state;
+        *x ^= *x << 7;
+        *x ^= *x >> 9;
+        *x ^= *x << 8;
+
+        *x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xorshift_works() {
+        let mut rng = XorShift::new(500);
+        let a = rng.next_u16();
+        let b = rng.next_u16();
+
+        assert_ne!(a, b);
+
+        let a1 = rng.next_u16();
+        let b1 = rng.next_u16();
+
+        assert_ne!(a, a1);
+        assert_ne!(b, b1);
+        assert_ne!(a1, b1);
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = XorShift::new(500);
+        let mut b = XorShift::new(500);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u16(), b.next_u16());
+        }
+        assert_ne!(XorShift::new(0).next_u16(), 0);
+    }
+}
@@ -0,0 +1,396 @@
+//! A dig-like command line tool, sending queries with the same code as the resolver
+
+use std::{
+    env,
+    net::{IpAddr, SocketAddr},
+    process,
+    time::Instant,
+};
+
+use ldns::{
+    message::{Edns, Message, QClass, QType, Question},
+    resolver::{Protocol, Resolver, ValidatingResolver, DEFAULT_SERVER},
+    reverse::reverse_name,
+};
+use rand::{OsRng, Rng};
+
+const USAGE: &str = "usage: ldns [@server] [-p port] [-x address] [name] [type] [class] [+tcp] [+norecurse] [+dnssec] [+short] [+trace] [+validate]";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    /// Address or host name of the server to query
+    server: Option<String>,
+    port: u16,
+    name: String,
+    qtype: QType,
+    qclass: QClass,
+    protocol: Protocol,
+    recursion_desired: bool,
+    dnssec_ok: bool,
+    short: bool,
+    /// Resolve iteratively from the root servers, showing every referral
+    trace: bool,
+    /// Validate the response from the root trust anchors down
+    validate: bool,
+}
+
+/// Parse arguments the way dig does, names of types and classes are recognized wherever they appear
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut server = None;
+    let mut port = 53;
+    let mut name = None;
+    let mut qtype = None;
+    let mut qclass = QClass::IN;
+    let mut protocol = Protocol::Udp;
+    let mut recursion_desired = true;
+    let mut dnssec_ok = false;
+    let mut short = false;
+    let mut trace = false;
+    let mut validate = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if let Some(host) = arg.strip_prefix('@') {
+            server = Some(String::from(host));
+        } else if let Some(option) = arg.strip_prefix('+') {
+            match option {
+                "tcp" => protocol = Protocol::Tcp,
+                "notcp" => protocol = Protocol::Udp,
+                "recurse" => recursion_desired = true,
+                "norecurse" => recursion_desired = false,
+                "dnssec" => dnssec_ok = true,
+                "nodnssec" => dnssec_ok = false,
+                "short" => short = true,
+                "noshort" => short = false,
+                "trace" => trace = true,
+                "notrace" => trace = false,
+                "validate" => validate = true,
+                "novalidate" => validate = false,
+                _ => return Err(format!("Invalid option: {arg}")),
+            }
+        } else if arg == "-p" {
+            let value = args.next().ok_or("Missing port after -p")?;
+            port = value
+                .parse()
+                .map_err(|_| format!("Invalid port: {value}"))?;
+        } else if arg == "-x" {
+            let value = args.next().ok_or("Missing address after -x")?;
+            let addr: IpAddr = value
+                .parse()
+                .map_err(|_| format!("Invalid address: {value}"))?;
+            name = Some(reverse_name(addr));
+            qtype = qtype.or(Some(QType::PTR));
+        } else if arg.starts_with('-') {
+            return Err(format!("Invalid option: {arg}"));
+        } else if arg.eq_ignore_ascii_case("ANY") {
+            // Also the name of a class, dig takes it as the type: https://datatracker.ietf.org/doc/html/rfc8482
+            qtype = Some(QType::ALL);
+        } else if let Ok(value) = arg.parse() {
+            qtype = Some(value);
+        } else if let Ok(value) = arg.parse() {
+            qclass = value;
+        } else {
+            name = Some(arg);
+        }
+    }
+
+    // Without a name the root servers are asked for, like dig does
+    let (name, qtype) = match name {
+        Some(name) => (name, qtype.unwrap_or(QType::A)),
+        None => (String::from("."), qtype.unwrap_or(QType::NS)),
+    };
+
+    let options = Options {
+        server,
+        port,
+        name,
+        qtype,
+        qclass,
+        protocol,
+        recursion_desired,
+        dnssec_ok,
+        short,
+        trace,
+        validate,
+    };
+    check_options(&options)?;
+    Ok(options)
+}
+
+/// Reject options that +trace and +validate can't honor
+///
+/// Tracing always starts at the root servers without recursion desired, the server is only asked
+/// for name servers without glue. Validation needs a recursive server and always requests DNSSEC
+/// records. Both only work in class IN.
+fn check_options(options: &Options) -> Result<(), String> {
+    if options.trace && options.validate {
+        return Err(String::from("+trace and +validate can't be combined"));
+    }
+    if (options.trace || options.validate) && options.qclass != QClass::IN {
+        return Err(String::from("+trace and +validate only support class IN"));
+    }
+    if options.trace && options.dnssec_ok {
+        return Err(String::from("+trace doesn't support +dnssec"));
+    }
+    if options.validate && !options.recursion_desired {
+        return Err(String::from(
+            "+validate needs recursion, +norecurse can't be used",
+        ));
+    }
+    Ok(())
+}
+
+/// Address of the server to query, host names are looked up with the resolver
+fn server_address(options: &Options) -> Result<SocketAddr, String> {
+    let host = match &options.server {
+        Some(host) => host,
+        None if options.port == 53 => return Ok(DEFAULT_SERVER),
+        None => return Ok(SocketAddr::new(DEFAULT_SERVER.ip(), options.port)),
+    };
+
+    if let Ok(addr) = host.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(addr, options.port));
+    }
+
+    let addresses = Resolver::default()
+        .lookup_ip_sorted(host)
+        .map_err(|e| format!("Couldn't get address for '{host}': {e}"))?;
+    let addr = addresses
+        .first()
+        .ok_or_else(|| format!("Couldn't get address for '{host}': not found"))?;
+
+    Ok(SocketAddr::new(addr.addr, options.port))
+}
+
+fn query(options: &Options) -> Message {
+    let mut name = options.name.clone();
+    if !name.ends_with('.') {
+        name.push('.');
+    }
+
+    Message::builder()
+        .id(OsRng.next_u16())
+        .recursion_desired(options.recursion_desired)
+        .question(Question {
+            qname: name,
+            qtype: options.qtype,
+            qclass: options.qclass,
+        })
+        .edns(Edns {
+            dnssec_ok: options.dnssec_ok,
+            ..Default::default()
+        })
+        .build()
+}
+
+fn print_response(
+    options: &Options,
+    args: &[String],
+    response: &Message,
+    size: usize,
+    server: SocketAddr,
+) {
+    if options.short {
+        for record in response.answers() {
+            println!("{}", record.rdata);
+        }
+        return;
+    }
+
+    println!("; <<>> ldns <<>> {}", args.join(" "));
+    println!(";; Got answer:");
+    print!("{response}");
+
+    let protocol = match options.protocol {
+        Protocol::Udp => "UDP",
+        Protocol::Tcp => "TCP",
+    };
+    println!();
+    println!(
+        ";; SERVER: {}#{}({}) ({protocol})",
+        server.ip(),
+        server.port(),
+        server.ip()
+    );
+    println!(";; MSG SIZE  rcvd: {size}");
+}
+
+fn print_trace(options: &Options, args: &[String], server: SocketAddr) {
+    let mut name = options.name.clone();
+    if !name.ends_with('.') {
+        name.push('.');
+    }
+
+    let resolver = Resolver::new(server).protocol(options.protocol);
+    let steps = match resolver.trace(&name, options.qtype) {
+        Ok(steps) => steps,
+        Err(e) => {
+            eprintln!(";; trace failed: {e}");
+            process::exit(9);
+        }
+    };
+
+    println!("; <<>> ldns <<>> {}", args.join(" "));
+    for step in steps.iter() {
+        let referral = step.referral();
+        let records = if !referral.is_empty() {
+            referral.into_iter().chain(step.glue()).collect()
+        } else if !step.response.answers().is_empty() {
+            step.response.answers().iter().collect()
+        } else {
+            step.response.authority().iter().collect::<Vec<_>>()
+        };
+
+        for record in records {
+            println!("{record}");
+        }
+        println!(
+            ";; Received {} bytes from {}#{}({}) in {} ms",
+            step.size,
+            step.server.ip(),
+            step.server.port(),
+            step.server_name,
+            step.elapsed.as_millis()
+        );
+        println!();
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let options = match parse_args(args.clone()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    let server = match server_address(&options) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("ldns: {e}");
+            process::exit(10);
+        }
+    };
+
+    if options.trace {
+        print_trace(&options, &args, server);
+        return;
+    }
+
+    let start = Instant::now();
+    let query = query(&options);
+    let (response, size, security) = if options.validate {
+        let question = &query.questions()[0];
+        ValidatingResolver::new(server)
+            .protocol(options.protocol)
+            .query(&question.qname, question.qtype)
+            .map(|validated| (validated.response, validated.size, Some(validated.security)))
+    } else {
+        Resolver::exchange_with_size(&query, server, options.protocol)
+            .map(|(response, size)| (response, size, None))
+    }
+    .unwrap_or_else(|e| {
+        eprintln!(";; communications error to {server}: {e}");
+        process::exit(9);
+    });
+    let elapsed = start.elapsed();
+
+    print_response(&options, &args, &response, size, server);
+    if !options.short {
+        println!(";; Query time: {} msec", elapsed.as_millis());
+        if let Some(security) = security {
+            println!(";; VALIDATION: {security}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_args_works() {
+        let options =
+            parse("@9.9.9.9 -p 5353 example.com aaaa ch +tcp +norecurse +dnssec +short").unwrap();
+
+        assert_eq!(
+            options,
+            Options {
+                server: Some(String::from("9.9.9.9")),
+                port: 5353,
+                name: String::from("example.com"),
+                qtype: QType::AAAA,
+                qclass: QClass::CH,
+                protocol: Protocol::Tcp,
+                recursion_desired: false,
+                dnssec_ok: true,
+                short: true,
+                trace: false,
+                validate: false,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_args_any_is_a_type() {
+        let options = parse("example.com ANY").unwrap();
+        assert_eq!(options.qtype, QType::ALL);
+        assert_eq!(options.qclass, QClass::IN);
+
+        let options = parse("any example.com ch").unwrap();
+        assert_eq!(options.qtype, QType::ALL);
+        assert_eq!(options.qclass, QClass::CH);
+    }
+
+    #[test]
+    fn parse_args_checks_trace_and_validate() {
+        assert!(parse("example.com +trace +tcp +norecurse").unwrap().trace);
+        assert!(
+            parse("@9.9.9.9 -p 5353 example.com +validate +tcp +dnssec")
+                .unwrap()
+                .validate
+        );
+
+        assert!(parse("example.com +trace +validate").is_err());
+        assert!(parse("example.com ch +trace").is_err());
+        assert!(parse("example.com ch +validate").is_err());
+        assert!(parse("@9.9.9.9 -p 5353 example.com +trace").unwrap().trace);
+        assert!(parse("example.com +trace +dnssec").is_err());
+        assert!(parse("example.com +validate +norecurse").is_err());
+    }
+
+    #[test]
+    fn parse_args_uses_dig_defaults() {
+        let options = parse("").unwrap();
+        assert_eq!(options.name, ".");
+        assert_eq!(options.qtype, QType::NS);
+        assert_eq!(options.qclass, QClass::IN);
+        assert_eq!(options.protocol, Protocol::Udp);
+
+        assert_eq!(parse("example.com").unwrap().qtype, QType::A);
+    }
+
+    #[test]
+    fn parse_args_reverse_lookup() {
+        let options = parse("-x 192.0.2.1").unwrap();
+
+        assert_eq!(options.name, "1.2.0.192.in-addr.arpa.");
+        assert_eq!(options.qtype, QType::PTR);
+    }
+
+    #[test]
+    fn parse_args_rejects_invalid_arguments() {
+        assert!(parse("+bogus").is_err());
+        assert!(parse("-p").is_err());
+        assert!(parse("-p http").is_err());
+        assert!(parse("-x example.com").is_err());
+    }
+}
@@ -0,0 +1,728 @@
+//! Verification of RRset signatures and delegations: https://datatracker.ietf.org/doc/html/rfc4035#section-5
+
+use core::{cmp::Ordering, fmt};
+use std::error::Error;
+
+use bitreader::BitWriter;
+
+use crate::message::{
+    canonical_name, canonical_name_bytes, is_subdomain, names_eq, RData, Record, Type, DNSKEY, DS,
+    RRSIG,
+};
+
+mod denial;
+pub use denial::*;
+
+/// Signature algorithms: https://www.iana.org/assignments/dns-sec-alg-numbers
+pub const RSASHA256: u8 = 8;
+pub const ECDSAP256SHA256: u8 = 13;
+pub const ECDSAP384SHA384: u8 = 14;
+pub const ED25519: u8 = 15;
+
+/// Whether signatures of `algorithm` can be verified
+pub fn supports_algorithm(algorithm: u8) -> bool {
+    matches!(
+        algorithm,
+        RSASHA256 | ECDSAP256SHA256 | ECDSAP384SHA384 | ED25519
+    )
+}
+
+/// DS digest types: https://www.iana.org/assignments/ds-rr-types
+pub const SHA1: u8 = 1;
+pub const SHA256: u8 = 2;
+pub const SHA384: u8 = 4;
+
+/// Whether DS records with `digest_type` can be matched with a DNSKEY
+pub fn supports_digest(digest_type: u8) -> bool {
+    matches!(digest_type, SHA1 | SHA256 | SHA384)
+}
+
+/// Digest of `dnskey` owned by `owner` as found in DS records: https://datatracker.ietf.org/doc/html/rfc4034#section-5.1.4
+pub fn ds_digest(owner: &str, dnskey: &DNSKEY, digest_type: u8) -> Option<Vec<u8>> {
+    let mut writer = BitWriter::new();
+    writer.write_bytes(&canonical_name_bytes(owner).ok()?);
+    dnskey.write(&mut writer);
+    let data = writer.into_bytes();
+
+    match digest_type {
+        SHA1 => Some(crypto::sha1(&data).to_vec()),
+        SHA256 => Some(crypto::sha256(&data).to_vec()),
+        SHA384 => Some(crypto::sha384(&data).to_vec()),
+        _ => None,
+    }
+}
+
+/// Whether the DNSKEY record `dnskey` is the key `ds` refers to
+pub fn matches_ds(dnskey: &Record, ds: &DS) -> bool {
+    let RData::DNSKEY(key) = &dnskey.rdata else {
+        return false;
+    };
+    key.algorithm == ds.algorithm
+        && key.key_tag() == ds.key_tag
+        && ds_digest(&dnskey.name, key, ds.digest_type).is_some_and(|digest| digest == ds.digest)
+}
+
+/// Security status of validated data: https://datatracker.ietf.org/doc/html/rfc4035#section-4.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Security {
+    /// Signatures chain up to a trust anchor
+    Secure,
+    /// Signed data proves there is no chain of trust, the zone is not signed
+    Insecure,
+    /// A chain of trust should exist but doesn't validate
+    Bogus(ValidationError),
+    /// No trust anchor covers the data
+    Indeterminate,
+}
+
+impl Security {
+    /// The weaker of two statuses, the one of data made of several parts
+    pub fn min(self, other: Security) -> Security {
+        let strength = |security: &Security| match security {
+            Security::Secure => 3,
+            Security::Insecure => 2,
+            Security::Indeterminate => 1,
+            Security::Bogus(_) => 0,
+        };
+        if strength(&other) < strength(&self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Security::Secure => write!(f, "secure"),
+            Security::Insecure => write!(f, "insecure"),
+            Security::Bogus(e) => write!(f, "bogus: {e}"),
+            Security::Indeterminate => write!(f, "indeterminate"),
+        }
+    }
+}
+
+/// Why an RRset failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The records don't form a single RRset
+    InvalidRRset,
+    /// No RRSIG covers the RRset
+    MissingSignature,
+    UnsupportedAlgorithm(u8),
+    /// The RRSIG doesn't match the owner name of the RRset or the signer is not above it
+    SignatureMismatch,
+    /// No DNSKEY of the signer matches the key tag and algorithm of the RRSIG
+    MissingKey,
+    NotYetValid,
+    Expired,
+    InvalidSignature,
+    /// No DNSKEY of the zone matches its DS records
+    UnmatchedDS,
+    /// No NSEC or NSEC3 record proves the absence of the data
+    MissingDenial,
+}
+
+impl Error for ValidationError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidRRset => write!(f, "Records are not a single RRset"),
+            ValidationError::MissingSignature => write!(f, "No signature covers the RRset"),
+            ValidationError::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "Unsupported algorithm {algorithm}")
+            }
+            ValidationError::SignatureMismatch => write!(f, "Signature does not match the RRset"),
+            ValidationError::MissingKey => write!(f, "No key matches the signature"),
+            ValidationError::NotYetValid => write!(f, "Signature is not valid yet"),
+            ValidationError::Expired => write!(f, "Signature has expired"),
+            ValidationError::InvalidSignature => write!(f, "Invalid signature"),
+            ValidationError::UnmatchedDS => write!(f, "No key matches the DS records"),
+            ValidationError::MissingDenial => write!(f, "Absence of the data is not proven"),
+        }
+    }
+}
+
+/// Whether `a` comes before or is `b` in serial number arithmetic: https://datatracker.ietf.org/doc/html/rfc1982#section-3.2
+fn serial_le(a: u32, b: u32) -> bool {
+    b.wrapping_sub(a) < 1 << 31
+}
+
+/// Verify `signature` over `data` with the public key in the format of its algorithm
+fn verify_signature(dnskey: &DNSKEY, data: &[u8], signature: &[u8]) -> Result<(), ValidationError> {
+    let key = &dnskey.public_key;
+    let valid = match dnskey.algorithm {
+        // The exponent length is one byte, or zero followed by two bytes: https://datatracker.ietf.org/doc/html/rfc3110#section-2
+        RSASHA256 => {
+            let (length, key) = match key.split_first() {
+                Some((0, rest)) if rest.len() >= 2 => {
+                    (u16::from_be_bytes([rest[0], rest[1]]) as usize, &rest[2..])
+                }
+                Some((&length, rest)) => (length as usize, rest),
+                None => (0, &key[..]),
+            };
+            length > 0
+                && key.len() > length
+                && crypto::rsa::verify_sha256(&key[length..], &key[..length], data, signature)
+        }
+        ECDSAP256SHA256 => crypto::ecdsa::verify_p256_sha256(key, data, signature),
+        ECDSAP384SHA384 => crypto::ecdsa::verify_p384_sha384(key, data, signature),
+        ED25519 => crypto::ed25519::verify(key, data, signature),
+        algorithm => return Err(ValidationError::UnsupportedAlgorithm(algorithm)),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidSignature)
+    }
+}
+
+/// The data covered by `rrsig`, the RRSIG RDATA and the records in canonical form and order: https://datatracker.ietf.org/doc/html/rfc4034#section-3.1.8.1
+fn signed_data(rrset: &[Record], rrsig: &RRSIG) -> Result<Vec<u8>, ValidationError> {
+    let owner = &rrset[0].name;
+    let labels: Vec<&str> = owner.split('.').filter(|label| !label.is_empty()).collect();
+    let owner = match (rrsig.labels as usize).cmp(&labels.len()) {
+        Ordering::Equal => owner.clone(),
+        // Expanded from a wildcard: https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.2
+        Ordering::Less => {
+            let mut wildcard = String::from("*.");
+            for label in &labels[labels.len() - rrsig.labels as usize..] {
+                wildcard.push_str(label);
+                wildcard.push('.');
+            }
+            wildcard
+        }
+        Ordering::Greater => return Err(ValidationError::SignatureMismatch),
+    };
+
+    let mut records: Vec<Vec<u8>> = rrset
+        .iter()
+        .map(|record| {
+            Record {
+                name: owner.clone(),
+                ttl: rrsig.original_ttl,
+                ..record.clone()
+            }
+            .canonical_bytes()
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|_| ValidationError::InvalidRRset)?;
+    // Only the RDATA differs between the records, so they sort by it
+    records.sort();
+    records.dedup();
+
+    let mut writer = BitWriter::new();
+    RRSIG {
+        signer_name: canonical_name(&rrsig.signer_name)
+            .map_err(|_| ValidationError::InvalidRRset)?,
+        ..rrsig.clone()
+    }
+    .write_without_signature(&mut writer)
+    .map_err(|_| ValidationError::InvalidRRset)?;
+    let mut data = writer.into_bytes();
+    data.extend(records.concat());
+
+    Ok(data)
+}
+
+/// Verify a single RRSIG over `rrset` with `dnskey` owned by the signer, at `now` in seconds since the epoch
+pub fn verify_rrsig(
+    rrset: &[Record],
+    rrsig: &RRSIG,
+    dnskey: &Record,
+    now: u32,
+) -> Result<(), ValidationError> {
+    let Some(first) = rrset.first() else {
+        return Err(ValidationError::InvalidRRset);
+    };
+    let same_rrset = rrset.iter().all(|record| {
+        names_eq(&record.name, &first.name)
+            && record.r#type == first.r#type
+            && record.class == first.class
+    });
+    if !same_rrset {
+        return Err(ValidationError::InvalidRRset);
+    }
+
+    if rrsig.type_covered != first.r#type as u16 || !is_subdomain(&first.name, &rrsig.signer_name) {
+        return Err(ValidationError::SignatureMismatch);
+    }
+
+    let RData::DNSKEY(key) = &dnskey.rdata else {
+        return Err(ValidationError::MissingKey);
+    };
+    if !names_eq(&dnskey.name, &rrsig.signer_name)
+        || !key.is_zone_key()
+        || key.protocol != 3
+        || key.algorithm != rrsig.algorithm
+        || key.key_tag() != rrsig.key_tag
+    {
+        return Err(ValidationError::MissingKey);
+    }
+
+    if !serial_le(rrsig.inception, now) {
+        return Err(ValidationError::NotYetValid);
+    }
+    if !serial_le(now, rrsig.expiration) {
+        return Err(ValidationError::Expired);
+    }
+
+    verify_signature(key, &signed_data(rrset, rrsig)?, &rrsig.signature)
+}
+
+/// Verify `rrset` with any of the RRSIG records in `rrsigs` made by one of the DNSKEY records in
+/// `dnskeys`, returning the RRSIG that verified it
+///
+/// The error is the one of the last candidate tried, so it describes the closest match.
+pub fn verify_rrset<'a>(
+    rrset: &[Record],
+    rrsigs: &'a [Record],
+    dnskeys: &[Record],
+    now: u32,
+) -> Result<&'a RRSIG, ValidationError> {
+    let Some(first) = rrset.first() else {
+        return Err(ValidationError::InvalidRRset);
+    };
+
+    let mut error = ValidationError::MissingSignature;
+    let covering = rrsigs.iter().filter_map(|record| match &record.rdata {
+        RData::RRSIG(rrsig)
+            if names_eq(&record.name, &first.name) && rrsig.type_covered == first.r#type as u16 =>
+        {
+            Some(rrsig)
+        }
+        _ => None,
+    });
+
+    for rrsig in covering {
+        if !supports_algorithm(rrsig.algorithm) {
+            error = ValidationError::UnsupportedAlgorithm(rrsig.algorithm);
+            continue;
+        }
+
+        error = ValidationError::MissingKey;
+        for dnskey in dnskeys {
+            match verify_rrsig(rrset, rrsig, dnskey, now) {
+                Ok(()) => return Ok(rrsig),
+                Err(ValidationError::MissingKey) => {}
+                Err(e) => error = e,
+            }
+        }
+    }
+
+    Err(error)
+}
+
+/// Sign `rrset` with the Ed25519 `secret_key` of `dnskey`, valid from `inception` to `expiration`
+///
+/// Meant for test zones, signing doesn't run in constant time.
+pub fn sign_rrset(
+    rrset: &[Record],
+    dnskey: &Record,
+    secret_key: &[u8; 32],
+    inception: u32,
+    expiration: u32,
+) -> Result<Record, ValidationError> {
+    let Some(first) = rrset.first() else {
+        return Err(ValidationError::InvalidRRset);
+    };
+    let RData::DNSKEY(key) = &dnskey.rdata else {
+        return Err(ValidationError::MissingKey);
+    };
+    if key.algorithm != ED25519 {
+        return Err(ValidationError::UnsupportedAlgorithm(key.algorithm));
+    }
+
+    let mut rrsig = RRSIG {
+        type_covered: first.r#type as u16,
+        algorithm: key.algorithm,
+        // A leading wildcard label is not counted
+        labels: first
+            .name
+            .split('.')
+            .filter(|label| !label.is_empty() && *label != "*")
+            .count() as u8,
+        original_ttl: first.ttl,
+        expiration,
+        inception,
+        key_tag: key.key_tag(),
+        signer_name: dnskey.name.clone(),
+        signature: vec![],
+    };
+    rrsig.signature = crypto::ed25519::sign(secret_key, &signed_data(rrset, &rrsig)?);
+
+    Ok(Record {
+        name: first.name.clone(),
+        r#type: Type::RRSIG,
+        class: first.class,
+        ttl: first.ttl,
+        rdata: RData::RRSIG(rrsig),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::{
+        message::{Class, Message, MX},
+        util::{from_base64, from_hex},
+    };
+
+    use super::*;
+
+    fn record(name: &str, r#type: Type, ttl: u32, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl,
+            rdata,
+        }
+    }
+
+    fn dnskey(name: &str, flags: u16, algorithm: u8, key: &str) -> Record {
+        record(
+            name,
+            Type::DNSKEY,
+            3600,
+            RData::DNSKEY(DNSKEY {
+                flags,
+                protocol: 3,
+                algorithm,
+                public_key: from_base64(key).unwrap(),
+            }),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn rrsig(
+        name: &str,
+        r#type: Type,
+        algorithm: u8,
+        labels: u8,
+        expiration: u32,
+        inception: u32,
+        key_tag: u16,
+        signer_name: &str,
+        signature: &str,
+    ) -> Record {
+        record(
+            name,
+            Type::RRSIG,
+            3600,
+            RData::RRSIG(RRSIG {
+                type_covered: r#type as u16,
+                algorithm,
+                labels,
+                original_ttl: 3600,
+                expiration,
+                inception,
+                key_tag,
+                signer_name: String::from(signer_name),
+                signature: from_base64(signature).unwrap(),
+            }),
+        )
+    }
+
+    /// Address records of the RFC 5702 and RFC 6605 examples
+    fn a(octet: u8) -> Vec<Record> {
+        vec![record(
+            "www.example.net.",
+            Type::A,
+            3600,
+            RData::A(Ipv4Addr::new(192, 0, 2, octet)),
+        )]
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc5702#section-6.1
+    #[test]
+    fn rsasha256_works() {
+        let dnskeys = [dnskey("example.net.", 256, RSASHA256, "AwEAAcFcGsaxxdgiuuGmCkVImy4h99CqT7jwY3pexPGcnUFtR2Fh36BponcwtkZ4cAgtvd4Qs8PkxUdp6p/DlUmObdk=")];
+        let rrsigs = [rrsig("www.example.net.", Type::A, RSASHA256, 3, 1893456000, 946684800, 9033, "example.net.", "kRCOH6u7l0QGy9qpC9l1sLncJcOKFLJ7GhiUOibu4teYp5VE9RncriShZNz85mwlMgNEacFYK/lPtPiVYP4bwg==")];
+
+        assert!(verify_rrset(&a(91), &rrsigs, &dnskeys, 1700000000).is_ok());
+        assert_eq!(
+            verify_rrset(&a(92), &rrsigs, &dnskeys, 1700000000),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn costly_rsa_keys_are_rejected() {
+        let key = |public_key: Vec<u8>| DNSKEY {
+            flags: DNSKEY::ZONE_KEY,
+            protocol: 3,
+            algorithm: RSASHA256,
+            public_key,
+        };
+
+        // A 8192 bit modulus
+        let large_modulus = key([[3, 1, 0, 1].as_slice(), &[0xff; 1024]].concat());
+        assert_eq!(
+            verify_signature(&large_modulus, b"data", &[0x01; 1024]),
+            Err(ValidationError::InvalidSignature)
+        );
+
+        // A 64 KB exponent
+        let large_exponent = key([[0, 0xff, 0xff].as_slice(), &[0xff; 0xffff + 64]].concat());
+        assert_eq!(
+            verify_signature(&large_exponent, b"data", &[0x01; 64]),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc6605#section-6.1
+    #[test]
+    fn ecdsap256sha256_works() {
+        let dnskeys = [dnskey("example.net.", 257, ECDSAP256SHA256, "GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edbkrSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==")];
+        let rrsigs = [rrsig("www.example.net.", Type::A, ECDSAP256SHA256, 3, 1284026679, 1281607479, 55648, "example.net.", "qx6wLYqmh+l9oCKTN6qIc+bw6ya+KJ8oMz0YP107epXAyGmt+3SNruPFKG7tZoLBLlUzGGus7ZwmwWep666VCw==")];
+
+        assert!(verify_rrset(&a(1), &rrsigs, &dnskeys, 1282000000).is_ok());
+        assert_eq!(
+            verify_rrset(&a(1), &rrsigs, &dnskeys, 1290000000),
+            Err(ValidationError::Expired)
+        );
+        assert_eq!(
+            verify_rrset(&a(1), &rrsigs, &dnskeys, 1280000000),
+            Err(ValidationError::NotYetValid)
+        );
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc6605#section-6.2
+    #[test]
+    fn ecdsap384sha384_works() {
+        let dnskeys = [dnskey("example.net.", 257, ECDSAP384SHA384, "xKYaNhWdGOfJ+nPrL8/arkwf2EY3MDJ+SErKivBVSum1w/egsXvSADtNJhyem5RCOpgQ6K8X1DRSEkrbYQ+OB+v8/uX45NBwY8rp65F6Glur8I/mlVNgF6W/qTI37m40")];
+        let rrsigs = [rrsig("www.example.net.", Type::A, ECDSAP384SHA384, 3, 1284027625, 1281608425, 10771, "example.net.", "/L5hDKIvGDyI1fcARX3z65qrmPsVz73QD1Mr5CEqOiLP95hxQouuroGCeZOvzFaxsT8Glr74hbavRKayJNuydCuzWTSSPdz7wnqXL5bdcJzusdnI0RSMROxxwGipWcJm")];
+
+        assert!(verify_rrset(&a(1), &rrsigs, &dnskeys, 1282000000).is_ok());
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc8080#section-6.1
+    #[test]
+    fn ed25519_works() {
+        let dnskeys = [dnskey(
+            "example.com.",
+            257,
+            ED25519,
+            "l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=",
+        )];
+        let rrsigs = [rrsig("example.com.", Type::MX, ED25519, 2, 1440021600, 1438207200, 3613, "example.com.", "oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==")];
+        let mx = |exchange: &str| {
+            record(
+                "Example.COM.",
+                Type::MX,
+                60,
+                RData::MX(MX {
+                    preference: 10,
+                    exchange: String::from(exchange),
+                }),
+            )
+        };
+
+        // Names are lowercased and the original TTL replaces the remaining one
+        let rrset = [mx("MAIL.example.com.")];
+        assert!(verify_rrset(&rrset, &rrsigs, &dnskeys, 1439000000).is_ok());
+        assert_eq!(
+            verify_rrset(&[mx("mail.example.org.")], &rrsigs, &dnskeys, 1439000000),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn compressed_rrsets_are_validated() {
+        let dnskeys = [dnskey(
+            "example.com.",
+            257,
+            ED25519,
+            "l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=",
+        )];
+        let rrsigs = [rrsig("example.com.", Type::MX, ED25519, 2, 1440021600, 1438207200, 3613, "example.com.", "oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==")];
+        // The RFC 8080 MX record as a server would send it, its exchange points into the question
+        let response = [
+            0, 1, 0x81, 0x80, 0, 1, 0, 1, 0, 0, 0, 0, //
+            7, b'E', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'C', b'O', b'M', 0, 0, 15, 0,
+            1, //
+            0xc0, 12, 0, 15, 0, 1, 0, 0, 0x0e, 0x10, 0, 9, //
+            0, 10, 4, b'M', b'A', b'I', b'L', 0xc0, 12,
+        ];
+
+        let response = Message::from_bytes(&response).unwrap();
+        assert!(verify_rrset(response.answers(), &rrsigs, &dnskeys, 1439000000).is_ok());
+    }
+
+    #[test]
+    fn keys_and_signatures_are_matched() {
+        let dnskeys = [dnskey(
+            "example.com.",
+            257,
+            ED25519,
+            "l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=",
+        )];
+        let signature = "oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==";
+        let rrset = [record(
+            "example.com.",
+            Type::MX,
+            3600,
+            RData::MX(MX {
+                preference: 10,
+                exchange: String::new(),
+            }),
+        )];
+        let now = 1439000000;
+
+        let wrong_tag = [rrsig(
+            "example.com.",
+            Type::MX,
+            ED25519,
+            2,
+            1440021600,
+            1438207200,
+            3614,
+            "example.com.",
+            signature,
+        )];
+        assert_eq!(
+            verify_rrset(&rrset, &wrong_tag, &dnskeys, now),
+            Err(ValidationError::MissingKey)
+        );
+
+        let other_type = [rrsig(
+            "example.com.",
+            Type::A,
+            ED25519,
+            2,
+            1440021600,
+            1438207200,
+            3613,
+            "example.com.",
+            signature,
+        )];
+        assert_eq!(
+            verify_rrset(&rrset, &other_type, &dnskeys, now),
+            Err(ValidationError::MissingSignature)
+        );
+
+        let unsupported = [rrsig(
+            "example.com.",
+            Type::MX,
+            5,
+            2,
+            1440021600,
+            1438207200,
+            3613,
+            "example.com.",
+            signature,
+        )];
+        assert_eq!(
+            verify_rrset(&rrset, &unsupported, &dnskeys, now),
+            Err(ValidationError::UnsupportedAlgorithm(5))
+        );
+
+        let too_many_labels = [rrsig(
+            "example.com.",
+            Type::MX,
+            ED25519,
+            3,
+            1440021600,
+            1438207200,
+            3613,
+            "example.com.",
+            signature,
+        )];
+        assert_eq!(
+            verify_rrset(&rrset, &too_many_labels, &dnskeys, now),
+            Err(ValidationError::SignatureMismatch)
+        );
+    }
+
+    #[test]
+    fn signed_data_uses_wildcard_owner() {
+        let rrset = a(1);
+        let RData::RRSIG(wildcard) = rrsig(
+            "www.example.net.",
+            Type::A,
+            ED25519,
+            2,
+            0,
+            0,
+            0,
+            "Example.NET.",
+            "",
+        )
+        .rdata
+        else {
+            unreachable!()
+        };
+
+        let data = signed_data(&rrset, &wildcard).unwrap();
+        assert!(data.ends_with(
+            b"\x01*\x07example\x03net\x00\x00\x01\x00\x01\x00\x00\x0e\x10\x00\x04\xc0\x00\x02\x01"
+        ));
+        // The signer name is lowercased
+        assert_eq!(data[18..31], *b"\x07example\x03net\x00");
+    }
+
+    #[test]
+    fn serial_arithmetic_wraps() {
+        assert!(serial_le(1, 2));
+        assert!(serial_le(2, 2));
+        assert!(!serial_le(2, 1));
+        assert!(serial_le(u32::MAX, 5));
+        assert!(!serial_le(5, u32::MAX));
+    }
+
+    // https://data.iana.org/root-anchors/root-anchors.xml
+    #[test]
+    fn root_key_matches_its_ds() {
+        let root = dnskey("", 257, RSASHA256, "AwEAAaz/tAm8yTn4Mfeh5eyI96WSVexTBAvkMgJzkKTOiW1vkIbzxeF3+/4RgWOq7HrxRixHlFlExOLAJr5emLvN7SWXgnLh4+B5xQlNVz8Og8kvArMtNROxVQuCaSnIDdD5LKyWbRd2n9WGe2R8PzgCmr3EgVLrjyBxWezF0jLHwVN8efS3rCj/EWgvIWgb9tarpVUDK/b58Da+sqqls3eNbuv7pr+eoZG+SrDK6nWeL3c6H5Apxz7LjVc1uTIdsIXxuOLYA4/ilBmSVIzuDWfdRUfhHdY6+cn8HFRm+2hM8AnXGXws9555KrUB5qihylGa8subX2Nn6UwNR1AkUTV74bU=");
+        let mut ds = DS {
+            key_tag: 20326,
+            algorithm: RSASHA256,
+            digest_type: SHA256,
+            digest: from_hex("E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D")
+                .unwrap(),
+        };
+        assert!(matches_ds(&root, &ds));
+
+        ds.digest[0] ^= 1;
+        assert!(!matches_ds(&root, &ds));
+    }
+
+    #[test]
+    fn sign_rrset_works() {
+        let secret_key = [7; 32];
+        let dnskeys = [record(
+            "example.net.",
+            Type::DNSKEY,
+            3600,
+            RData::DNSKEY(DNSKEY {
+                flags: DNSKEY::ZONE_KEY,
+                protocol: 3,
+                algorithm: ED25519,
+                public_key: crypto::ed25519::public_key(&secret_key),
+            }),
+        )];
+
+        let rrsigs = [sign_rrset(&a(1), &dnskeys[0], &secret_key, 1000, 2000).unwrap()];
+        assert!(verify_rrset(&a(1), &rrsigs, &dnskeys, 1500).is_ok());
+        assert_eq!(
+            verify_rrset(&a(2), &rrsigs, &dnskeys, 1500),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn weakest_security_wins() {
+        let bogus = Security::Bogus(ValidationError::Expired);
+        assert_eq!(Security::Secure.min(Security::Insecure), Security::Insecure);
+        assert_eq!(
+            Security::Indeterminate.min(Security::Secure),
+            Security::Indeterminate
+        );
+        assert_eq!(Security::Insecure.min(bogus.clone()), bogus);
+    }
+}
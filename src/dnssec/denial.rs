@@ -0,0 +1,534 @@
+//! Authenticated denial of existence: https://datatracker.ietf.org/doc/html/rfc4035#section-5.4
+
+use core::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::{
+    error::DnsError,
+    message::{
+        canonical_name_bytes, cmp_names, is_subdomain, names_eq, parent_name, RData, Record, Type,
+        NSEC, NSEC3,
+    },
+    util::from_base32hex,
+};
+
+/// Hash algorithm of NSEC3 records: https://datatracker.ietf.org/doc/html/rfc5155#section-11
+pub const NSEC3_SHA1: u8 = 1;
+
+/// Iterations above which NSEC3 records are treated as insecure instead of hashed: https://datatracker.ietf.org/doc/html/rfc9276#section-3.2
+pub const MAX_NSEC3_ITERATIONS: u16 = 150;
+
+/// Hashed owner name of `name` in NSEC3 records: https://datatracker.ietf.org/doc/html/rfc5155#section-5
+pub fn nsec3_hash(name: &str, salt: &[u8], iterations: u16) -> Result<Vec<u8>, DnsError> {
+    let mut data = canonical_name_bytes(name)?;
+    for _ in 0..=iterations {
+        data.extend_from_slice(salt);
+        data = crypto::sha1(&data).to_vec();
+    }
+    Ok(data)
+}
+
+/// What NSEC or NSEC3 records prove about a name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denial {
+    /// The name exists, or a wildcard expands to it, with only these types
+    Types(Vec<u16>),
+    /// Neither the name nor a wildcard that could expand to it exist
+    NoName,
+    /// An opt-out NSEC3 record covers the name, which may be an unsigned delegation
+    OptOut,
+    /// The NSEC3 records use more than [`MAX_NSEC3_ITERATIONS`] iterations and prove nothing
+    TooManyIterations,
+}
+
+/// What the NSEC or NSEC3 records in `records` prove about `name` in `zone`
+///
+/// Their signatures have to be verified beforehand. A name that doesn't exist is only denied
+/// together with the wildcard at its closest encloser: https://datatracker.ietf.org/doc/html/rfc4035#section-5.4
+pub fn prove_denial(name: &str, zone: &str, records: &[Record]) -> Option<Denial> {
+    if !is_subdomain(name, zone) {
+        return None;
+    }
+    prove_with_nsec(name, zone, records)
+        .or_else(|| prove_with_nsec3(name, zone, &mut Nsec3Chain::new(zone, records)))
+}
+
+/// What the NSEC or NSEC3 records in `records` prove about the name one label below `encloser`
+/// towards `name` in `zone`
+///
+/// Answers expanded from the wildcard at `encloser` need [`Denial::NoName`]: https://datatracker.ietf.org/doc/html/rfc5155#section-8.8
+pub fn prove_expansion(
+    name: &str,
+    encloser: &str,
+    zone: &str,
+    records: &[Record],
+) -> Option<Denial> {
+    if !is_subdomain(name, encloser) || names_eq(name, encloser) || !is_subdomain(encloser, zone) {
+        return None;
+    }
+    let next_closer = next_closer(name, encloser);
+    if nsec_covering(&nsec_records(zone, records), next_closer).is_some() {
+        return Some(Denial::NoName);
+    }
+
+    let mut chain = Nsec3Chain::new(zone, records);
+    if chain.too_many_iterations() {
+        return Some(Denial::TooManyIterations);
+    }
+    chain.covering(next_closer).map(|_| Denial::NoName)
+}
+
+/// Ancestor of `name` one label below `encloser`, which has to be above `name`
+fn next_closer<'a>(name: &'a str, encloser: &str) -> &'a str {
+    let mut next_closer = name;
+    while let Some(parent) = parent_name(next_closer).filter(|name| !names_eq(name, encloser)) {
+        next_closer = parent;
+    }
+    next_closer
+}
+
+/// Name of the wildcard directly below `encloser`
+fn wildcard(encloser: &str) -> String {
+    format!("*.{encloser}")
+}
+
+/// Whether `value` is strictly between `owner` and `next`, `next` wraps around to the start
+/// after the last record
+fn covers<T: ?Sized>(owner: &T, next: &T, value: &T, cmp: impl Fn(&T, &T) -> Ordering) -> bool {
+    cmp(owner, value) == Ordering::Less
+        && (cmp(value, next) == Ordering::Less || cmp(next, owner) != Ordering::Greater)
+}
+
+fn nsec_records<'a>(zone: &str, records: &'a [Record]) -> Vec<(&'a str, &'a NSEC)> {
+    records
+        .iter()
+        .filter_map(|record| match &record.rdata {
+            RData::NSEC(nsec) if is_subdomain(&record.name, zone) => {
+                Some((record.name.as_str(), nsec))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The NSEC record proving `name` doesn't exist, not even as an empty non-terminal
+fn nsec_covering<'a>(nsecs: &[(&'a str, &'a NSEC)], name: &str) -> Option<(&'a str, &'a NSEC)> {
+    // Names below a delegation are in the child zone, which the parent can't deny
+    let delegation =
+        |types: &[u16]| types.contains(&(Type::NS as u16)) && !types.contains(&(Type::SOA as u16));
+    nsecs
+        .iter()
+        .find(|(owner, nsec)| {
+            covers(*owner, nsec.next_domain_name.as_str(), name, cmp_names)
+                && !is_subdomain(&nsec.next_domain_name, name)
+                && !(delegation(&nsec.types) && is_subdomain(name, owner))
+        })
+        .copied()
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc4035#section-5.4
+fn prove_with_nsec(name: &str, zone: &str, records: &[Record]) -> Option<Denial> {
+    let nsecs = nsec_records(zone, records);
+    let matching = |name: &str| {
+        nsecs
+            .iter()
+            .find(|(owner, _)| names_eq(owner, name))
+            .map(|(_, nsec)| Denial::Types(nsec.types.clone()))
+    };
+
+    if let Some(denial) = matching(name) {
+        return Some(denial);
+    }
+    // A gap ending below the name makes it an empty non-terminal: https://datatracker.ietf.org/doc/html/rfc7129#section-3
+    if nsecs.iter().any(|(owner, nsec)| {
+        covers(*owner, nsec.next_domain_name.as_str(), name, cmp_names)
+            && is_subdomain(&nsec.next_domain_name, name)
+    }) {
+        return Some(Denial::Types(vec![]));
+    }
+
+    // The closest encloser is the longest ancestor the name shares with either end of the gap
+    let (owner, nsec) = nsec_covering(&nsecs, name)?;
+    let common_ancestor = |other: &str| {
+        let mut ancestor = name;
+        while !is_subdomain(other, ancestor) {
+            ancestor = parent_name(ancestor)?;
+        }
+        Some(ancestor)
+    };
+    let (a, b) = (
+        common_ancestor(owner)?,
+        common_ancestor(&nsec.next_domain_name)?,
+    );
+    let encloser = if is_subdomain(a, b) { a } else { b };
+    if !is_subdomain(encloser, zone) {
+        return None;
+    }
+
+    let wildcard = wildcard(encloser);
+    matching(&wildcard).or_else(|| nsec_covering(&nsecs, &wildcard).map(|_| Denial::NoName))
+}
+
+/// Lowercased name, salt and iterations of an NSEC3 hash
+type HashInput<'a> = (String, &'a [u8], u16);
+
+/// NSEC3 records of a zone by hashed owner, each name is hashed once per salt and iterations
+struct Nsec3Chain<'a> {
+    records: Vec<(Vec<u8>, &'a NSEC3)>,
+    /// `None` for names that can't be hashed
+    hashes: HashMap<HashInput<'a>, Option<Vec<u8>>>,
+}
+
+impl<'a> Nsec3Chain<'a> {
+    fn new(zone: &str, records: &'a [Record]) -> Nsec3Chain<'a> {
+        let records = records
+            .iter()
+            .filter_map(|record| match &record.rdata {
+                RData::NSEC3(nsec3) if nsec3.hash_algorithm == NSEC3_SHA1 => {
+                    let (hash, owner_zone) = record.name.split_once('.')?;
+                    names_eq(owner_zone, zone).then_some((from_base32hex(hash)?, nsec3))
+                }
+                _ => None,
+            })
+            .collect();
+        Nsec3Chain {
+            records,
+            hashes: HashMap::new(),
+        }
+    }
+
+    fn too_many_iterations(&self) -> bool {
+        self.records
+            .iter()
+            .any(|(_, nsec3)| nsec3.iterations > MAX_NSEC3_ITERATIONS)
+    }
+
+    /// First record for which `found` holds given its hashed owner and the hash of `name`
+    fn find(
+        &mut self,
+        name: &str,
+        found: impl Fn(&[u8], &NSEC3, &[u8]) -> bool,
+    ) -> Option<&'a NSEC3> {
+        let key = name.to_ascii_lowercase();
+        let Nsec3Chain { records, hashes } = self;
+        records
+            .iter()
+            .find(|(owner, nsec3)| {
+                hashes
+                    .entry((key.clone(), &nsec3.salt, nsec3.iterations))
+                    .or_insert_with(|| nsec3_hash(name, &nsec3.salt, nsec3.iterations).ok())
+                    .as_deref()
+                    .is_some_and(|hash| found(owner, nsec3, hash))
+            })
+            .map(|(_, nsec3)| *nsec3)
+    }
+
+    fn matching(&mut self, name: &str) -> Option<&'a NSEC3> {
+        self.find(name, |owner, _, hash| owner == hash)
+    }
+
+    fn covering(&mut self, name: &str) -> Option<&'a NSEC3> {
+        self.find(name, |owner, nsec3, hash| {
+            covers(owner, &nsec3.next_hashed_owner, hash, Ord::cmp)
+        })
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc5155#section-8
+fn prove_with_nsec3(name: &str, zone: &str, chain: &mut Nsec3Chain) -> Option<Denial> {
+    if chain.too_many_iterations() {
+        return Some(Denial::TooManyIterations);
+    }
+
+    if let Some(nsec3) = chain.matching(name) {
+        return Some(Denial::Types(nsec3.types.clone()));
+    }
+
+    // The closest encloser exists and the name one label below it doesn't: https://datatracker.ietf.org/doc/html/rfc5155#section-8.3
+    let mut next_closer = name;
+    while let Some(encloser) = parent_name(next_closer).filter(|name| is_subdomain(name, zone)) {
+        if chain.matching(encloser).is_some() {
+            let nsec3 = chain.covering(next_closer)?;
+            if nsec3.is_opt_out() {
+                return Some(Denial::OptOut);
+            }
+
+            // Neither does the wildcard at the closest encloser: https://datatracker.ietf.org/doc/html/rfc5155#section-8.4
+            let wildcard = wildcard(encloser);
+            return match chain.matching(&wildcard) {
+                Some(nsec3) => Some(Denial::Types(nsec3.types.clone())),
+                None => chain.covering(&wildcard).map(|_| Denial::NoName),
+            };
+        }
+        next_closer = encloser;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{message::Class, util::Base32Hex};
+
+    use super::*;
+
+    fn record(name: &str, r#type: Type, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl: 3600,
+            rdata,
+        }
+    }
+
+    fn types(types: &[Type]) -> Vec<u16> {
+        types.iter().map(|r#type| *r#type as u16).collect()
+    }
+
+    fn nsec(name: &str, next: &str, present: &[Type]) -> Record {
+        record(
+            name,
+            Type::NSEC,
+            RData::NSEC(NSEC {
+                next_domain_name: String::from(next),
+                types: types(present),
+            }),
+        )
+    }
+
+    // https://datatracker.ietf.org/doc/html/rfc5155#appendix-A
+    const SALT: [u8; 4] = [0xaa, 0xbb, 0xcc, 0xdd];
+
+    fn hashed(name: &str) -> Vec<u8> {
+        nsec3_hash(name, &SALT, 12).unwrap()
+    }
+
+    fn nsec3(name: &str, next: &str, flags: u8, present: &[Type]) -> Record {
+        record(
+            &format!("{}.example.", Base32Hex(&hashed(name))),
+            Type::NSEC3,
+            RData::NSEC3(NSEC3 {
+                hash_algorithm: NSEC3_SHA1,
+                flags,
+                iterations: 12,
+                salt: SALT.to_vec(),
+                next_hashed_owner: hashed(next),
+                types: types(present),
+            }),
+        )
+    }
+
+    #[test]
+    fn nsec3_hash_works() {
+        assert_eq!(
+            Base32Hex(&hashed("example.")).to_string(),
+            "0P9MHAVEQVM6T7VBL5LOP2U3T2RP3TOM"
+        );
+        assert_eq!(
+            Base32Hex(&hashed("A.Example.")).to_string(),
+            "35MTHGPGCU1QG68FAB165KLNSNK3DPVL"
+        );
+        assert_eq!(
+            Base32Hex(&hashed("ns1.example.")).to_string(),
+            "2T7B4G4VSA5SMI47K61MV5BV1A22BOJR"
+        );
+    }
+
+    #[test]
+    fn nsec_proves_denial() {
+        let records = [
+            nsec("example.", "a.example.", &[Type::NS, Type::SOA, Type::NSEC]),
+            nsec("a.example.", "c.example.", &[Type::NS, Type::NSEC]),
+            nsec("c.example.", "example.", &[Type::A, Type::NSEC]),
+        ];
+
+        assert_eq!(
+            prove_denial("C.example.", "example.", &records),
+            Some(Denial::Types(types(&[Type::A, Type::NSEC])))
+        );
+        assert_eq!(
+            prove_denial("b.example.", "example.", &records),
+            Some(Denial::NoName)
+        );
+        // The last record wraps around to the apex
+        assert_eq!(
+            prove_denial("z.example.", "example.", &records),
+            Some(Denial::NoName)
+        );
+        assert_eq!(prove_denial("www.a.example.", "example.", &records), None);
+        assert_eq!(prove_denial("www.example.org.", "example.", &records), None);
+
+        // d.example. exists as an empty non-terminal
+        let records = [nsec("c.example.", "x.d.example.", &[Type::A])];
+        assert_eq!(
+            prove_denial("d.example.", "example.", &records),
+            Some(Denial::Types(vec![]))
+        );
+    }
+
+    #[test]
+    fn nsec_denials_need_the_wildcard() {
+        let records = [
+            nsec("a.example.", "c.example.", &[Type::NS, Type::NSEC]),
+            nsec("c.example.", "example.", &[Type::A, Type::NSEC]),
+        ];
+        // Nothing proves *.example. doesn't exist
+        assert_eq!(prove_denial("b.example.", "example.", &records), None);
+
+        let wildcard = nsec("*.example.", "a.example.", &[Type::TXT, Type::NSEC]);
+        assert_eq!(
+            prove_denial("b.example.", "example.", &[wildcard, records[0].clone()]),
+            Some(Denial::Types(types(&[Type::TXT, Type::NSEC])))
+        );
+    }
+
+    #[test]
+    fn nsec_proves_expansion() {
+        let records = [nsec("a.example.", "c.example.", &[Type::A, Type::NSEC])];
+
+        assert_eq!(
+            prove_expansion("www.b.example.", "example.", "example.", &records),
+            Some(Denial::NoName)
+        );
+        assert_eq!(
+            prove_expansion("www.d.example.", "example.", "example.", &records),
+            None
+        );
+        assert_eq!(
+            prove_expansion("b.example.", "b.example.", "example.", &records),
+            None
+        );
+        assert_eq!(
+            prove_expansion("b.example.", "example.", "org.", &records),
+            None
+        );
+    }
+
+    #[test]
+    fn nsec3_proves_denial() {
+        // Hash order is example., ns1.example., a.example.
+        let chain = |flags: u8| {
+            vec![
+                nsec3("example.", "ns1.example.", flags, &[Type::NS, Type::SOA]),
+                nsec3("ns1.example.", "a.example.", flags, &[Type::A]),
+                nsec3("a.example.", "example.", flags, &[Type::NS]),
+            ]
+        };
+
+        assert_eq!(
+            prove_denial("ns1.example.", "example.", &chain(0)),
+            Some(Denial::Types(types(&[Type::A])))
+        );
+        assert_eq!(
+            prove_denial("b.example.", "example.", &chain(0)),
+            Some(Denial::NoName)
+        );
+        assert_eq!(
+            prove_denial("www.b.example.", "example.", &chain(NSEC3::OPT_OUT)),
+            Some(Denial::OptOut)
+        );
+
+        // Without the closest encloser there is no proof
+        assert_eq!(prove_denial("b.example.", "example.", &chain(0)[1..]), None);
+        assert_eq!(prove_denial("b.example.", "example.org.", &chain(0)), None);
+    }
+
+    #[test]
+    fn nsec3_denials_need_the_wildcard() {
+        // Hash order is example., ns1.example., x.example., b.example., *.example., w.example.
+        let encloser = nsec3("example.", "ns1.example.", 0, &[Type::NS, Type::SOA]);
+        let next_closer = nsec3("x.example.", "*.example.", 0, &[Type::A]);
+        assert_eq!(
+            prove_denial(
+                "b.example.",
+                "example.",
+                &[encloser.clone(), next_closer.clone()]
+            ),
+            None
+        );
+
+        let wildcard = nsec3("*.example.", "w.example.", 0, &[Type::TXT]);
+        assert_eq!(
+            prove_denial(
+                "b.example.",
+                "example.",
+                &[encloser.clone(), next_closer, wildcard]
+            ),
+            Some(Denial::Types(types(&[Type::TXT])))
+        );
+
+        let both = nsec3("x.example.", "w.example.", 0, &[Type::A]);
+        assert_eq!(
+            prove_denial("b.example.", "example.", &[encloser, both]),
+            Some(Denial::NoName)
+        );
+    }
+
+    #[test]
+    fn nsec3_proves_expansion() {
+        // Covers b.example. but not www.b.example.
+        let records = [nsec3("x.example.", "*.example.", 0, &[Type::A])];
+
+        assert_eq!(
+            prove_expansion("www.b.example.", "example.", "example.", &records),
+            Some(Denial::NoName)
+        );
+        assert_eq!(
+            prove_expansion("www.b.example.", "b.example.", "example.", &records),
+            None
+        );
+    }
+
+    #[test]
+    fn nsec3_iterations_are_capped() {
+        let chain = |iterations: u16| {
+            let mut records = vec![
+                nsec3("example.", "ns1.example.", 0, &[Type::NS, Type::SOA]),
+                nsec3("x.example.", "w.example.", 0, &[Type::A]),
+            ];
+            for record in &mut records {
+                if let RData::NSEC3(nsec3) = &mut record.rdata {
+                    nsec3.iterations = iterations;
+                }
+            }
+            records
+        };
+
+        // Too costly to hash, whatever the records say
+        assert_eq!(
+            prove_denial("b.example.", "example.", &chain(MAX_NSEC3_ITERATIONS + 1)),
+            Some(Denial::TooManyIterations)
+        );
+        assert_eq!(
+            prove_expansion(
+                "www.b.example.",
+                "example.",
+                "example.",
+                &chain(MAX_NSEC3_ITERATIONS + 1)
+            ),
+            Some(Denial::TooManyIterations)
+        );
+        assert_eq!(
+            prove_denial("b.example.", "example.", &chain(12)),
+            Some(Denial::NoName)
+        );
+    }
+
+    #[test]
+    fn nsec3_hashes_are_computed_once() {
+        let records = [
+            nsec3("example.", "ns1.example.", 0, &[Type::NS, Type::SOA]),
+            nsec3("ns1.example.", "x.example.", 0, &[Type::A]),
+            nsec3("x.example.", "w.example.", 0, &[Type::A]),
+        ];
+        let mut chain = Nsec3Chain::new("example.", &records);
+
+        assert_eq!(
+            prove_with_nsec3("B.example.", "example.", &mut chain),
+            Some(Denial::NoName)
+        );
+        // b.example., example. and *.example. once, though all three records were checked
+        assert_eq!(chain.hashes.len(), 3);
+    }
+}
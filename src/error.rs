@@ -1,10 +1,15 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, io};
 
 use bitreader::error::BitError;
 
+use crate::message::RCode;
+
 #[derive(Debug)]
 pub enum DnsError {
     ParseError(&'static str),
+    IoError(io::Error),
+    /// The server answered with an error, like NXDOMAIN for names that don't exist
+    ResponseCode(RCode),
 }
 
 impl Error for DnsError {}
@@ -13,7 +18,7 @@ impl From<BitError> for DnsError {
     fn from(e: BitError) -> Self {
         match e {
             BitError::EndOfBytes => DnsError::ParseError("Unexpected end of bytes"),
-            BitError::InvalidCountSize => panic!("Invalid count size"),
+            BitError::InvalidCountSize => DnsError::ParseError("Invalid bit count"),
         }
     }
 }
@@ -24,10 +29,18 @@ impl From<&'static str> for DnsError {
     }
 }
 
+impl From<io::Error> for DnsError {
+    fn from(e: io::Error) -> Self {
+        DnsError::IoError(e)
+    }
+}
+
 impl Display for DnsError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DnsError::ParseError(e) => write!(f, "{e}"),
+            DnsError::IoError(e) => write!(f, "{e}"),
+            DnsError::ResponseCode(rcode) => write!(f, "Server responded with {rcode}"),
         }
     }
 }
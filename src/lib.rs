@@ -1,3 +1,8 @@
+// DNS type and class mnemonics are kept as written in the RFCs
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod dnssec;
+
 pub mod message;
 
 pub mod util;
@@ -6,4 +11,6 @@ pub mod error;
 
 pub mod resolver;
 
+pub mod reverse;
+
 pub type Result<T> = core::result::Result<T, error::DnsError>;
@@ -1,86 +1,131 @@
 use std::fmt::Debug;
-use std::net::{Ipv4Addr, Ipv6Addr};
 
-use bitreader::BitReader;
-use rand::xorshift_u16;
+use bitreader::{BitWriter, ByteReader};
+use rand::Rng;
 
 use crate::error::DnsError;
 
+mod builder;
+pub use builder::MessageBuilder;
+
+mod display;
+
+mod edns;
+pub use edns::*;
+
 mod header;
-use header::Header;
+pub use header::Header;
+
+#[cfg(feature = "serde")]
+mod json;
+
+mod name;
+pub use name::*;
+
+mod message_ref;
+pub use message_ref::*;
 
 mod opcode;
-use opcode::*;
+pub use opcode::*;
 
 mod record;
-use record::*;
+pub use record::*;
+
+mod rdata;
+pub use rdata::*;
 
 mod question;
-use question::*;
+pub use question::*;
 
 mod rcode;
-use rcode::*;
+pub use rcode::*;
+
+#[cfg(test)]
+mod arbitrary;
 
-fn read_name(mut bit_reader: &mut BitReader) -> Result<String, DnsError> {
+/// Read a possibly compressed domain name: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4
+fn read_name(reader: &mut ByteReader) -> Result<String, DnsError> {
     let mut name = String::new();
+    let mut length_on_wire = 1;
+
+    // Where to continue reading after the name, set when the first pointer is followed
+    let mut end = None;
+    // Pointers have to point before the previous one, which rules out loops
+    let mut limit = reader.get_pointer();
+
+    loop {
+        let length = reader.next_u8()?;
+
+        match length {
+            0 => break,
+            1..=63 => {
+                push_label(&mut name, reader.next_bytes(length as usize)?);
+                length_on_wire += 1 + length as usize;
+            }
+            0xc0..=0xff => {
+                let pointer = ((length as usize & 0x3f) << 8) | reader.next_u8()? as usize;
+                if pointer >= limit {
+                    return Err(DnsError::ParseError("Invalid compression pointer"));
+                }
+
+                end.get_or_insert(reader.get_pointer());
+                limit = pointer;
+                reader.set_pointer(pointer);
+            }
+            _ => return Err(DnsError::ParseError("Invalid label type")),
+        }
 
-    // Max of 255 bytes per name: https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4
-    for _ in 0..255 {
-        if bit_reader.next_u8(2)? == 3 {
-            let start = bit_reader.next_u8(6)? as usize;
-            bit_reader.skip(2);
-            let offset = bit_reader.next_u8(6)? as usize;
+        // Max of 255 bytes per name: https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4
+        if length_on_wire > 255 {
+            return Err(DnsError::ParseError("Name too long"));
+        }
+    }
 
-            let current_pointer = bit_reader.get_pointer();
-            bit_reader.set_pointer(start * 8 + offset * 8);
+    if let Some(end) = end {
+        reader.set_pointer(end);
+    }
 
-            name.push_str(&read_name(&mut bit_reader)?);
+    Ok(name)
+}
 
-            bit_reader.set_pointer(current_pointer);
+/// Write a domain name as a sequence of labels: https://datatracker.ietf.org/doc/html/rfc1035#section-3.1
+fn write_name(writer: &mut BitWriter, name: &str) -> Result<(), DnsError> {
+    writer.write_bytes(&name_bytes(name)?);
+    Ok(())
+}
 
-            return Ok(name);
-        }
+/// Group records into RRsets, records with the same name, type and class: https://datatracker.ietf.org/doc/html/rfc2181#section-5
+pub(crate) fn rrsets(records: &[Record]) -> Vec<Vec<&Record>> {
+    let mut rrsets: Vec<Vec<&Record>> = vec![];
 
-        let char_count = bit_reader.next_u8(6)?;
+    for record in records {
+        let rrset = rrsets.iter_mut().find(|rrset| {
+            rrset[0].name.eq_ignore_ascii_case(&record.name)
+                && rrset[0].r#type as u16 == record.r#type as u16
+                && rrset[0].class as u16 == record.class as u16
+        });
 
-        if char_count == 0 {
-            break;
+        match rrset {
+            Some(rrset) => rrset.push(record),
+            None => rrsets.push(vec![record]),
         }
-
-        for _ in 0..char_count {
-            let n = bit_reader.next_u8(8)?;
-            name.push(n as char)
-        }
-        name.push('.');
     }
 
-    Ok(name)
+    rrsets
 }
 
 /// Read DNS Resource Records according to: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.3
-fn read_record(mut bit_reader: &mut BitReader) -> Result<Record, DnsError> {
-    let name = read_name(&mut bit_reader)?;
+fn read_record(reader: &mut ByteReader) -> Result<Record, DnsError> {
+    let name = read_name(reader)?;
 
-    let type_value = bit_reader.next_u16(16)?;
+    let type_value = reader.next_u16()?;
     let r#type = Type::from_u16(type_value).ok_or(DnsError::ParseError("Invalid type"))?;
-    let class_value = bit_reader.next_u16(16)?;
-    let class = Class::from_u16(class_value).expect("Invalid class");
-    let ttl = bit_reader.next_u32(32)?;
+    let class_value = reader.next_u16()?;
+    let class = Class::from_u16(class_value).ok_or(DnsError::ParseError("Invalid class"))?;
+    let ttl = reader.next_u32()?;
 
-    // TODO use rdlength somehow
-    let _rdlength = bit_reader.next_u16(16)?;
-    let rdata = match r#type {
-        Type::AAAA => {
-            let c = bit_reader.next_u128(128)?;
-            Ipv6Addr::from(c).to_string()
-        }
-        Type::A => {
-            let c = bit_reader.next_u32(32)?;
-            Ipv4Addr::from(c).to_string()
-        }
-        Type::CNAME => read_name(&mut bit_reader)?,
-        _ => String::new(), // TODO implement other RData formats
-    };
+    let rdlength = reader.next_u16()?;
+    let rdata = RData::read(r#type, reader, rdlength)?;
 
     Ok(Record {
         name,
@@ -91,7 +136,12 @@ fn read_record(mut bit_reader: &mut BitReader) -> Result<Record, DnsError> {
     })
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "json::MessageJson", try_from = "json::MessageJson")
+)]
 pub struct Message {
     header: Header,
 
@@ -99,80 +149,247 @@ pub struct Message {
     answers: Vec<Record>,
     authority: Vec<Record>,
     additional: Vec<Record>,
+    edns: Option<Edns>,
 }
 
 /// DNS Message Parser following https://datatracker.ietf.org/doc/html/rfc1035
 impl Message {
-    pub fn query(qname: String) -> Message {
-        let questions = vec![Question {
-            qname: qname,
-            qtype: QType::A,
-            qclass: QClass::Any,
-        }];
-
-        let header = Header {
-            id: xorshift_u16(),
-            response: true,
-            opcode: Opcode::Query,
-            authenticated_data: false,
-            checking_disabled: false,
-            rcode: RCode::NoError,
-            authoritive_answer: false,
-            truncated: false,
-            recursion_desired: false,
-            recursion_available: false,
-            question_count: questions.len() as u16,
-            answer_count: 0,
-            authority_count: 0,
-            additional_resource_count: 0,
-        };
+    pub fn builder() -> MessageBuilder {
+        MessageBuilder::new()
+    }
 
-        Message {
-            header,
-            questions,
-            answers: vec![],
-            authority: vec![],
-            additional: vec![],
+    /// Create a recursive query for `qname` in the internet class, with an ID taken from `rng`
+    pub fn query(qname: String, qtype: QType, rng: &mut impl Rng) -> Message {
+        Message::builder()
+            .id(rng.next_u16())
+            .recursion_desired(true)
+            .question(Question {
+                qname,
+                qtype,
+                qclass: QClass::IN,
+            })
+            .build()
+    }
+
+    /// Start a response to `request`, copying its ID, opcode, RD flag and questions
+    ///
+    /// EDNS is included when the request used it, echoing the DO bit: https://datatracker.ietf.org/doc/html/rfc3225#section-3
+    pub fn response_to(request: &Message) -> MessageBuilder {
+        let mut builder = Message::builder()
+            .id(request.header.id)
+            .response(true)
+            .opcode(request.header.opcode)
+            .recursion_desired(request.header.recursion_desired);
+
+        for question in request.questions.iter() {
+            builder = builder.question(question.clone());
+        }
+
+        if let Some(edns) = &request.edns {
+            builder = builder.edns(Edns {
+                dnssec_ok: edns.dnssec_ok,
+                ..Default::default()
+            });
         }
+
+        builder
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Set the AD bit, for a resolver validating responses itself
+    pub fn set_authenticated_data(&mut self, authenticated_data: bool) {
+        self.header.authenticated_data = authenticated_data;
+    }
+
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    /// Questions can be changed in place, but not added or removed as the header counts them
+    pub fn questions_mut(&mut self) -> &mut [Question] {
+        &mut self.questions
+    }
+
+    pub fn answers(&self) -> &[Record] {
+        &self.answers
+    }
+
+    pub fn authority(&self) -> &[Record] {
+        &self.authority
+    }
+
+    /// Records of the additional section, without the OPT pseudo-record which is available through [`Message::edns`]
+    pub fn additional(&self) -> &[Record] {
+        &self.additional
+    }
+
+    pub fn edns(&self) -> Option<&Edns> {
+        self.edns.as_ref()
+    }
+
+    /// Set the section counts of the header to the amount of questions and records
+    fn update_counts(&mut self) {
+        self.header.question_count = self.questions.len() as u16;
+        self.header.answer_count = self.answers.len() as u16;
+        self.header.authority_count = self.authority.len() as u16;
+        self.header.additional_resource_count =
+            self.additional.len() as u16 + self.edns.is_some() as u16;
     }
 
     /// Return a byte array of the DNS Message in big-endian order
-    pub fn as_bytes(&self) -> Vec<u8> {
-        let bytes = self.header.as_bytes();
-        return bytes;
+    ///
+    /// Fails when a name has a label longer than 63 bytes or is longer than 255 bytes
+    pub fn as_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        self.check_rcode()?;
+        let mut writer = BitWriter::new();
+        self.header.write(&mut writer);
+
+        for question in self.questions.iter() {
+            question.write(&mut writer)?;
+        }
+        for record in self
+            .answers
+            .iter()
+            .chain(self.authority.iter())
+            .chain(self.additional.iter())
+        {
+            record.write(&mut writer)?;
+        }
+        if let Some(edns) = &self.edns {
+            edns.write(&mut writer, self.header.rcode)?;
+        }
+
+        Ok(writer.into_bytes())
+    }
+
+    /// Extended RCODEs don't fit the header alone: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3
+    fn check_rcode(&self) -> Result<(), DnsError> {
+        if self.edns.is_none() && self.header.rcode.extended_bits() > 0 {
+            return Err(DnsError::ParseError("Extended RCODE without EDNS"));
+        }
+        Ok(())
+    }
+
+    /// The largest response the sender of this message is able to receive over UDP: https://datatracker.ietf.org/doc/html/rfc6891#section-6.2.5
+    pub fn max_response_size(&self) -> usize {
+        match &self.edns {
+            Some(edns) => usize::max(edns.udp_payload_size as usize, 512),
+            None => 512,
+        }
+    }
+
+    /// Like [`Message::as_bytes`] but at most `max_size` bytes long
+    ///
+    /// Whole RRsets of the additional and then the authority section are dropped until the message fits,
+    /// when not all answers fit the message is marked as truncated: https://datatracker.ietf.org/doc/html/rfc2181#section-9
+    ///
+    /// Fails when the header, questions and OPT pseudo-record alone don't fit.
+    pub fn as_bytes_with_limit(&self, max_size: usize) -> Result<Vec<u8>, DnsError> {
+        self.check_rcode()?;
+        let mut header = self.header.clone();
+
+        // The header is written last, when the counts are known
+        let mut writer = BitWriter::new();
+        writer.skip(12 * 8);
+        for question in self.questions.iter() {
+            question.write(&mut writer)?;
+        }
+
+        // The OPT pseudo-record is always kept so the receiver knows EDNS is supported
+        let edns = self
+            .edns
+            .as_ref()
+            .map(|edns| edns.as_bytes(self.header.rcode))
+            .transpose()?
+            .unwrap_or_default();
+        let mut remaining = max_size
+            .checked_sub(writer.as_bytes().len() + edns.len())
+            .ok_or(DnsError::ParseError(
+                "Questions don't fit in the size limit",
+            ))?;
+
+        let mut counts = [0; 3];
+        let sections = [&self.answers, &self.authority, &self.additional];
+        'sections: for (i, section) in sections.into_iter().enumerate() {
+            for rrset in rrsets(section) {
+                let mut rrset_writer = BitWriter::new();
+                for record in rrset.iter() {
+                    record.write(&mut rrset_writer)?;
+                }
+                let bytes = rrset_writer.into_bytes();
+
+                if bytes.len() > remaining {
+                    // Only missing answers make the response truncated
+                    header.truncated |= i == 0;
+                    break 'sections;
+                }
+
+                remaining -= bytes.len();
+                counts[i] += rrset.len() as u16;
+                writer.write_bytes(&bytes);
+            }
+        }
+        writer.write_bytes(&edns);
+
+        header.answer_count = counts[0];
+        header.authority_count = counts[1];
+        header.additional_resource_count = counts[2] + self.edns.is_some() as u16;
+
+        let end = writer.get_pointer();
+        writer.set_pointer(0);
+        header.write(&mut writer);
+        writer.set_pointer(end);
+
+        Ok(writer.into_bytes())
     }
 
     /// Implements https://datatracker.ietf.org/doc/html/rfc1035#section-4
     pub fn from_bytes(bytes: &[u8]) -> Result<Message, DnsError> {
-        let mut bit_reader = BitReader::from_bytes(bytes);
-        let header = Header::from_bytes(
-            &bytes[0..12]
-            // bytes
-            //     .take(..12)
-            //     .ok_or(DnsError::ParseError("Not enough bytes for header"))?,
-        )?;
+        let mut reader = ByteReader::from_bytes(bytes);
+        let mut header = Header::read(&mut reader)?;
 
         let mut questions = vec![];
         for _ in 0..header.question_count {
             questions.push(Question {
-                qname: read_name(&mut bit_reader)?,
-                qtype: QType::from_u16(bit_reader.next_u16(16)?)
+                qname: read_name(&mut reader)?,
+                qtype: QType::from_u16(reader.next_u16()?)
                     .ok_or(DnsError::ParseError("Invalid qtype"))?,
-                qclass: QClass::try_from(bit_reader.next_u16(16)?)?,
+                qclass: QClass::try_from(reader.next_u16()?)?,
             });
         }
 
         let mut answers = vec![];
         for _ in 0..header.answer_count {
-            answers.push(read_record(&mut bit_reader)?)
+            answers.push(read_record(&mut reader)?)
         }
         let mut authority = vec![];
         for _ in 0..header.authority_count {
-            authority.push(read_record(&mut bit_reader)?)
+            authority.push(read_record(&mut reader)?)
         }
         let mut additional = vec![];
+        let mut edns = None;
         for _ in 0..header.additional_resource_count {
-            additional.push(read_record(&mut bit_reader)?)
+            // The OPT pseudo-record is identified by its type, which comes after the name
+            let start = reader.get_pointer();
+            let name = read_name(&mut reader)?;
+            if reader.next_u16()? == OPT_TYPE {
+                // Owned by the root: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2
+                if !name.is_empty() {
+                    return Err(DnsError::ParseError("OPT record not owned by the root"));
+                }
+                // Only one OPT pseudo-record is allowed: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.1
+                if edns.is_some() {
+                    return Err(DnsError::ParseError("Multiple OPT records"));
+                }
+                edns = Some(Edns::read(&mut reader, &mut header)?);
+                continue;
+            }
+            reader.set_pointer(start);
+
+            additional.push(read_record(&mut reader)?)
         }
 
         Ok(Message {
@@ -181,12 +398,426 @@ impl Message {
             answers,
             authority,
             additional,
+            edns,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::Ipv4Addr;
+
+    use rand::FixedSequence;
+
+    use super::*;
+
+    #[test]
+    fn as_bytes_works() {
+        let message = Message::builder()
+            .id(0x1234)
+            .recursion_desired(true)
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .build();
+
+        assert_eq!(
+            message.as_bytes().unwrap(),
+            [
+                0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, //
+                7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1,
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_names_are_not_encoded() {
+        let query = |qname: String| {
+            Message::builder()
+                .question(Question {
+                    qname,
+                    qtype: QType::A,
+                    qclass: QClass::IN,
+                })
+                .build()
+        };
+
+        assert!(query(format!("{}.com.", "a".repeat(64)))
+            .as_bytes()
+            .is_err());
+        assert!(query("a.".repeat(128)).as_bytes().is_err());
+        assert!(query("a.".repeat(128)).as_bytes_with_limit(512).is_err());
+
+        // Label bytes outside of printable ASCII are escaped and survive a round trip
+        let bytes = query(String::from("\\195\\188.\\046.")).as_bytes().unwrap();
+        assert_eq!(&bytes[12..18], b"\x02\xc3\xbc\x01.\x00");
+        let decoded = Message::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.questions()[0].qname, "\\195\\188.\\046.");
+    }
+
+    #[test]
+    fn extended_rcodes_use_edns() {
+        let message = Message::builder()
+            .response(true)
+            .rcode(RCode::BadVers)
+            .edns(Edns::default())
+            .build();
+        let bytes = message.as_bytes().unwrap();
+        // NOERROR in the header, 1 in the upper bits of the OPT pseudo-record
+        assert_eq!(bytes[3] & 0xf, 0);
+        assert_eq!(bytes[17], 1);
+        assert_eq!(
+            Message::from_bytes(&bytes).unwrap().header().rcode,
+            RCode::BadVers
+        );
+
+        let message = Message::builder().rcode(RCode::BadVers).build();
+        assert!(message.as_bytes().is_err());
+
+        let mut bytes = Message::builder()
+            .edns(Edns::default())
+            .build()
+            .as_bytes()
+            .unwrap();
+        bytes[11] = 2;
+        bytes.extend_from_within(12..);
+        assert!(Message::from_bytes(&bytes).is_err());
+    }
+
     #[test]
-    fn to_bytes_works() {}
+    fn opt_records_are_owned_by_the_root() {
+        let bytes = Message::builder()
+            .edns(Edns::default())
+            .build()
+            .as_bytes()
+            .unwrap();
+        assert!(Message::from_bytes(&bytes).unwrap().edns().is_some());
+
+        // The OPT pseudo-record owned by a.
+        let mut owned = bytes[..12].to_vec();
+        owned.extend([1, b'a']);
+        owned.extend_from_slice(&bytes[12..]);
+        assert!(Message::from_bytes(&owned).is_err());
+    }
+
+    #[test]
+    fn query_takes_its_id_from_rng() {
+        let mut rng = FixedSequence::new([0x1234, 0x5678]);
+
+        let first = Message::query(String::from("example.com."), QType::A, &mut rng);
+        let second = Message::query(String::from("example.com."), QType::AAAA, &mut rng);
+
+        assert_eq!(first.header().id, 0x1234);
+        assert_eq!(second.header().id, 0x5678);
+        assert!(first.header().recursion_desired);
+    }
+
+    /// Response to `example.com. A` from a recursive resolver, with the answer name compressed and an OPT record
+    const GOLDEN_A: &[u8] = &[
+        0x1f, 0x2e, 0x81, 0x80, 0, 1, 0, 1, 0, 0, 0, 1, //
+        7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1, //
+        0xc0, 12, 0, 1, 0, 1, 0, 0, 0x0e, 0x10, 0, 4, 93, 184, 215, 14, //
+        0, 0, 41, 0x04, 0xd0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    /// Response to `www.github.com. A` where the CNAME target points into the question name
+    const GOLDEN_CNAME: &[u8] = &[
+        0x9a, 0x01, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0, //
+        3, b'w', b'w', b'w', 6, b'g', b'i', b't', b'h', b'u', b'b', 3, b'c', b'o', b'm', 0, 0, 1,
+        0, 1, //
+        0xc0, 12, 0, 5, 0, 1, 0, 0, 0x0e, 0x10, 0, 2, 0xc0, 16, //
+        0xc0, 16, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 140, 82, 121, 4,
+    ];
+
+    /// NXDOMAIN response to `nope.example. A` with the SOA of the zone in the authority section
+    const GOLDEN_NXDOMAIN: &[u8] = &[
+        0xab, 0xcd, 0x81, 0x83, 0, 1, 0, 0, 0, 1, 0, 0, //
+        4, b'n', b'o', b'p', b'e', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0, 0, 1, 0,
+        1, //
+        0xc0, 17, 0, 6, 0, 1, 0, 0, 0x03, 0x84, 0, 33, //
+        2, b'n', b's', 0xc0, 17, 5, b'a', b'd', b'm', b'i', b'n', 0xc0, 17, //
+        0x78, 0x6a, 0x0c, 0x01, 0, 0, 0x1c, 0x20, 0, 0, 0x0e, 0x10, 0, 0x12, 0x75, 0x00, 0, 0,
+        0x03, 0x84,
+    ];
+
+    fn question(qname: &str) -> Question {
+        Question {
+            qname: String::from(qname),
+            qtype: QType::A,
+            qclass: QClass::IN,
+        }
+    }
+
+    fn record(name: &str, r#type: Type, ttl: u32, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl,
+            rdata,
+        }
+    }
+
+    /// Decode `bytes`, compare against `expected` and check the uncompressed encoding decodes the same
+    fn assert_golden(bytes: &[u8], expected: Message) {
+        let message = Message::from_bytes(bytes).unwrap();
+        assert_eq!(message, expected);
+        assert_eq!(
+            Message::from_bytes(&message.as_bytes().unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn golden_a_response() {
+        let expected = Message::builder()
+            .id(0x1f2e)
+            .response(true)
+            .recursion_desired(true)
+            .recursion_available(true)
+            .question(question("example.com."))
+            .answer(record(
+                "example.com.",
+                Type::A,
+                3600,
+                RData::A(Ipv4Addr::new(93, 184, 215, 14)),
+            ))
+            .edns(Edns::default())
+            .build();
+
+        assert_golden(GOLDEN_A, expected);
+    }
+
+    #[test]
+    fn golden_cname_response() {
+        let expected = Message::builder()
+            .id(0x9a01)
+            .response(true)
+            .recursion_desired(true)
+            .recursion_available(true)
+            .question(question("www.github.com."))
+            .answer(record(
+                "www.github.com.",
+                Type::CNAME,
+                3600,
+                RData::CNAME(String::from("github.com.")),
+            ))
+            .answer(record(
+                "github.com.",
+                Type::A,
+                60,
+                RData::A(Ipv4Addr::new(140, 82, 121, 4)),
+            ))
+            .build();
+
+        assert_golden(GOLDEN_CNAME, expected);
+    }
+
+    #[test]
+    fn golden_nxdomain_response() {
+        let expected = Message::builder()
+            .id(0xabcd)
+            .response(true)
+            .recursion_desired(true)
+            .recursion_available(true)
+            .rcode(RCode::NameError)
+            .question(question("nope.example."))
+            .authority(record(
+                "example.",
+                Type::SOA,
+                900,
+                RData::SOA(SOA {
+                    mname: String::from("ns.example."),
+                    rname: String::from("admin.example."),
+                    serial: 2020215809,
+                    refresh: 7200,
+                    retry: 3600,
+                    expire: 1209600,
+                    minimum: 900,
+                }),
+            ))
+            .build();
+
+        assert_golden(GOLDEN_NXDOMAIN, expected);
+    }
+
+    #[test]
+    fn from_bytes_works() {
+        let message = Message::builder()
+            .id(7)
+            .response(true)
+            .recursion_desired(true)
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::NAPTR,
+                qclass: QClass::IN,
+            })
+            .answer(Record {
+                name: String::from("example.com."),
+                r#type: Type::NAPTR,
+                class: Class::IN,
+                ttl: 3600,
+                rdata: RData::NAPTR(NAPTR {
+                    order: 100,
+                    preference: 10,
+                    flags: b"S".to_vec(),
+                    services: b"SIP+D2U".to_vec(),
+                    regexp: vec![],
+                    replacement: String::from("_sip._udp.example.com."),
+                }),
+            })
+            .additional(a_record("example.com.", 1))
+            .edns(Edns {
+                udp_payload_size: 4096,
+                ..Default::default()
+            })
+            .build();
+
+        let decoded = Message::from_bytes(&message.as_bytes().unwrap()).unwrap();
+
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn from_bytes_follows_compression_pointers() {
+        let bytes = [
+            0, 1, 0x81, 0x80, 0, 1, 0, 1, 0, 0, 0, 0, //
+            3, b'w', b'w', b'w', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0, 0, 5, 0,
+            1, //
+            0xc0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0, 6, 3, b'c', b'd', b'n', 0xc0, 16,
+        ];
+
+        let message = Message::from_bytes(&bytes).unwrap();
+
+        assert_eq!(message.answers()[0].name, "www.example.");
+        assert_eq!(message.answers()[0].rdata.to_string(), "cdn.example.");
+
+        // Pointers have to point backwards
+        let mut looping = bytes.to_vec();
+        looping[46] = 41;
+        assert!(Message::from_bytes(&looping).is_err());
+        assert!(Message::from_bytes(&bytes[..11]).is_err());
+    }
+
+    fn a_record(name: &str, octet: u8) -> Record {
+        Record {
+            name: String::from(name),
+            r#type: Type::A,
+            class: Class::IN,
+            ttl: 60,
+            rdata: RData::A(Ipv4Addr::new(192, 0, 2, octet)),
+        }
+    }
+
+    /// Return the TC flag and the section counts of an encoded message
+    fn truncated_and_counts(bytes: &[u8]) -> (bool, [u16; 4]) {
+        let mut counts = [0; 4];
+        for (i, count) in counts.iter_mut().enumerate() {
+            *count = u16::from_be_bytes([bytes[4 + 2 * i], bytes[5 + 2 * i]]);
+        }
+        (bytes[2] & 0b10 > 0, counts)
+    }
+
+    #[test]
+    fn as_bytes_with_limit_drops_additional_first() {
+        // Each record is 27 bytes long, the header and question 29 bytes
+        let mut builder = Message::builder().question(Question {
+            qname: String::from("example.com."),
+            qtype: QType::A,
+            qclass: QClass::IN,
+        });
+        builder = builder.answer(a_record("example.com.", 1));
+        builder = builder.authority(a_record("example.com.", 2));
+        for octet in 0..2 {
+            builder = builder.additional(a_record("example.net.", octet));
+        }
+        let message = builder.build();
+
+        assert_eq!(
+            message.as_bytes_with_limit(512).unwrap(),
+            message.as_bytes().unwrap()
+        );
+
+        // The additional RRset is not split even though one of its records would fit
+        let bytes = message.as_bytes_with_limit(29 + 3 * 27).unwrap();
+        assert_eq!(bytes.len(), 29 + 2 * 27);
+        assert_eq!(truncated_and_counts(&bytes), (false, [1, 1, 1, 0]));
+
+        let bytes = message.as_bytes_with_limit(29 + 27).unwrap();
+        assert_eq!(truncated_and_counts(&bytes), (false, [1, 1, 0, 0]));
+    }
+
+    #[test]
+    fn as_bytes_with_limit_sets_truncated() {
+        let mut builder = Message::builder().response(true);
+        for octet in 0..32 {
+            builder = builder.answer(a_record("example.com.", octet));
+        }
+        let message = builder.edns(Edns::default()).build();
+
+        let bytes = message.as_bytes_with_limit(512).unwrap();
+        assert_eq!(bytes.len(), 12 + 11);
+        assert_eq!(truncated_and_counts(&bytes), (true, [0, 0, 0, 1]));
+    }
+
+    #[test]
+    fn as_bytes_with_limit_fails_when_questions_do_not_fit() {
+        let message = Message::builder()
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .edns(Edns::default())
+            .build();
+
+        // The header and question take 29 bytes, the OPT record 11 more
+        assert_eq!(message.as_bytes_with_limit(40).unwrap().len(), 40);
+        assert!(message.as_bytes_with_limit(39).is_err());
+        assert!(message.as_bytes_with_limit(12).is_err());
+    }
+
+    #[test]
+    fn response_to_works() {
+        let request = Message::builder()
+            .id(42)
+            .recursion_desired(true)
+            .checking_disabled(true)
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .edns(Edns {
+                udp_payload_size: 4096,
+                dnssec_ok: true,
+                ..Default::default()
+            })
+            .build();
+
+        let response = Message::response_to(&request)
+            .rcode(RCode::NameError)
+            .authority(Record {
+                name: String::from("example.com."),
+                r#type: Type::A,
+                class: Class::IN,
+                ttl: 60,
+                rdata: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            })
+            .build();
+
+        let header = response.header();
+        assert_eq!(header.id, 42);
+        assert!(header.response);
+        assert!(header.recursion_desired);
+        assert!(!header.checking_disabled);
+        assert!(matches!(header.opcode, Opcode::Query));
+        assert!(matches!(header.rcode, RCode::NameError));
+        assert_eq!(header.question_count, 1);
+        assert_eq!(header.authority_count, 1);
+        assert_eq!(response.questions()[0].qname, "example.com.");
+        assert!(response.edns().unwrap().dnssec_ok);
+    }
 }
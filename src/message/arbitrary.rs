@@ -0,0 +1,375 @@
+//! Generators for arbitrary valid messages, used to check that every message survives a round trip through the codec
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use std::cell::RefCell;
+
+use rand::{Rng, XorShift};
+
+use super::*;
+
+/// Amount of messages generated by the round trip test
+const ROUNDS: usize = 500;
+
+thread_local! {
+    // Seeded so failures can be reproduced
+    static RNG: RefCell<XorShift> = RefCell::new(XorShift::new(500));
+}
+
+fn random_u16() -> u16 {
+    RNG.with(|rng| rng.borrow_mut().next_u16())
+}
+
+fn random_u8() -> u8 {
+    random_u16() as u8
+}
+
+fn random_u32() -> u32 {
+    ((random_u16() as u32) << 16) | random_u16() as u32
+}
+
+fn random_bool() -> bool {
+    random_u16() & 1 == 1
+}
+
+/// Random value in `0..max`
+fn random_below(max: usize) -> usize {
+    random_u16() as usize % max
+}
+
+fn random_bytes(max_length: usize) -> Vec<u8> {
+    (0..random_below(max_length + 1))
+        .map(|_| random_u8())
+        .collect()
+}
+
+/// Random printable ASCII, which is valid UTF-8 in every decoder
+fn random_ascii(max_length: usize) -> String {
+    (0..random_below(max_length + 1))
+        .map(|_| (0x20 + random_below(0x5f) as u8) as char)
+        .collect()
+}
+
+/// Random name ending with a dot, or the root name
+///
+/// Labels are mostly letters, digits and hyphens, some are arbitrary bytes which are escaped.
+pub fn name() -> String {
+    const CHARACTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-";
+
+    let mut name = String::new();
+    // The root label
+    let mut length_on_wire = 1;
+    for _ in 0..random_below(6) {
+        let length = 1 + random_below(63);
+        // Stay within the 255 byte limit including length octets
+        if length_on_wire + 1 + length > 255 {
+            break;
+        }
+        length_on_wire += 1 + length;
+
+        let label: Vec<u8> = if random_below(4) == 0 {
+            (0..length).map(|_| random_u8()).collect()
+        } else {
+            (0..length)
+                .map(|_| CHARACTERS[random_below(CHARACTERS.len())])
+                .collect()
+        };
+        push_label(&mut name, &label);
+    }
+    name
+}
+
+/// Sorted types without duplicates, as decoded from a type bitmap
+fn type_bitmap() -> Vec<u16> {
+    let mut types: Vec<u16> = (0..random_below(8)).map(|_| random_u16()).collect();
+    types.sort_unstable();
+    types.dedup();
+    types
+}
+
+fn opcode() -> Opcode {
+    loop {
+        if let Ok(opcode) = Opcode::try_from(random_u8() % 16) {
+            return opcode;
+        }
+    }
+}
+
+/// Extended RCODEs are only generated for messages with EDNS
+fn rcode(extended: bool) -> RCode {
+    let max = if extended { 32 } else { 16 };
+    loop {
+        if let Some(rcode) = RCode::from_u16(random_u16() % max) {
+            return rcode;
+        }
+    }
+}
+
+fn qtype() -> QType {
+    loop {
+        if let Some(qtype) = QType::from_u16(random_u16() % 260) {
+            return qtype;
+        }
+    }
+}
+
+fn qclass() -> QClass {
+    loop {
+        if let Ok(qclass) = QClass::try_from(random_u16() % 256) {
+            return qclass;
+        }
+    }
+}
+
+fn r#type() -> Type {
+    loop {
+        if let Some(r#type) = Type::from_u16(random_u16() % 260) {
+            return r#type;
+        }
+    }
+}
+
+fn class() -> Class {
+    loop {
+        if let Some(class) = Class::from_u16(random_u16() % 8) {
+            return class;
+        }
+    }
+}
+
+pub fn question() -> Question {
+    Question {
+        qname: name(),
+        qtype: qtype(),
+        qclass: qclass(),
+    }
+}
+
+/// Random RDATA matching `r#type`, types without a parser get unknown RDATA
+pub fn rdata(r#type: Type) -> RData {
+    match r#type {
+        Type::A => RData::A(Ipv4Addr::from(random_u32())),
+        Type::AAAA => RData::AAAA(Ipv6Addr::from(
+            ((random_u32() as u128) << 96) | random_u32() as u128,
+        )),
+        Type::NS => RData::NS(name()),
+        Type::CNAME => RData::CNAME(name()),
+        Type::PTR => RData::PTR(name()),
+        Type::SOA => RData::SOA(SOA {
+            mname: name(),
+            rname: name(),
+            serial: random_u32(),
+            refresh: random_u32(),
+            retry: random_u32(),
+            expire: random_u32(),
+            minimum: random_u32(),
+        }),
+        Type::MX => RData::MX(MX {
+            preference: random_u16(),
+            exchange: name(),
+        }),
+        Type::SRV => RData::SRV(SRV {
+            priority: random_u16(),
+            weight: random_u16(),
+            port: random_u16(),
+            target: name(),
+        }),
+        Type::NAPTR => RData::NAPTR(NAPTR {
+            order: random_u16(),
+            preference: random_u16(),
+            flags: random_bytes(2),
+            services: random_bytes(32),
+            regexp: random_bytes(64),
+            replacement: name(),
+        }),
+        Type::SSHFP => RData::SSHFP(SSHFP {
+            algorithm: random_u8(),
+            fingerprint_type: random_u8(),
+            fingerprint: random_bytes(32),
+        }),
+        Type::TLSA => RData::TLSA(TLSA {
+            usage: random_u8(),
+            selector: random_u8(),
+            matching_type: random_u8(),
+            data: random_bytes(64),
+        }),
+        Type::CAA => RData::CAA(CAA {
+            flags: random_u8(),
+            // Tags are at least one byte long
+            tag: format!("t{}", random_ascii(14)),
+            value: random_bytes(64),
+        }),
+        Type::DS => RData::DS(DS {
+            key_tag: random_u16(),
+            algorithm: random_u8(),
+            digest_type: random_u8(),
+            digest: random_bytes(32),
+        }),
+        Type::RRSIG => RData::RRSIG(RRSIG {
+            type_covered: random_u16(),
+            algorithm: random_u8(),
+            labels: random_u8(),
+            original_ttl: random_u32(),
+            expiration: random_u32(),
+            inception: random_u32(),
+            key_tag: random_u16(),
+            signer_name: name(),
+            signature: random_bytes(64),
+        }),
+        Type::NSEC => RData::NSEC(NSEC {
+            next_domain_name: name(),
+            types: type_bitmap(),
+        }),
+        Type::DNSKEY => RData::DNSKEY(DNSKEY {
+            flags: random_u16(),
+            protocol: random_u8(),
+            algorithm: random_u8(),
+            public_key: random_bytes(64),
+        }),
+        Type::NSEC3 => RData::NSEC3(NSEC3 {
+            hash_algorithm: random_u8(),
+            flags: random_u8(),
+            iterations: random_u16(),
+            salt: random_bytes(8),
+            // A SHA-1 hash, the length must not be zero
+            next_hashed_owner: (0..20).map(|_| random_u8()).collect(),
+            types: type_bitmap(),
+        }),
+        Type::NSEC3PARAM => RData::NSEC3PARAM(NSEC3PARAM {
+            hash_algorithm: random_u8(),
+            flags: random_u8(),
+            iterations: random_u16(),
+            salt: random_bytes(8),
+        }),
+        _ => RData::Unknown(random_bytes(64)),
+    }
+}
+
+pub fn record() -> Record {
+    let r#type = r#type();
+    Record {
+        name: name(),
+        r#type,
+        class: class(),
+        ttl: random_u32(),
+        rdata: rdata(r#type),
+    }
+}
+
+pub fn edns() -> Edns {
+    Edns {
+        udp_payload_size: random_u16(),
+        version: random_u8(),
+        dnssec_ok: random_bool(),
+        options: (0..random_below(3))
+            .map(|_| EdnsOption {
+                code: random_u16(),
+                data: random_bytes(16),
+            })
+            .collect(),
+    }
+}
+
+pub fn message() -> Message {
+    let edns = random_bool().then(edns);
+    let mut builder = Message::builder()
+        .id(random_u16())
+        .response(random_bool())
+        .opcode(opcode())
+        .rcode(rcode(edns.is_some()))
+        .authoritive_answer(random_bool())
+        .truncated(random_bool())
+        .recursion_desired(random_bool())
+        .recursion_available(random_bool())
+        .authenticated_data(random_bool())
+        .checking_disabled(random_bool());
+
+    for _ in 0..random_below(3) {
+        builder = builder.question(question());
+    }
+    for _ in 0..random_below(5) {
+        builder = builder.answer(record());
+    }
+    for _ in 0..random_below(3) {
+        builder = builder.authority(record());
+    }
+    for _ in 0..random_below(3) {
+        builder = builder.additional(record());
+    }
+    if let Some(edns) = edns {
+        builder = builder.edns(edns);
+    }
+
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        for _ in 0..ROUNDS {
+            let message = message();
+            let bytes = message.as_bytes().unwrap();
+            let decoded = Message::from_bytes(&bytes).unwrap();
+
+            assert_eq!(decoded, message);
+            // Names compare case-insensitively, their case has to survive the round trip as well
+            assert_eq!(decoded.as_bytes().unwrap(), bytes);
+            for (decoded, question) in decoded.questions().iter().zip(message.questions()) {
+                assert_eq!(decoded.qname, question.qname);
+            }
+        }
+    }
+
+    #[test]
+    fn records_round_trip() {
+        for _ in 0..ROUNDS {
+            let record = record();
+            let bytes = record.as_bytes().unwrap();
+            let mut reader = ByteReader::from_bytes(&bytes);
+
+            assert_eq!(read_record(&mut reader).unwrap(), record);
+            assert_eq!(reader.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn truncated_messages_fail() {
+        for _ in 0..ROUNDS / 10 {
+            let bytes = message().as_bytes().unwrap();
+
+            for length in 0..bytes.len() {
+                assert!(Message::from_bytes(&bytes[..length]).is_err());
+            }
+        }
+    }
+
+    /// Decoders are fed network data, corrupted messages have to return errors instead of panicking
+    #[test]
+    fn corrupted_messages_do_not_panic() {
+        for _ in 0..ROUNDS {
+            let mut bytes = message().as_bytes().unwrap();
+            for _ in 0..1 + random_below(4) {
+                let i = random_below(bytes.len());
+                bytes[i] = random_u8();
+            }
+
+            let _ = Header::from_bytes(&bytes);
+            if let Ok(message) = Message::from_bytes(&bytes) {
+                message.as_bytes().unwrap();
+            }
+
+            let message = MessageRef::from_bytes(&bytes).unwrap();
+            for question in message.questions().flatten() {
+                let _ = question.qname.to_name();
+            }
+            for section in [message.answers(), message.authority(), message.additional()] {
+                for record in section.into_iter().flatten().flatten() {
+                    let _ = record.to_record();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use super::{Edns, Header, Message, Opcode, Question, RCode, Record};
+
+/// Fluent builder for queries and responses, the section counts of the header are set on [`MessageBuilder::build`]
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    header: Header,
+
+    questions: Vec<Question>,
+    answers: Vec<Record>,
+    authority: Vec<Record>,
+    additional: Vec<Record>,
+    edns: Option<Edns>,
+}
+
+impl Default for MessageBuilder {
+    fn default() -> Self {
+        MessageBuilder {
+            header: Header {
+                id: 0,
+                response: false,
+                opcode: Opcode::Query,
+                authoritive_answer: false,
+                truncated: false,
+                recursion_desired: false,
+                recursion_available: false,
+                authenticated_data: false,
+                checking_disabled: false,
+                rcode: RCode::NoError,
+                question_count: 0,
+                answer_count: 0,
+                authority_count: 0,
+                additional_resource_count: 0,
+            },
+            questions: vec![],
+            answers: vec![],
+            authority: vec![],
+            additional: vec![],
+            edns: None,
+        }
+    }
+}
+
+impl MessageBuilder {
+    pub fn new() -> MessageBuilder {
+        MessageBuilder::default()
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.header.id = id;
+        self
+    }
+
+    pub fn response(mut self, response: bool) -> Self {
+        self.header.response = response;
+        self
+    }
+
+    pub fn opcode(mut self, opcode: Opcode) -> Self {
+        self.header.opcode = opcode;
+        self
+    }
+
+    pub fn rcode(mut self, rcode: RCode) -> Self {
+        self.header.rcode = rcode;
+        self
+    }
+
+    pub fn authoritive_answer(mut self, authoritive_answer: bool) -> Self {
+        self.header.authoritive_answer = authoritive_answer;
+        self
+    }
+
+    pub fn truncated(mut self, truncated: bool) -> Self {
+        self.header.truncated = truncated;
+        self
+    }
+
+    pub fn recursion_desired(mut self, recursion_desired: bool) -> Self {
+        self.header.recursion_desired = recursion_desired;
+        self
+    }
+
+    pub fn recursion_available(mut self, recursion_available: bool) -> Self {
+        self.header.recursion_available = recursion_available;
+        self
+    }
+
+    pub fn authenticated_data(mut self, authenticated_data: bool) -> Self {
+        self.header.authenticated_data = authenticated_data;
+        self
+    }
+
+    pub fn checking_disabled(mut self, checking_disabled: bool) -> Self {
+        self.header.checking_disabled = checking_disabled;
+        self
+    }
+
+    pub fn question(mut self, question: Question) -> Self {
+        self.questions.push(question);
+        self
+    }
+
+    pub fn answer(mut self, record: Record) -> Self {
+        self.answers.push(record);
+        self
+    }
+
+    pub fn authority(mut self, record: Record) -> Self {
+        self.authority.push(record);
+        self
+    }
+
+    pub fn additional(mut self, record: Record) -> Self {
+        self.additional.push(record);
+        self
+    }
+
+    pub fn edns(mut self, edns: Edns) -> Self {
+        self.edns = Some(edns);
+        self
+    }
+
+    pub fn build(self) -> Message {
+        let mut message = Message {
+            header: self.header,
+            questions: self.questions,
+            answers: self.answers,
+            authority: self.authority,
+            additional: self.additional,
+            edns: self.edns,
+        };
+        message.update_counts();
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::message::{Class, QClass, QType, RData, Type};
+
+    #[test]
+    fn build_sets_counts() {
+        let record = Record {
+            name: String::from("example.com."),
+            r#type: Type::A,
+            class: Class::IN,
+            ttl: 300,
+            rdata: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+        };
+
+        let message = MessageBuilder::new()
+            .id(1234)
+            .response(true)
+            .recursion_desired(true)
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .answer(record.clone())
+            .answer(record.clone())
+            .authority(record)
+            .edns(Edns::default())
+            .build();
+
+        let header = message.header();
+        assert_eq!(header.id, 1234);
+        assert!(header.response);
+        assert!(header.recursion_desired);
+        assert_eq!(header.question_count, 1);
+        assert_eq!(header.answer_count, 2);
+        assert_eq!(header.authority_count, 1);
+        assert_eq!(header.additional_resource_count, 1);
+
+        assert_eq!(
+            &message.as_bytes().unwrap()[..12],
+            &[4, 210, 0x81, 0, 0, 1, 0, 2, 0, 1, 0, 1]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Presentation of messages in the format used by dig
+
+use core::fmt;
+
+use super::{Message, Question, Record};
+
+/// The root name decodes to an empty string, but is shown as a single dot
+fn presentation_name(name: &str) -> &str {
+    if name.is_empty() {
+        "."
+    } else {
+        name
+    }
+}
+
+fn widest(widths: impl Iterator<Item = usize>) -> usize {
+    widths.max().unwrap_or(0)
+}
+
+/// Widths of the columns shared by every question and record of a message, so all sections line up
+struct Columns {
+    name: usize,
+    ttl: usize,
+    class: usize,
+    r#type: usize,
+}
+
+impl Columns {
+    fn new(message: &Message) -> Columns {
+        let records = || {
+            message
+                .answers()
+                .iter()
+                .chain(message.authority())
+                .chain(message.additional())
+        };
+        let questions = || message.questions().iter();
+
+        Columns {
+            // Questions are commented out with a leading semicolon
+            name: widest(
+                questions()
+                    .map(|question| 1 + presentation_name(&question.qname).len())
+                    .chain(records().map(|record| presentation_name(&record.name).len())),
+            ),
+            ttl: widest(records().map(|record| record.ttl.to_string().len())),
+            class: widest(
+                questions()
+                    .map(|question| question.qclass.to_string().len())
+                    .chain(records().map(|record| record.class.to_string().len())),
+            ),
+            r#type: widest(
+                questions()
+                    .map(|question| question.qtype.to_string().len())
+                    .chain(records().map(|record| record.r#type.to_string().len())),
+            ),
+        }
+    }
+
+    fn write_question(&self, f: &mut fmt::Formatter<'_>, question: &Question) -> fmt::Result {
+        let name = format!(";{}", presentation_name(&question.qname));
+        writeln!(
+            f,
+            "{name:<name_width$} {:<ttl_width$} {:<class_width$} {}",
+            "",
+            question.qclass.to_string(),
+            question.qtype,
+            name_width = self.name,
+            ttl_width = self.ttl,
+            class_width = self.class,
+        )
+    }
+
+    fn write_record(&self, f: &mut fmt::Formatter<'_>, record: &Record) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<name_width$} {:<ttl_width$} {:<class_width$} {:<type_width$} {}",
+            presentation_name(&record.name),
+            record.ttl,
+            record.class.to_string(),
+            record.r#type.to_string(),
+            record.rdata,
+            name_width = self.name,
+            ttl_width = self.ttl,
+            class_width = self.class,
+            type_width = self.r#type,
+        )
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = self.header();
+        writeln!(
+            f,
+            ";; ->>HEADER<<- opcode: {}, status: {}, id: {}",
+            header.opcode, header.rcode, header.id
+        )?;
+
+        let flags = [
+            ("qr", header.response),
+            ("aa", header.authoritive_answer),
+            ("tc", header.truncated),
+            ("rd", header.recursion_desired),
+            ("ra", header.recursion_available),
+            ("ad", header.authenticated_data),
+            ("cd", header.checking_disabled),
+        ];
+        write!(f, ";; flags:")?;
+        for (flag, _) in flags.iter().filter(|(_, set)| *set) {
+            write!(f, " {flag}")?;
+        }
+        writeln!(
+            f,
+            "; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+            header.question_count,
+            header.answer_count,
+            header.authority_count,
+            header.additional_resource_count
+        )?;
+
+        if let Some(edns) = self.edns() {
+            writeln!(f)?;
+            writeln!(f, ";; OPT PSEUDOSECTION:")?;
+            writeln!(f, "{edns}")?;
+        }
+
+        let columns = Columns::new(self);
+
+        if !self.questions().is_empty() {
+            writeln!(f)?;
+            writeln!(f, ";; QUESTION SECTION:")?;
+            for question in self.questions() {
+                columns.write_question(f, question)?;
+            }
+        }
+
+        let sections = [
+            ("ANSWER", self.answers()),
+            ("AUTHORITY", self.authority()),
+            ("ADDITIONAL", self.additional()),
+        ];
+        for (section, records) in sections {
+            if records.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, ";; {section} SECTION:")?;
+            for record in records {
+                columns.write_record(f, record)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::super::*;
+
+    #[test]
+    fn display_works() {
+        let message = Message::builder()
+            .id(7982)
+            .response(true)
+            .recursion_desired(true)
+            .recursion_available(true)
+            .question(Question {
+                qname: String::from("www.example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .answer(Record {
+                name: String::from("www.example.com."),
+                r#type: Type::CNAME,
+                class: Class::IN,
+                ttl: 300,
+                rdata: RData::CNAME(String::from("example.com.")),
+            })
+            .answer(Record {
+                name: String::from("example.com."),
+                r#type: Type::A,
+                class: Class::IN,
+                ttl: 86400,
+                rdata: RData::A(Ipv4Addr::new(93, 184, 215, 14)),
+            })
+            .edns(Edns {
+                dnssec_ok: true,
+                ..Default::default()
+            })
+            .build();
+
+        assert_eq!(
+            message.to_string(),
+            "\
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 7982
+;; flags: qr rd ra; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+
+;; QUESTION SECTION:
+;www.example.com.       IN A
+
+;; ANSWER SECTION:
+www.example.com.  300   IN CNAME example.com.
+example.com.      86400 IN A     93.184.215.14
+"
+        );
+    }
+
+    #[test]
+    fn display_root_and_empty_sections() {
+        let message = Message::builder()
+            .id(1)
+            .question(Question {
+                qname: String::new(),
+                qtype: QType::NS,
+                qclass: QClass::IN,
+            })
+            .build();
+
+        assert_eq!(
+            message.to_string(),
+            "\
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 1
+;; flags:; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 0
+
+;; QUESTION SECTION:
+;.  IN NS
+"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Hex};
+
+use super::{Header, RCode};
+
+/// Type value of the OPT pseudo-record
+pub const OPT_TYPE: u16 = 41;
+
+/// An EDNS option stored in the RDATA of the OPT pseudo-record
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
+
+/// Extension mechanisms for DNS, carried as OPT pseudo-record in the additional section: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edns {
+    /// Largest UDP payload the sender is able to receive
+    pub udp_payload_size: u16,
+    pub version: u8,
+    /// DNSSEC OK: https://datatracker.ietf.org/doc/html/rfc3225#section-3
+    pub dnssec_ok: bool,
+    pub options: Vec<EdnsOption>,
+}
+
+impl Default for Edns {
+    fn default() -> Self {
+        Edns {
+            udp_payload_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+            options: vec![],
+        }
+    }
+}
+
+impl Edns {
+    /// Read the OPT pseudo-record starting after its name and type, the upper bits of the
+    /// extended RCODE it holds are combined with the RCODE of `header`
+    pub fn read(reader: &mut ByteReader, header: &mut Header) -> Result<Edns, DnsError> {
+        let udp_payload_size = reader.next_u16()?;
+        let extended_rcode = reader.next_u8()?;
+        let version = reader.next_u8()?;
+        let dnssec_ok = reader.next_u16()? & 0x8000 > 0;
+
+        header.rcode = RCode::from_u16((extended_rcode as u16) << 4 | header.rcode as u16)
+            .ok_or(DnsError::ParseError("Invalid extended rcode"))?;
+
+        let mut rdlength = reader.next_u16()? as usize;
+        let mut options = vec![];
+        while rdlength > 0 {
+            let code = reader.next_u16()?;
+            let length = reader.next_u16()? as usize;
+            let data = reader.next_bytes(length)?.to_vec();
+
+            rdlength = rdlength
+                .checked_sub(4 + length)
+                .ok_or(DnsError::ParseError("Invalid EDNS option length"))?;
+            options.push(EdnsOption { code, data });
+        }
+
+        Ok(Edns {
+            udp_payload_size,
+            version,
+            dnssec_ok,
+            options,
+        })
+    }
+
+    /// Return the wire format of the OPT pseudo-record in a message with `rcode`
+    pub fn as_bytes(&self, rcode: RCode) -> Result<Vec<u8>, DnsError> {
+        let mut writer = BitWriter::new();
+        self.write(&mut writer, rcode)?;
+        Ok(writer.into_bytes())
+    }
+
+    /// Write the OPT pseudo-record, which holds the upper bits of `rcode`
+    ///
+    /// Fails when the options don't fit in the 16 bit RDLENGTH.
+    pub fn write(&self, writer: &mut BitWriter, rcode: RCode) -> Result<(), DnsError> {
+        // Root name
+        writer.write_u8(0, 8);
+
+        writer.write_u16(OPT_TYPE, 16);
+        writer.write_u16(self.udp_payload_size, 16);
+        writer.write_u8(rcode.extended_bits(), 8);
+        writer.write_u8(self.version, 8);
+        writer.write_bit(self.dnssec_ok);
+        writer.write_u16(0, 15);
+
+        let rdlength: usize = self.options.iter().map(|o| 4 + o.data.len()).sum();
+        let rdlength =
+            u16::try_from(rdlength).map_err(|_| DnsError::ParseError("EDNS options too long"))?;
+        writer.write_u16(rdlength, 16);
+        for option in self.options.iter() {
+            // Shorter than the RDLENGTH, so it fits as well
+            writer.write_u16(option.code, 16);
+            writer.write_u16(option.data.len() as u16, 16);
+            writer.write_bytes(&option.data);
+        }
+        Ok(())
+    }
+}
+
+/// The OPT pseudosection as shown by dig, with options of unknown meaning in hexadecimal
+impl fmt::Display for Edns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "; EDNS: version: {}, flags:{}; udp: {}",
+            self.version,
+            if self.dnssec_ok { " do" } else { "" },
+            self.udp_payload_size
+        )?;
+        for option in self.options.iter() {
+            write!(f, "\n; OPT={}: {}", option.code, Hex(&option.data))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_bytes_works() {
+        let edns = Edns {
+            udp_payload_size: 4096,
+            dnssec_ok: true,
+            options: vec![EdnsOption {
+                code: 10,
+                data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            edns.as_bytes(RCode::BadVers).unwrap(),
+            vec![0, 0, 41, 16, 0, 1, 0, 0x80, 0, 0, 12, 0, 10, 0, 8, 1, 2, 3, 4, 5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn as_bytes_fails_on_long_options() {
+        let option = |length: usize| EdnsOption {
+            code: 12,
+            data: vec![0; length],
+        };
+        let edns = |options: Vec<EdnsOption>| Edns {
+            options,
+            ..Default::default()
+        };
+
+        assert!(edns(vec![option(0xffff - 4)])
+            .as_bytes(RCode::NoError)
+            .is_ok());
+        assert!(edns(vec![option(0xffff - 3)])
+            .as_bytes(RCode::NoError)
+            .is_err());
+        assert!(edns(vec![option(0x10000)])
+            .as_bytes(RCode::NoError)
+            .is_err());
+        assert!(edns(vec![option(0x8000), option(0x8000)])
+            .as_bytes(RCode::NoError)
+            .is_err());
+    }
+
+    #[test]
+    fn read_combines_extended_rcode() {
+        let mut header = Header::from_bytes(&[0; 12]).unwrap();
+        let bytes = [16, 0, 1, 0, 0, 0, 0, 0];
+        Edns::read(&mut ByteReader::from_bytes(&bytes), &mut header).unwrap();
+        assert_eq!(header.rcode, RCode::BadVers);
+
+        // Unassigned extended RCODE
+        let bytes = [16, 0, 0xff, 0, 0, 0, 0, 0];
+        assert!(Edns::read(&mut ByteReader::from_bytes(&bytes), &mut header).is_err());
+    }
+
+    #[test]
+    fn option_lengths_are_checked() {
+        // The option is longer than the RDATA
+        let mut bytes = vec![16, 0, 0, 0, 0, 0, 0, 4, 0, 1, 0xff, 0xfc];
+        bytes.resize(bytes.len() + 0xfffc, 0);
+        let mut header = Header::from_bytes(&[0; 12]).unwrap();
+        assert!(Edns::read(&mut ByteReader::from_bytes(&bytes), &mut header).is_err());
+    }
+
+    #[test]
+    fn display_works() {
+        let edns = Edns {
+            options: vec![EdnsOption {
+                code: 65001,
+                data: vec![0xab, 0xcd],
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            edns.to_string(),
+            "; EDNS: version: 0, flags:; udp: 1232\n; OPT=65001: ABCD"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use bitreader::BitReader;
+use bitreader::{BitReader, BitWriter, ByteReader};
 
 use crate::error::DnsError;
 
@@ -19,7 +19,12 @@ use super::{opcode::Opcode, rcode::RCode};
 // +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 // |                    ARCOUNT                    |
 // +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "super::json::HeaderJson", try_from = "super::json::HeaderJson")
+)]
 pub struct Header {
     pub id: u16,
     /// If false it is a query otherwise a response
@@ -43,67 +48,45 @@ pub struct Header {
 
 impl Header {
     pub fn as_bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![];
-
-        bytes.extend(self.id.to_be_bytes());
-
-        let mut byte: u8 = 0;
-
-        if self.response {
-            byte = 1;
-        }
-        byte <<= 1;
-
-        byte += self.opcode as u8;
-        byte <<= 4;
-
-        if self.authoritive_answer {
-            byte += 1;
-        }
-        byte <<= 1;
-
-        if self.truncated {
-            byte += 1;
-        }
-        byte <<= 1;
-
-        if self.recursion_desired {
-            byte += 1;
-        }
-
-        bytes.push(byte);
-        byte = 0;
-
-        if self.recursion_available {
-            byte = 1;
-        }
-        byte <<= 2; // Recursion Available + Reserved space
+        let mut writer = BitWriter::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
 
-        if self.authenticated_data {
-            byte += 1;
-        }
-        byte <<= 1;
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u16(self.id, 16);
 
-        if self.checking_disabled {
-            byte += 1;
-        }
-        byte <<= 1;
+        writer.write_bit(self.response);
+        writer.write_u8(self.opcode as u8, 4);
+        writer.write_bit(self.authoritive_answer);
+        writer.write_bit(self.truncated);
+        writer.write_bit(self.recursion_desired);
+        writer.write_bit(self.recursion_available);
 
-        byte += self.rcode as u8;
-        bytes.push(byte);
+        // Reserved Z bit
+        writer.write_bit(false);
 
-        bytes.extend(self.question_count.to_be_bytes());
-        bytes.extend(self.answer_count.to_be_bytes());
-        bytes.extend(self.authority_count.to_be_bytes());
-        bytes.extend(self.additional_resource_count.to_be_bytes());
+        writer.write_bit(self.authenticated_data);
+        writer.write_bit(self.checking_disabled);
+        writer.write_u8(self.rcode.header_bits(), 4);
 
-        return bytes;
+        writer.write_u16(self.question_count, 16);
+        writer.write_u16(self.answer_count, 16);
+        writer.write_u16(self.authority_count, 16);
+        writer.write_u16(self.additional_resource_count, 16);
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Header, DnsError> {
-        let mut bit_reader = BitReader::from_bytes(bytes);
+        Header::read(&mut ByteReader::from_bytes(bytes))
+    }
+
+    pub fn read(reader: &mut ByteReader) -> Result<Header, DnsError> {
+        let id = reader.next_u16()?;
+
+        // Flags are the only fields not aligned to bytes
+        let flags = reader.next_bytes(2)?;
+        let mut bit_reader = BitReader::from_bytes(flags);
 
-        let id = bit_reader.next_u16(16)?;
         let response = bit_reader.next_bit()?;
 
         let opcode = Opcode::try_from(bit_reader.next_u8(4)?)?;
@@ -118,15 +101,16 @@ impl Header {
         let authenticated_data = bit_reader.next_bit()?;
         let checking_disabled = bit_reader.next_bit()?;
 
-        let rcode =
-            RCode::from_u8(bit_reader.next_u8(4)?).ok_or(DnsError::ParseError("Invalid rcode"))?;
+        // Combined with the upper bits once the OPT pseudo-record has been read
+        let rcode = RCode::from_u16(bit_reader.next_u8(4)? as u16)
+            .ok_or(DnsError::ParseError("Invalid rcode"))?;
 
-        let question_count = bit_reader.next_u16(16)?;
-        let resource_count = bit_reader.next_u16(16)?;
-        let authority_count = bit_reader.next_u16(16)?;
-        let additional_resource_count = bit_reader.next_u16(16)?;
+        let question_count = reader.next_u16()?;
+        let resource_count = reader.next_u16()?;
+        let authority_count = reader.next_u16()?;
+        let additional_resource_count = reader.next_u16()?;
 
-        return Ok(Header {
+        Ok(Header {
             id,
             response,
             opcode,
@@ -141,7 +125,7 @@ impl Header {
             answer_count: resource_count,
             authority_count,
             additional_resource_count,
-        });
+        })
     }
 }
 
@@ -170,10 +154,32 @@ mod tests {
 
         let bytes = header.as_bytes();
 
-        for byte in bytes.iter() {
-            println!("{byte:#b}");
-        }
+        assert_eq!(bytes, vec![0, 0, 0b10000000, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        let header = Header {
+            id: 0xbeef,
+            response: true,
+            opcode: Opcode::Status,
+            authenticated_data: true,
+            checking_disabled: false,
+            rcode: RCode::Refused,
+            authoritive_answer: false,
+            truncated: true,
+            recursion_desired: false,
+            recursion_available: true,
+            question_count: 1,
+            answer_count: 2,
+            authority_count: 3,
+            additional_resource_count: 4,
+        };
+
+        let bytes = header.as_bytes();
+        assert_eq!(&bytes[2..4], &[0b1001_0010, 0b1010_0101]);
 
-        assert!(bytes, vec![0b0, 0b0, 0b100000000])
+        let decoded = Header::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, header);
     }
 }
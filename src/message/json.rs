@@ -0,0 +1,551 @@
+//! JSON representation of messages following https://datatracker.ietf.org/doc/html/rfc8427
+//!
+//! The types in this module mirror the member names of the RFC, the message types are converted
+//! into and from them when (de)serializing.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use bitreader::{BitWriter, ByteReader};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::DnsError,
+    util::{from_hex, Hex},
+};
+
+use super::{
+    Class, Edns, Header, Message, Opcode, QClass, QType, Question, RCode, RData, Record, Type,
+    OPT_TYPE,
+};
+
+fn hex(value: &str) -> Result<Vec<u8>, DnsError> {
+    from_hex(value).ok_or(DnsError::ParseError("Invalid RDATAHEX"))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub(super) struct HeaderJson {
+    id: u16,
+    qr: bool,
+    #[serde(rename = "Opcode")]
+    opcode: u8,
+    aa: bool,
+    tc: bool,
+    rd: bool,
+    ra: bool,
+    ad: bool,
+    cd: bool,
+    rcode: u8,
+    qdcount: u16,
+    ancount: u16,
+    nscount: u16,
+    arcount: u16,
+}
+
+impl From<Header> for HeaderJson {
+    fn from(header: Header) -> Self {
+        HeaderJson {
+            id: header.id,
+            qr: header.response,
+            opcode: header.opcode as u8,
+            aa: header.authoritive_answer,
+            tc: header.truncated,
+            rd: header.recursion_desired,
+            ra: header.recursion_available,
+            ad: header.authenticated_data,
+            cd: header.checking_disabled,
+            rcode: header.rcode.header_bits(),
+            qdcount: header.question_count,
+            ancount: header.answer_count,
+            nscount: header.authority_count,
+            arcount: header.additional_resource_count,
+        }
+    }
+}
+
+impl TryFrom<HeaderJson> for Header {
+    type Error = DnsError;
+
+    fn try_from(header: HeaderJson) -> Result<Self, Self::Error> {
+        Ok(Header {
+            id: header.id,
+            response: header.qr,
+            opcode: Opcode::try_from(header.opcode)?,
+            authoritive_answer: header.aa,
+            truncated: header.tc,
+            recursion_desired: header.rd,
+            recursion_available: header.ra,
+            authenticated_data: header.ad,
+            checking_disabled: header.cd,
+            rcode: RCode::from_u16(header.rcode as u16)
+                .ok_or(DnsError::ParseError("Invalid rcode"))?,
+            question_count: header.qdcount,
+            answer_count: header.ancount,
+            authority_count: header.nscount,
+            additional_resource_count: header.arcount,
+        })
+    }
+}
+
+/// Questions use their own member names: https://datatracker.ietf.org/doc/html/rfc8427#section-2.2
+#[derive(Serialize, Deserialize)]
+pub(super) struct QuestionJson {
+    #[serde(rename = "QNAME")]
+    name: String,
+    #[serde(rename = "QTYPE")]
+    r#type: u16,
+    #[serde(rename = "QTYPEname", default)]
+    type_name: String,
+    #[serde(rename = "QCLASS")]
+    class: u16,
+    #[serde(rename = "QCLASSname", default)]
+    class_name: String,
+}
+
+impl From<Question> for QuestionJson {
+    fn from(question: Question) -> Self {
+        QuestionJson {
+            name: question.qname,
+            r#type: question.qtype as u16,
+            type_name: question.qtype.to_string(),
+            class: question.qclass as u16,
+            class_name: question.qclass.to_string(),
+        }
+    }
+}
+
+impl TryFrom<QuestionJson> for Question {
+    type Error = DnsError;
+
+    fn try_from(question: QuestionJson) -> Result<Self, Self::Error> {
+        Ok(Question {
+            qname: question.name,
+            qtype: QType::from_u16(question.r#type).ok_or(DnsError::ParseError("Invalid qtype"))?,
+            qclass: QClass::try_from(question.class)?,
+        })
+    }
+}
+
+/// RDATA members of a resource record, the type specific member holds the presentation format
+///
+/// `RDATAHEX` is always written so RDATA of every type can be read back.
+#[derive(Default, Serialize, Deserialize)]
+pub(super) struct RDataJson {
+    #[serde(rename = "rdataA", skip_serializing_if = "Option::is_none", default)]
+    a: Option<Ipv4Addr>,
+    #[serde(rename = "rdataAAAA", skip_serializing_if = "Option::is_none", default)]
+    aaaa: Option<Ipv6Addr>,
+    #[serde(rename = "rdataNS", skip_serializing_if = "Option::is_none", default)]
+    ns: Option<String>,
+    #[serde(
+        rename = "rdataCNAME",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    cname: Option<String>,
+    #[serde(rename = "rdataPTR", skip_serializing_if = "Option::is_none", default)]
+    ptr: Option<String>,
+    #[serde(rename = "rdataSOA", skip_serializing_if = "Option::is_none", default)]
+    soa: Option<String>,
+    #[serde(rename = "rdataMX", skip_serializing_if = "Option::is_none", default)]
+    mx: Option<String>,
+    #[serde(rename = "rdataSRV", skip_serializing_if = "Option::is_none", default)]
+    srv: Option<String>,
+    #[serde(
+        rename = "rdataNAPTR",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    naptr: Option<String>,
+    #[serde(
+        rename = "rdataSSHFP",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    sshfp: Option<String>,
+    #[serde(rename = "rdataTLSA", skip_serializing_if = "Option::is_none", default)]
+    tlsa: Option<String>,
+    #[serde(rename = "rdataCAA", skip_serializing_if = "Option::is_none", default)]
+    caa: Option<String>,
+    #[serde(rename = "rdataDS", skip_serializing_if = "Option::is_none", default)]
+    ds: Option<String>,
+    #[serde(
+        rename = "rdataRRSIG",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    rrsig: Option<String>,
+    #[serde(rename = "rdataNSEC", skip_serializing_if = "Option::is_none", default)]
+    nsec: Option<String>,
+    #[serde(
+        rename = "rdataDNSKEY",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    dnskey: Option<String>,
+    #[serde(
+        rename = "rdataNSEC3",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    nsec3: Option<String>,
+    #[serde(
+        rename = "rdataNSEC3PARAM",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    nsec3param: Option<String>,
+    #[serde(rename = "RDATAHEX", skip_serializing_if = "Option::is_none", default)]
+    hex: Option<String>,
+}
+
+impl RDataJson {
+    /// Type of the RDATA, known from the type specific member
+    fn r#type(&self) -> Option<Type> {
+        [
+            (self.a.is_some(), Type::A),
+            (self.aaaa.is_some(), Type::AAAA),
+            (self.ns.is_some(), Type::NS),
+            (self.cname.is_some(), Type::CNAME),
+            (self.ptr.is_some(), Type::PTR),
+            (self.soa.is_some(), Type::SOA),
+            (self.mx.is_some(), Type::MX),
+            (self.srv.is_some(), Type::SRV),
+            (self.naptr.is_some(), Type::NAPTR),
+            (self.sshfp.is_some(), Type::SSHFP),
+            (self.tlsa.is_some(), Type::TLSA),
+            (self.caa.is_some(), Type::CAA),
+            (self.ds.is_some(), Type::DS),
+            (self.rrsig.is_some(), Type::RRSIG),
+            (self.nsec.is_some(), Type::NSEC),
+            (self.dnskey.is_some(), Type::DNSKEY),
+            (self.nsec3.is_some(), Type::NSEC3),
+            (self.nsec3param.is_some(), Type::NSEC3PARAM),
+        ]
+        .into_iter()
+        .find_map(|(present, r#type)| present.then_some(r#type))
+    }
+
+    /// Convert into RDATA of `r#type`, which is taken from the members when not given
+    fn into_rdata(self, r#type: Option<Type>) -> Result<RData, DnsError> {
+        let r#type = r#type.or(self.r#type());
+
+        if let Some(value) = &self.hex {
+            let bytes = hex(value)?;
+            let rdlength = u16::try_from(bytes.len())
+                .map_err(|_| DnsError::ParseError("RDATAHEX too long"))?;
+
+            return match r#type {
+                Some(r#type) => RData::read(r#type, &mut ByteReader::from_bytes(&bytes), rdlength),
+                None => Ok(RData::Unknown(bytes)),
+            };
+        }
+
+        match self {
+            RDataJson { a: Some(addr), .. } => Ok(RData::A(addr)),
+            RDataJson {
+                aaaa: Some(addr), ..
+            } => Ok(RData::AAAA(addr)),
+            RDataJson { ns: Some(name), .. } => Ok(RData::NS(name)),
+            RDataJson {
+                cname: Some(name), ..
+            } => Ok(RData::CNAME(name)),
+            RDataJson {
+                ptr: Some(name), ..
+            } => Ok(RData::PTR(name)),
+            _ => Err(DnsError::ParseError("Missing RDATAHEX")),
+        }
+    }
+}
+
+impl From<RData> for RDataJson {
+    fn from(rdata: RData) -> Self {
+        let mut json = RDataJson {
+            hex: rdata.as_bytes().ok().map(|bytes| Hex(&bytes).to_string()),
+            ..Default::default()
+        };
+
+        let presentation = rdata.to_string();
+        match rdata {
+            RData::A(addr) => json.a = Some(addr),
+            RData::AAAA(addr) => json.aaaa = Some(addr),
+            RData::NS(name) => json.ns = Some(name),
+            RData::CNAME(name) => json.cname = Some(name),
+            RData::PTR(name) => json.ptr = Some(name),
+            RData::SOA(_) => json.soa = Some(presentation),
+            RData::MX(_) => json.mx = Some(presentation),
+            RData::SRV(_) => json.srv = Some(presentation),
+            RData::NAPTR(_) => json.naptr = Some(presentation),
+            RData::SSHFP(_) => json.sshfp = Some(presentation),
+            RData::TLSA(_) => json.tlsa = Some(presentation),
+            RData::CAA(_) => json.caa = Some(presentation),
+            RData::DS(_) => json.ds = Some(presentation),
+            RData::RRSIG(_) => json.rrsig = Some(presentation),
+            RData::NSEC(_) => json.nsec = Some(presentation),
+            RData::DNSKEY(_) => json.dnskey = Some(presentation),
+            RData::NSEC3(_) => json.nsec3 = Some(presentation),
+            RData::NSEC3PARAM(_) => json.nsec3param = Some(presentation),
+            RData::Unknown(_) => {}
+        }
+
+        json
+    }
+}
+
+impl TryFrom<RDataJson> for RData {
+    type Error = DnsError;
+
+    fn try_from(rdata: RDataJson) -> Result<Self, Self::Error> {
+        rdata.into_rdata(None)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct RecordJson {
+    #[serde(rename = "NAME")]
+    name: String,
+    #[serde(rename = "TYPE")]
+    r#type: u16,
+    #[serde(rename = "TYPEname", default)]
+    type_name: String,
+    #[serde(rename = "CLASS")]
+    class: u16,
+    #[serde(rename = "CLASSname", default)]
+    class_name: String,
+    #[serde(rename = "TTL")]
+    ttl: u32,
+    #[serde(rename = "RDLENGTH", default)]
+    rdlength: u16,
+    #[serde(flatten)]
+    rdata: RDataJson,
+}
+
+impl From<Record> for RecordJson {
+    fn from(record: Record) -> Self {
+        RecordJson {
+            name: record.name,
+            r#type: record.r#type as u16,
+            type_name: record.r#type.to_string(),
+            class: record.class as u16,
+            class_name: record.class.to_string(),
+            ttl: record.ttl,
+            rdlength: record
+                .rdata
+                .as_bytes()
+                .map_or(0, |bytes| bytes.len() as u16),
+            rdata: RDataJson::from(record.rdata),
+        }
+    }
+}
+
+impl TryFrom<RecordJson> for Record {
+    type Error = DnsError;
+
+    fn try_from(record: RecordJson) -> Result<Self, Self::Error> {
+        let r#type = Type::from_u16(record.r#type).ok_or(DnsError::ParseError("Invalid type"))?;
+
+        Ok(Record {
+            name: record.name,
+            r#type,
+            class: Class::from_u16(record.class).ok_or(DnsError::ParseError("Invalid class"))?,
+            ttl: record.ttl,
+            rdata: record.rdata.into_rdata(Some(r#type))?,
+        })
+    }
+}
+
+/// EDNS is written as the OPT pseudo-record it is on the wire, holding the upper bits of `rcode`: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2
+fn opt_record(edns: Edns, rcode: RCode) -> RecordJson {
+    // Skip the root name, type, class, TTL and RDLENGTH, options too long for the wire are left out
+    let options = edns
+        .as_bytes(rcode)
+        .map_or(vec![], |mut bytes| bytes.split_off(11));
+
+    RecordJson {
+        name: String::from("."),
+        r#type: OPT_TYPE,
+        type_name: String::from("OPT"),
+        class: edns.udp_payload_size,
+        // The class holds the payload size, written in the generic form: https://datatracker.ietf.org/doc/html/rfc3597#section-5
+        class_name: format!("CLASS{}", edns.udp_payload_size),
+        ttl: (rcode.extended_bits() as u32) << 24
+            | (edns.version as u32) << 16
+            | (edns.dnssec_ok as u32) << 15,
+        rdlength: options.len() as u16,
+        rdata: RDataJson {
+            hex: Some(Hex(&options).to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+/// Read EDNS from its OPT pseudo-record, combining the RCODE of `header` with the upper bits it holds
+fn read_opt_record(record: RecordJson, header: &mut Header) -> Result<Edns, DnsError> {
+    let options = hex(record.rdata.hex.as_deref().unwrap_or_default())?;
+
+    // Reuse the wire format parser, which starts after the name and type
+    let mut writer = BitWriter::new();
+    writer.write_u16(record.class, 16);
+    writer.write_u32(record.ttl, 32);
+    writer.write_u16(options.len() as u16, 16);
+    writer.write_bytes(&options);
+
+    Edns::read(&mut ByteReader::from_bytes(writer.as_bytes()), header)
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct MessageJson {
+    #[serde(flatten)]
+    header: HeaderJson,
+    #[serde(rename = "questionRRs", default)]
+    questions: Vec<QuestionJson>,
+    #[serde(rename = "answerRRs", default)]
+    answers: Vec<RecordJson>,
+    #[serde(rename = "authorityRRs", default)]
+    authority: Vec<RecordJson>,
+    #[serde(rename = "additionalRRs", default)]
+    additional: Vec<RecordJson>,
+}
+
+impl From<Message> for MessageJson {
+    fn from(message: Message) -> Self {
+        let records = |records: Vec<Record>| records.into_iter().map(RecordJson::from).collect();
+
+        let mut additional: Vec<RecordJson> = records(message.additional);
+        additional.extend(
+            message
+                .edns
+                .map(|edns| opt_record(edns, message.header.rcode)),
+        );
+
+        MessageJson {
+            header: HeaderJson::from(message.header),
+            questions: message
+                .questions
+                .into_iter()
+                .map(QuestionJson::from)
+                .collect(),
+            answers: records(message.answers),
+            authority: records(message.authority),
+            additional,
+        }
+    }
+}
+
+impl TryFrom<MessageJson> for Message {
+    type Error = DnsError;
+
+    fn try_from(json: MessageJson) -> Result<Self, Self::Error> {
+        let records = |records: Vec<RecordJson>| {
+            records
+                .into_iter()
+                .map(Record::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut header = Header::try_from(json.header)?;
+        let mut edns = None;
+        let mut additional = vec![];
+        for record in json.additional {
+            if record.r#type == OPT_TYPE {
+                edns = Some(read_opt_record(record, &mut header)?);
+            } else {
+                additional.push(Record::try_from(record)?);
+            }
+        }
+
+        let mut message = Message {
+            header,
+            questions: json
+                .questions
+                .into_iter()
+                .map(Question::try_from)
+                .collect::<Result<_, _>>()?,
+            answers: records(json.answers)?,
+            authority: records(json.authority)?,
+            additional,
+            edns,
+        };
+        message.update_counts();
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::arbitrary;
+    use super::*;
+
+    #[test]
+    fn serialize_works() {
+        let message = Message::builder()
+            .id(0x1f2e)
+            .response(true)
+            .recursion_desired(true)
+            .question(Question {
+                qname: String::from("example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .answer(Record {
+                name: String::from("example.com."),
+                r#type: Type::A,
+                class: Class::IN,
+                ttl: 3600,
+                rdata: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            })
+            .edns(Edns {
+                dnssec_ok: true,
+                ..Default::default()
+            })
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "ID": 0x1f2e, "QR": true, "Opcode": 0, "AA": false, "TC": false, "RD": true,
+                "RA": false, "AD": false, "CD": false, "RCODE": 0,
+                "QDCOUNT": 1, "ANCOUNT": 1, "NSCOUNT": 0, "ARCOUNT": 1,
+                "questionRRs": [
+                    { "QNAME": "example.com.", "QTYPE": 1, "QTYPEname": "A", "QCLASS": 1, "QCLASSname": "IN" }
+                ],
+                "answerRRs": [{
+                    "NAME": "example.com.", "TYPE": 1, "TYPEname": "A", "CLASS": 1, "CLASSname": "IN",
+                    "TTL": 3600, "RDLENGTH": 4, "rdataA": "192.0.2.1", "RDATAHEX": "C0000201"
+                }],
+                "authorityRRs": [],
+                "additionalRRs": [{
+                    "NAME": ".", "TYPE": 41, "TYPEname": "OPT", "CLASS": 1232, "CLASSname": "CLASS1232",
+                    "TTL": 0x8000, "RDLENGTH": 0, "RDATAHEX": ""
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_without_rdatahex_works() {
+        let record: Record = serde_json::from_value(json!({
+            "NAME": "www.example.com.", "TYPE": 5, "CLASS": 1, "TTL": 60,
+            "rdataCNAME": "example.com."
+        }))
+        .unwrap();
+
+        assert_eq!(record.rdata, RData::CNAME(String::from("example.com.")));
+
+        let rdata: RData = serde_json::from_value(json!({ "RDATAHEX": "c0000201" })).unwrap();
+        assert_eq!(rdata, RData::Unknown(vec![192, 0, 2, 1]));
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        for _ in 0..100 {
+            let message = arbitrary::message();
+            let json = serde_json::to_string(&message).unwrap();
+
+            assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+        }
+    }
+}
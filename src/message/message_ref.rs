@@ -0,0 +1,522 @@
+use core::fmt;
+
+use bitreader::ByteReader;
+
+use crate::error::DnsError;
+
+use super::{label_eq, push_label, Class, Header, QClass, QType, RData, Record, Type};
+
+/// Move the reader past the name at its position without following pointers
+fn skip_name(reader: &mut ByteReader) -> Result<(), DnsError> {
+    loop {
+        let length = reader.next_u8()?;
+
+        match length {
+            0 => return Ok(()),
+            // Compression pointer: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4
+            0xc0..=0xff => {
+                reader.skip(1);
+                return Ok(());
+            }
+            1..=63 => reader.skip(length as usize),
+            _ => return Err(DnsError::ParseError("Invalid label type")),
+        }
+    }
+}
+
+/// A borrowed view of a DNS message which parses sections and names only when they are accessed
+///
+/// Unlike [`super::Message::from_bytes`] no allocations are made, which makes it suited for
+/// inspecting large amounts of packets where only a few fields are of interest.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<MessageRef<'a>, DnsError> {
+        if bytes.len() < 12 {
+            return Err(DnsError::ParseError("Not enough bytes for header"));
+        }
+
+        Ok(MessageRef { bytes })
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Parse the full header
+    pub fn header(&self) -> Result<Header, DnsError> {
+        Header::from_bytes(&self.bytes[..12])
+    }
+
+    pub fn id(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[0], self.bytes[1]])
+    }
+
+    pub fn question_count(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[4], self.bytes[5]])
+    }
+
+    pub fn answer_count(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[6], self.bytes[7]])
+    }
+
+    pub fn authority_count(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[8], self.bytes[9]])
+    }
+
+    pub fn additional_resource_count(&self) -> u16 {
+        u16::from_be_bytes([self.bytes[10], self.bytes[11]])
+    }
+
+    /// Reader positioned at `offset`
+    fn reader(&self, offset: usize) -> ByteReader<'a> {
+        let mut reader = ByteReader::from_bytes(self.bytes);
+        reader.set_pointer(offset);
+        reader
+    }
+
+    pub fn questions(&self) -> QuestionIter<'a> {
+        QuestionIter {
+            bytes: self.bytes,
+            reader: self.reader(12),
+            remaining: self.question_count(),
+        }
+    }
+
+    /// Iterate over the answer section, skipping over the questions first
+    pub fn answers(&self) -> Result<RecordIter<'a>, DnsError> {
+        let mut reader = self.reader(12);
+        for _ in 0..self.question_count() {
+            skip_name(&mut reader)?;
+            reader.skip(4);
+        }
+
+        Ok(RecordIter {
+            bytes: self.bytes,
+            reader,
+            remaining: self.answer_count(),
+        })
+    }
+
+    /// Iterate over the authority section, skipping over the questions and answers first
+    pub fn authority(&self) -> Result<RecordIter<'a>, DnsError> {
+        let mut answers = self.answers()?;
+        let offset = answers.skip_all()?;
+
+        Ok(RecordIter {
+            bytes: self.bytes,
+            reader: self.reader(offset),
+            remaining: self.authority_count(),
+        })
+    }
+
+    /// Iterate over the additional section, including the OPT pseudo-record
+    pub fn additional(&self) -> Result<RecordIter<'a>, DnsError> {
+        let mut authority = self.authority()?;
+        let offset = authority.skip_all()?;
+
+        Ok(RecordIter {
+            bytes: self.bytes,
+            reader: self.reader(offset),
+            remaining: self.additional_resource_count(),
+        })
+    }
+}
+
+/// A domain name within a message, its labels are only read when needed
+#[derive(Debug, Clone, Copy)]
+pub struct NameRef<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// Iterate over the labels of the name, following compression pointers
+    ///
+    /// Iteration stops early on malformed names, use [`NameRef::to_name`] to detect these
+    pub fn labels(&self) -> Labels<'a> {
+        Labels {
+            bytes: self.bytes,
+            offset: Some(self.offset),
+            limit: self.offset,
+            length_on_wire: 1,
+        }
+    }
+
+    /// Compare with a name in presentation format, ignoring case and the trailing dot
+    pub fn eq_name(&self, name: &str) -> bool {
+        let mut expected = name
+            .trim_end_matches('.')
+            .split('.')
+            .filter(|l| !l.is_empty());
+        let mut labels = self.labels();
+
+        loop {
+            match (labels.next(), expected.next()) {
+                (Some(label), Some(expected)) if label_eq(label, expected) => {}
+                (None, None) => return labels.offset.is_none(),
+                _ => return false,
+            }
+        }
+    }
+
+    /// Decode the name into the same format as [`super::Message::from_bytes`]
+    pub fn to_name(&self) -> Result<String, DnsError> {
+        let mut labels = self.labels();
+        let mut name = String::new();
+
+        for label in labels.by_ref() {
+            push_label(&mut name, label);
+        }
+
+        match labels.offset {
+            None => Ok(name),
+            Some(_) => Err(DnsError::ParseError("Invalid name")),
+        }
+    }
+}
+
+impl<'a> fmt::Display for NameRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut name = String::new();
+        for label in self.labels() {
+            push_label(&mut name, label);
+        }
+
+        if name.is_empty() {
+            name.push('.');
+        }
+
+        write!(f, "{name}")
+    }
+}
+
+/// Iterator over the labels of a [`NameRef`]
+#[derive(Debug, Clone)]
+pub struct Labels<'a> {
+    bytes: &'a [u8],
+    /// Offset of the next label, `None` when the end of the name has been reached
+    offset: Option<usize>,
+    /// Pointers have to point before the previous one, which rules out loops
+    limit: usize,
+    length_on_wire: usize,
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = self.offset?;
+            let length = *self.bytes.get(offset)? as usize;
+
+            match length {
+                0 => {
+                    self.offset = None;
+                    return None;
+                }
+                // Compression pointer: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4
+                0xc0..=0xff => {
+                    let pointer = ((length & 0x3f) << 8) | *self.bytes.get(offset + 1)? as usize;
+                    if pointer >= self.limit {
+                        return None;
+                    }
+                    self.limit = pointer;
+                    self.offset = Some(pointer);
+                }
+                1..=63 => {
+                    // Max of 255 bytes per name: https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4
+                    self.length_on_wire += 1 + length;
+                    if self.length_on_wire > 255 {
+                        return None;
+                    }
+                    let label = self.bytes.get(offset + 1..offset + 1 + length)?;
+                    self.offset = Some(offset + 1 + length);
+                    return Some(label);
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuestionRef<'a> {
+    pub qname: NameRef<'a>,
+    qtype: u16,
+    qclass: u16,
+}
+
+impl<'a> QuestionRef<'a> {
+    pub fn qtype(&self) -> Option<QType> {
+        QType::from_u16(self.qtype)
+    }
+
+    pub fn qclass(&self) -> Option<QClass> {
+        QClass::try_from(self.qclass).ok()
+    }
+}
+
+pub struct QuestionIter<'a> {
+    bytes: &'a [u8],
+    reader: ByteReader<'a>,
+    remaining: u16,
+}
+
+impl<'a> Iterator for QuestionIter<'a> {
+    type Item = Result<QuestionRef<'a>, DnsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let question = (|| {
+            let qname = NameRef {
+                bytes: self.bytes,
+                offset: self.reader.get_pointer(),
+            };
+            skip_name(&mut self.reader)?;
+            let qtype = self.reader.next_u16()?;
+            let qclass = self.reader.next_u16()?;
+
+            Ok(QuestionRef {
+                qname,
+                qtype,
+                qclass,
+            })
+        })();
+
+        // Nothing after malformed data can be trusted
+        if question.is_err() {
+            self.remaining = 0;
+        }
+        Some(question)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RecordRef<'a> {
+    pub name: NameRef<'a>,
+    r#type: u16,
+    class: u16,
+    pub ttl: u32,
+    bytes: &'a [u8],
+    rdata_offset: usize,
+    rdlength: u16,
+}
+
+impl<'a> RecordRef<'a> {
+    pub fn r#type(&self) -> Option<Type> {
+        Type::from_u16(self.r#type)
+    }
+
+    /// Raw value of the type, also available for types that are not implemented
+    pub fn type_value(&self) -> u16 {
+        self.r#type
+    }
+
+    pub fn class(&self) -> Option<Class> {
+        Class::from_u16(self.class)
+    }
+
+    /// Raw value of the class, which is the UDP payload size for OPT pseudo-records
+    pub fn class_value(&self) -> u16 {
+        self.class
+    }
+
+    /// The undecoded RDATA, names within it may be compressed
+    pub fn rdata(&self) -> &'a [u8] {
+        &self.bytes[self.rdata_offset..self.rdata_offset + self.rdlength as usize]
+    }
+
+    /// Decode into an owned record
+    pub fn to_record(&self) -> Result<Record, DnsError> {
+        let r#type = self.r#type().ok_or(DnsError::ParseError("Invalid type"))?;
+        let class = self.class().ok_or(DnsError::ParseError("Invalid class"))?;
+
+        let mut reader = ByteReader::from_bytes(self.bytes);
+        reader.set_pointer(self.rdata_offset);
+
+        Ok(Record {
+            name: self.name.to_name()?,
+            r#type,
+            class,
+            ttl: self.ttl,
+            rdata: RData::read(r#type, &mut reader, self.rdlength)?,
+        })
+    }
+}
+
+pub struct RecordIter<'a> {
+    bytes: &'a [u8],
+    reader: ByteReader<'a>,
+    remaining: u16,
+}
+
+impl<'a> RecordIter<'a> {
+    /// Skip over the remaining records and return the offset after them
+    fn skip_all(&mut self) -> Result<usize, DnsError> {
+        for record in self.by_ref() {
+            record?;
+        }
+        Ok(self.reader.get_pointer())
+    }
+}
+
+impl<'a> Iterator for RecordIter<'a> {
+    type Item = Result<RecordRef<'a>, DnsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let record = (|| {
+            let name = NameRef {
+                bytes: self.bytes,
+                offset: self.reader.get_pointer(),
+            };
+            skip_name(&mut self.reader)?;
+            let r#type = self.reader.next_u16()?;
+            let class = self.reader.next_u16()?;
+            let ttl = self.reader.next_u32()?;
+            let rdlength = self.reader.next_u16()?;
+
+            let rdata_offset = self.reader.get_pointer();
+            self.reader.next_bytes(rdlength as usize)?;
+
+            Ok(RecordRef {
+                name,
+                r#type,
+                class,
+                ttl,
+                bytes: self.bytes,
+                rdata_offset,
+                rdlength,
+            })
+        })();
+
+        // Nothing after malformed data can be trusted
+        if record.is_err() {
+            self.remaining = 0;
+        }
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+
+    use super::*;
+
+    /// Response for example.com. A with a compressed answer name and CNAME target
+    const RESPONSE: &[u8] = &[
+        0x12, 0x34, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0, //
+        7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1, //
+        0xc0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0, 6, 3, b'w', b'w', b'w', 0xc0, 12, //
+        0xc0, 41, 0, 1, 0, 1, 0, 0, 1, 0, 0, 4, 192, 0, 2, 1,
+    ];
+
+    #[test]
+    fn iterators_work() {
+        let message = MessageRef::from_bytes(RESPONSE).unwrap();
+        assert_eq!(message.id(), 0x1234);
+
+        let questions: Vec<_> = message.questions().collect::<Result<_, _>>().unwrap();
+        assert_eq!(questions.len(), 1);
+        assert!(questions[0].qname.eq_name("EXAMPLE.com"));
+        assert!(matches!(questions[0].qtype(), Some(QType::A)));
+
+        let answers: Vec<_> = message
+            .answers()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].name.to_string(), "example.com.");
+        assert!(matches!(answers[0].r#type(), Some(Type::CNAME)));
+        assert_eq!(answers[0].ttl, 60);
+        assert_eq!(answers[1].name.to_name().unwrap(), "www.example.com.");
+        assert_eq!(answers[1].rdata(), &[192, 0, 2, 1]);
+
+        assert_eq!(message.authority().unwrap().count(), 0);
+        assert_eq!(message.additional().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let mut bytes = RESPONSE[..12].to_vec();
+        bytes.extend([3, b'a', b'.', 0xff, 0, 0, 1, 0, 1]);
+        let message = MessageRef::from_bytes(&bytes).unwrap();
+        let question = message.questions().next().unwrap().unwrap();
+
+        assert_eq!(question.qname.to_name().unwrap(), "a\\046\\255.");
+        assert_eq!(question.qname.to_string(), "a\\046\\255.");
+        assert!(question.qname.eq_name("A\\046\\255"));
+        assert!(!question.qname.eq_name("a.\\255"));
+    }
+
+    #[test]
+    fn malformed_messages_return_errors() {
+        assert!(MessageRef::from_bytes(&RESPONSE[..11]).is_err());
+
+        let message = MessageRef::from_bytes(&RESPONSE[..RESPONSE.len() - 1]).unwrap();
+        let answers: Vec<_> = message.answers().unwrap().collect();
+        assert!(answers[0].is_ok());
+        assert!(answers[1].is_err());
+
+        // A pointer to itself
+        let mut looping = RESPONSE[..12].to_vec();
+        looping.extend([0xc0, 12, 0, 1, 0, 1]);
+        let message = MessageRef::from_bytes(&looping).unwrap();
+        let question = message.questions().next().unwrap().unwrap();
+        assert!(question.qname.to_name().is_err());
+        assert!(!question.qname.eq_name(""));
+    }
+
+    #[test]
+    fn forward_pointers_are_rejected() {
+        // The question name points at the name of the answer after it
+        let mut bytes = RESPONSE[..12].to_vec();
+        bytes[7] = 1;
+        bytes.extend([0xc0, 18, 0, 1, 0, 1]);
+        bytes.extend([
+            3, b'c', b'o', b'm', 0, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1,
+        ]);
+        let message = MessageRef::from_bytes(&bytes).unwrap();
+
+        let question = message.questions().next().unwrap().unwrap();
+        assert!(question.qname.to_name().is_err());
+        assert!(!question.qname.eq_name("com."));
+        let answer = message.answers().unwrap().next().unwrap().unwrap();
+        assert_eq!(answer.name.to_name().unwrap(), "com.");
+        assert!(Message::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn names_over_255_bytes_are_rejected() {
+        // 127 one byte labels and the root fill 255 bytes, one more label points back at them
+        let mut bytes = RESPONSE[..12].to_vec();
+        bytes[5] = 2;
+        bytes[7] = 0;
+        bytes.extend([1, b'a'].repeat(127));
+        bytes.extend([0, 0, 1, 0, 1]);
+        bytes.extend([1, b'b', 0xc0, 12, 0, 1, 0, 1]);
+        let message = MessageRef::from_bytes(&bytes).unwrap();
+        let questions: Vec<_> = message.questions().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(questions[0].qname.to_name().unwrap(), "a.".repeat(127));
+        assert!(questions[1].qname.to_name().is_err());
+        assert!(!questions[1]
+            .qname
+            .eq_name(&format!("b.{}", "a.".repeat(127))));
+        assert!(Message::from_bytes(&bytes).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use core::{
+    cmp::Ordering,
+    fmt::Write,
+    hash::{Hash, Hasher},
+};
+
+use crate::error::DnsError;
+
+/// Append a label in presentation format, dots, backslashes and bytes outside of printable
+/// ASCII are escaped as `\DDD`: https://datatracker.ietf.org/doc/html/rfc4343#section-2.1
+pub fn push_label(name: &mut String, label: &[u8]) {
+    for &byte in label {
+        match byte {
+            b'.' | b'\\' | ..=b' ' | 0x7f.. => write!(name, "\\{byte:03}").unwrap(),
+            _ => name.push(byte as char),
+        }
+    }
+    name.push('.');
+}
+
+/// Bytes of a label in presentation format with its escapes decoded one at a time, `None` for
+/// an invalid escape
+fn unescape(label: &str) -> impl Iterator<Item = Option<u8>> + '_ {
+    let mut chars = label.bytes();
+    core::iter::from_fn(move || {
+        let byte = chars.next()?;
+        if byte != b'\\' {
+            return Some(Some(byte));
+        }
+
+        let decode = |chars: &mut core::str::Bytes| {
+            let escaped = chars.next()?;
+            if !escaped.is_ascii_digit() {
+                return Some(escaped);
+            }
+            let mut value = (escaped - b'0') as u16;
+            for _ in 0..2 {
+                let digit = chars.next().filter(u8::is_ascii_digit)?;
+                value = value * 10 + (digit - b'0') as u16;
+            }
+            u8::try_from(value).ok()
+        };
+        Some(decode(&mut chars))
+    })
+}
+
+/// Bytes of a label in presentation format with its escapes decoded, `None` for invalid escapes
+pub fn label_bytes(label: &str) -> Option<Vec<u8>> {
+    unescape(label).collect()
+}
+
+/// Whether the label in presentation format `escaped` decodes to `label`, ignoring ASCII case,
+/// without allocating
+pub fn label_eq(label: &[u8], escaped: &str) -> bool {
+    let mut decoded = unescape(escaped);
+    label.iter().all(|byte| {
+        decoded
+            .next()
+            .flatten()
+            .is_some_and(|decoded| decoded.eq_ignore_ascii_case(byte))
+    }) && decoded.next().is_none()
+}
+
+/// Decoded labels of a name from the root up, without the empty root label
+fn reversed_labels(name: &str) -> impl Iterator<Item = Vec<u8>> + '_ {
+    name.trim_end_matches('.')
+        .rsplit('.')
+        .filter(|label| !label.is_empty())
+        .map(|label| {
+            let mut bytes = label_bytes(label).unwrap_or_else(|| label.as_bytes().to_vec());
+            bytes.make_ascii_lowercase();
+            bytes
+        })
+}
+
+/// Compare names case insensitively with or without a trailing dot: https://datatracker.ietf.org/doc/html/rfc4343
+pub fn names_eq(a: &str, b: &str) -> bool {
+    reversed_labels(a).eq(reversed_labels(b))
+}
+
+/// Hash a name consistently with [`names_eq`]
+pub fn hash_name<H: Hasher>(name: &str, state: &mut H) {
+    for label in reversed_labels(name) {
+        label.hash(state);
+    }
+}
+
+/// Canonical DNS name order, comparing lowercased labels starting from the root: https://datatracker.ietf.org/doc/html/rfc4034#section-6.1
+pub fn cmp_names(a: &str, b: &str) -> Ordering {
+    reversed_labels(a).cmp(reversed_labels(b))
+}
+
+/// Whether `name` is `zone` or a name below it
+pub fn is_subdomain(name: &str, zone: &str) -> bool {
+    let mut labels = reversed_labels(name);
+    reversed_labels(zone).all(|zone_label| labels.next().is_some_and(|label| label == zone_label))
+}
+
+/// Name one label up, `None` for the root
+pub fn parent_name(name: &str) -> Option<&str> {
+    if name.trim_end_matches('.').is_empty() {
+        return None;
+    }
+    Some(name.split_once('.').map_or("", |(_, parent)| parent))
+}
+
+/// Uncompressed wire format of a name in presentation format: https://datatracker.ietf.org/doc/html/rfc1035#section-3.1
+///
+/// Labels are limited to 63 bytes and names to 255: https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4
+pub fn name_bytes(name: &str) -> Result<Vec<u8>, DnsError> {
+    let mut bytes = vec![];
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let label = label_bytes(label).ok_or(DnsError::ParseError("Invalid escape in name"))?;
+        if label.len() > 63 {
+            return Err(DnsError::ParseError("Label too long"));
+        }
+        bytes.push(label.len() as u8);
+        bytes.extend(label);
+    }
+    bytes.push(0);
+
+    if bytes.len() > 255 {
+        return Err(DnsError::ParseError("Name too long"));
+    }
+    Ok(bytes)
+}
+
+/// Uncompressed wire format of the lowercased name, as hashed in DS and NSEC3 records: https://datatracker.ietf.org/doc/html/rfc4034#section-6.2
+pub fn canonical_name_bytes(name: &str) -> Result<Vec<u8>, DnsError> {
+    let mut bytes = name_bytes(name)?;
+    // Length octets are at most 63, below the uppercase letters
+    bytes.make_ascii_lowercase();
+    Ok(bytes)
+}
+
+/// Name in presentation format as given by [`canonical_name_bytes`], escaped letters such as
+/// `\065` are lowercased too
+pub fn canonical_name(name: &str) -> Result<String, DnsError> {
+    let bytes = canonical_name_bytes(name)?;
+    let mut canonical = String::new();
+    let mut rest = &bytes[..];
+    while let Some((&length, labels)) = rest.split_first().filter(|(length, _)| **length > 0) {
+        let (label, next) = labels.split_at(length as usize);
+        push_label(&mut canonical, label);
+        rest = next;
+    }
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+
+    fn hash(name: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hash_name(name, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn names_eq_ignores_case_and_trailing_dot() {
+        assert!(names_eq("WWW.Example.com.", "www.example.com"));
+        assert!(!names_eq("www.example.com.", "example.com."));
+        assert_eq!(hash("WWW.Example.com."), hash("www.example.com"));
+    }
+
+    #[test]
+    fn is_subdomain_works() {
+        assert!(is_subdomain("www.Example.com.", "example.com."));
+        assert!(is_subdomain("example.com", "example.com."));
+        assert!(is_subdomain("example.com.", ""));
+        assert!(!is_subdomain("example.com.", "www.example.com."));
+        assert!(!is_subdomain("badexample.com.", "example.com."));
+    }
+
+    #[test]
+    fn parent_name_works() {
+        assert_eq!(parent_name("www.example.com."), Some("example.com."));
+        assert_eq!(parent_name("com."), Some(""));
+        assert_eq!(parent_name("com"), Some(""));
+        assert_eq!(parent_name(""), None);
+        assert_eq!(parent_name("."), None);
+    }
+
+    #[test]
+    fn canonical_name_bytes_works() {
+        assert_eq!(
+            canonical_name_bytes("WWW.Example.").unwrap(),
+            b"\x03www\x07example\x00"
+        );
+        assert_eq!(canonical_name_bytes("").unwrap(), b"\x00");
+        assert_eq!(
+            canonical_name_bytes("\\087ww.\\069xample.").unwrap(),
+            b"\x03www\x07example\x00"
+        );
+        assert_eq!(
+            canonical_name("\\087W\\046w.Example").unwrap(),
+            "ww\\046w.example."
+        );
+        assert_eq!(canonical_name("").unwrap(), "");
+    }
+
+    #[test]
+    fn name_bytes_works() {
+        assert_eq!(
+            name_bytes("a\\046b.\\255\\\\.").unwrap(),
+            b"\x03a.b\x02\xff\\\x00"
+        );
+        assert!(name_bytes(&format!("{}.", "a".repeat(63))).is_ok());
+        assert!(name_bytes(&format!("{}.", "a".repeat(64))).is_err());
+        assert!(name_bytes(&"a.".repeat(127)).is_ok());
+        assert!(name_bytes(&"a.".repeat(128)).is_err());
+        assert!(name_bytes("\\256.").is_err());
+        assert!(name_bytes("a\\").is_err());
+    }
+
+    #[test]
+    fn labels_round_trip() {
+        let label: Vec<u8> = (0..=255).collect();
+        for chunk in label.chunks(63) {
+            let mut name = String::new();
+            push_label(&mut name, chunk);
+            assert!(name.is_ascii());
+            assert_eq!(label_bytes(name.trim_end_matches('.')).unwrap(), chunk);
+        }
+        assert!(names_eq("\\065.example.", "a.EXAMPLE"));
+    }
+
+    #[test]
+    fn label_eq_decodes_escapes() {
+        assert!(label_eq(b"a.B", "A\\046b"));
+        assert!(label_eq(b"\xff", "\\255"));
+        assert!(!label_eq(b"ab", "a"));
+        assert!(!label_eq(b"a", "ab"));
+        assert!(!label_eq(b"a\x00", "a\\256"));
+        assert!(!label_eq(b"a", "a\\"));
+    }
+
+    #[test]
+    fn cmp_names_uses_canonical_order() {
+        // Example from https://datatracker.ietf.org/doc/html/rfc4034#section-6.1
+        let ordered = [
+            "example.",
+            "a.example.",
+            "yljkjljk.a.example.",
+            "Z.a.example.",
+            "zABC.a.EXAMPLE.",
+            "z.example.",
+            "\u{1}.z.example.",
+            "*.z.example.",
+        ];
+
+        for pair in ordered.windows(2) {
+            assert_eq!(cmp_names(pair[0], pair[1]), Ordering::Less, "{pair:?}");
+            assert_eq!(cmp_names(pair[1], pair[0]), Ordering::Greater, "{pair:?}");
+        }
+        assert_eq!(cmp_names("", "."), Ordering::Equal);
+        assert_eq!(cmp_names(".", "com."), Ordering::Less);
+    }
+}
@@ -4,7 +4,7 @@ use std::fmt::Write;
 use crate::util::UpperCaseFormatter;
 
 // TODO implement https://datatracker.ietf.org/doc/html/rfc2929#section-2.2
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Opcode {
     Query = 0,
     // FIXME: make this outdated https://datatracker.ietf.org/doc/html/rfc3425
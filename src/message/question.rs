@@ -1,6 +1,16 @@
-use core::fmt;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
-#[derive(Debug, Clone, Copy)]
+use bitreader::BitWriter;
+
+use crate::error::DnsError;
+
+use super::{hash_name, names_eq, write_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum QClass {
     IN = 1, // Internet
     CS = 2, // CSNET (Deprecated)
@@ -26,6 +36,18 @@ impl TryFrom<u16> for QClass {
     }
 }
 
+/// Parse the mnemonic of a class as shown by [`fmt::Display`], ignoring case
+impl FromStr for QClass {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        (0..=u8::MAX as u16)
+            .filter_map(|value| QClass::try_from(value).ok())
+            .find(|qclass| qclass.to_string().eq_ignore_ascii_case(s))
+            .ok_or("Unknown qclass")
+    }
+}
+
 impl fmt::Display for QClass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{:?}", self)
@@ -33,29 +55,41 @@ impl fmt::Display for QClass {
 }
 
 // TODO implement all https://en.wikipedia.org/wiki/List_of_DNS_record_types
-#[derive(Debug, Clone, Copy)]
+// Declared in numeric order, so the derived `Ord` sorts by value like `Type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum QType {
-    A,
-    AAAA,
-    NS,
-    MD,
-    MF,
-    CNAME,
-    SOA,
-    MB,
-    MG,
-    MR,
-    NULL,
-    WKS,
-    PTR,
-    HINFO,
-    MINFO,
-    MX,
-    TXT,
-    AXFR,
-    MAILB,
-    MAILA,
-    ALL,
+    A = 1,
+    NS = 2,
+    MD = 3,
+    MF = 4,
+    CNAME = 5,
+    SOA = 6,
+    MB = 7,
+    MG = 8,
+    MR = 9,
+    NULL = 10,
+    WKS = 11,
+    PTR = 12,
+    HINFO = 13,
+    MINFO = 14,
+    MX = 15,
+    TXT = 16,
+    AAAA = 28,
+    SRV = 33,
+    NAPTR = 35,
+    DS = 43,
+    SSHFP = 44,
+    RRSIG = 46,
+    NSEC = 47,
+    DNSKEY = 48,
+    NSEC3 = 50,
+    NSEC3PARAM = 51,
+    TLSA = 52,
+    AXFR = 252,
+    MAILB = 253,
+    MAILA = 254,
+    ALL = 255,
+    CAA = 257,
 }
 impl QType {
     pub fn from_u16(value: u16) -> Option<QType> {
@@ -77,14 +111,37 @@ impl QType {
             15 => Some(QType::MX),
             16 => Some(QType::TXT),
             28 => Some(QType::AAAA),
+            33 => Some(QType::SRV),
+            35 => Some(QType::NAPTR),
+            43 => Some(QType::DS),
+            44 => Some(QType::SSHFP),
+            46 => Some(QType::RRSIG),
+            47 => Some(QType::NSEC),
+            48 => Some(QType::DNSKEY),
+            50 => Some(QType::NSEC3),
+            51 => Some(QType::NSEC3PARAM),
+            52 => Some(QType::TLSA),
             252 => Some(QType::AXFR),
             253 => Some(QType::MAILB),
             254 => Some(QType::MAILA),
             255 => Some(QType::ALL),
+            257 => Some(QType::CAA),
             _ => None,
         }
     }
 }
+/// Parse the mnemonic of a type as shown by [`fmt::Display`], ignoring case
+impl FromStr for QType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        (0..=u16::MAX)
+            .filter_map(QType::from_u16)
+            .find(|qtype| qtype.to_string().eq_ignore_ascii_case(s))
+            .ok_or("Unknown qtype")
+    }
+}
+
 impl fmt::Display for QType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{:?}", self)
@@ -92,8 +149,65 @@ impl fmt::Display for QType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "super::json::QuestionJson",
+        try_from = "super::json::QuestionJson"
+    )
+)]
 pub struct Question {
     pub qname: String,
     pub qtype: QType,
     pub qclass: QClass,
 }
+
+impl Question {
+    /// Return the wire format of the question: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.2
+    pub fn as_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        let mut writer = BitWriter::new();
+        self.write(&mut writer)?;
+        Ok(writer.into_bytes())
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        write_name(writer, &self.qname)?;
+        writer.write_u16(self.qtype as u16, 16);
+        writer.write_u16(self.qclass as u16, 16);
+        Ok(())
+    }
+}
+
+// Names are compared case insensitively
+impl PartialEq for Question {
+    fn eq(&self, other: &Self) -> bool {
+        names_eq(&self.qname, &other.qname)
+            && self.qtype == other.qtype
+            && self.qclass == other.qclass
+    }
+}
+
+impl Eq for Question {}
+
+impl Hash for Question {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_name(&self.qname, state);
+        self.qtype.hash(state);
+        self.qclass.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_works() {
+        assert_eq!("aaaa".parse(), Ok(QType::AAAA));
+        assert_eq!("CAA".parse(), Ok(QType::CAA));
+        assert_eq!("ch".parse(), Ok(QClass::CH));
+        assert_eq!("ANY".parse(), Ok(QClass::Any));
+        assert!("example".parse::<QType>().is_err());
+    }
+}
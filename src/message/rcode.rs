@@ -3,18 +3,34 @@ use std::fmt::Write;
 
 use crate::util::UpperCaseFormatter;
 
-// TODO implement https://datatracker.ietf.org/doc/html/rfc2929#section-2.3
-#[derive(Debug, Clone, Copy)]
+/// Response code, values above 15 need EDNS for their upper 8 bits: https://datatracker.ietf.org/doc/html/rfc6895#section-2.3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u16)]
 pub enum RCode {
-    NoError,
-    FormatError,
-    ServerFailure,
-    NameError,
-    NotImplemented,
-    Refused,
+    NoError = 0,
+    FormatError = 1,
+    ServerFailure = 2,
+    NameError = 3,
+    NotImplemented = 4,
+    Refused = 5,
+    YXDomain = 6,
+    YXRRSet = 7,
+    NXRRSet = 8,
+    NotAuth = 9,
+    NotZone = 10,
+    DSOTypeNI = 11,
+    BadVers = 16,
+    BadKey = 17,
+    BadTime = 18,
+    BadMode = 19,
+    BadName = 20,
+    BadAlg = 21,
+    BadTrunc = 22,
+    BadCookie = 23,
 }
+
 impl RCode {
-    pub fn from_u8(value: u8) -> Option<RCode> {
+    pub fn from_u16(value: u16) -> Option<RCode> {
         match value {
             0 => Some(RCode::NoError),
             1 => Some(RCode::FormatError),
@@ -22,10 +38,35 @@ impl RCode {
             3 => Some(RCode::NameError),
             4 => Some(RCode::NotImplemented),
             5 => Some(RCode::Refused),
+            6 => Some(RCode::YXDomain),
+            7 => Some(RCode::YXRRSet),
+            8 => Some(RCode::NXRRSet),
+            9 => Some(RCode::NotAuth),
+            10 => Some(RCode::NotZone),
+            11 => Some(RCode::DSOTypeNI),
+            16 => Some(RCode::BadVers),
+            17 => Some(RCode::BadKey),
+            18 => Some(RCode::BadTime),
+            19 => Some(RCode::BadMode),
+            20 => Some(RCode::BadName),
+            21 => Some(RCode::BadAlg),
+            22 => Some(RCode::BadTrunc),
+            23 => Some(RCode::BadCookie),
             _ => None,
         }
     }
+
+    /// The lower 4 bits, which are stored in the header
+    pub fn header_bits(self) -> u8 {
+        (self as u16 & 0xf) as u8
+    }
+
+    /// The upper 8 bits, which are stored in the OPT pseudo-record: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3
+    pub fn extended_bits(self) -> u8 {
+        (self as u16 >> 4) as u8
+    }
 }
+
 impl fmt::Display for RCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(UpperCaseFormatter(f), "{:?}", self)
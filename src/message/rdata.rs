@@ -0,0 +1,360 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Hex};
+
+use super::{
+    canonical_name, canonical_name_bytes, hash_name, names_eq, read_name, write_name, Type,
+};
+
+mod caa;
+pub use caa::CAA;
+
+mod dnskey;
+pub use dnskey::DNSKEY;
+
+mod mx;
+pub use mx::MX;
+
+mod ds;
+pub use ds::DS;
+
+mod naptr;
+pub use naptr::NAPTR;
+
+mod nsec;
+pub use nsec::NSEC;
+
+mod nsec3;
+pub use nsec3::NSEC3;
+
+mod nsec3param;
+pub use nsec3param::NSEC3PARAM;
+
+mod rrsig;
+pub use rrsig::RRSIG;
+
+mod soa;
+pub use soa::SOA;
+
+mod srv;
+pub use srv::SRV;
+
+mod sshfp;
+pub use sshfp::SSHFP;
+
+mod tlsa;
+pub use tlsa::TLSA;
+
+mod type_bitmap;
+
+/// Read a length prefixed <character-string>: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3
+fn read_character_string(reader: &mut ByteReader) -> Result<Vec<u8>, DnsError> {
+    let length = reader.next_u8()?;
+    Ok(reader.next_bytes(length as usize)?.to_vec())
+}
+
+/// Write a <character-string>, which is at most 255 bytes long: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3
+fn write_character_string(writer: &mut BitWriter, string: &[u8]) -> Result<(), DnsError> {
+    let length = u8::try_from(string.len())
+        .map_err(|_| DnsError::ParseError("Character string too long"))?;
+    writer.write_u8(length, 8);
+    writer.write_bytes(string);
+    Ok(())
+}
+
+/// Write a <character-string> in presentation format: https://datatracker.ietf.org/doc/html/rfc1035#section-5.1
+fn fmt_character_string(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    write!(f, "\"")?;
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(f, "\\{}", byte as char)?,
+            0x20..=0x7e => write!(f, "{}", byte as char)?,
+            _ => write!(f, "\\{byte:03}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Write the mnemonic of a type, or TYPE followed by its value when unknown: https://datatracker.ietf.org/doc/html/rfc3597#section-5
+fn fmt_type(f: &mut fmt::Formatter<'_>, r#type: u16) -> fmt::Result {
+    match Type::from_u16(r#type) {
+        Some(r#type) => write!(f, "{type}"),
+        None => write!(f, "TYPE{type}"),
+    }
+}
+
+/// Write an NSEC3 salt as hexadecimal, or "-" when there is none: https://datatracker.ietf.org/doc/html/rfc5155#section-3.3
+fn fmt_salt(f: &mut fmt::Formatter<'_>, salt: &[u8]) -> fmt::Result {
+    if salt.is_empty() {
+        write!(f, "-")
+    } else {
+        write!(f, "{}", Hex(salt))
+    }
+}
+
+/// The RDATA of a resource record, parsed according to its type
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "super::json::RDataJson", try_from = "super::json::RDataJson")
+)]
+pub enum RData {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    NS(String),
+    CNAME(String),
+    PTR(String),
+    SOA(SOA),
+    MX(MX),
+    SRV(SRV),
+    NAPTR(NAPTR),
+    SSHFP(SSHFP),
+    TLSA(TLSA),
+    CAA(CAA),
+    DS(DS),
+    RRSIG(RRSIG),
+    NSEC(NSEC),
+    DNSKEY(DNSKEY),
+    NSEC3(NSEC3),
+    NSEC3PARAM(NSEC3PARAM),
+    /// RDATA of a type that is not implemented yet, kept as is
+    Unknown(Vec<u8>),
+}
+
+impl RData {
+    /// Read `rdlength` bytes of RDATA for a record of type `r#type`
+    pub fn read(r#type: Type, reader: &mut ByteReader, rdlength: u16) -> Result<RData, DnsError> {
+        let end = reader.get_pointer() + rdlength as usize;
+
+        let rdata = match r#type {
+            Type::A => RData::A(Ipv4Addr::from(reader.next_u32()?)),
+            Type::AAAA => RData::AAAA(Ipv6Addr::from(reader.next_u128()?)),
+            Type::NS => RData::NS(read_name(reader)?),
+            Type::CNAME => RData::CNAME(read_name(reader)?),
+            Type::PTR => RData::PTR(read_name(reader)?),
+            Type::SOA => RData::SOA(SOA::read(reader)?),
+            Type::MX => RData::MX(MX::read(reader)?),
+            Type::SRV => RData::SRV(SRV::read(reader)?),
+            Type::NAPTR => RData::NAPTR(NAPTR::read(reader)?),
+            Type::SSHFP => RData::SSHFP(SSHFP::read(reader, rdlength)?),
+            Type::TLSA => RData::TLSA(TLSA::read(reader, rdlength)?),
+            Type::CAA => RData::CAA(CAA::read(reader, rdlength)?),
+            Type::DS => RData::DS(DS::read(reader, rdlength)?),
+            Type::RRSIG => RData::RRSIG(RRSIG::read(reader, rdlength)?),
+            Type::NSEC => RData::NSEC(NSEC::read(reader, rdlength)?),
+            Type::DNSKEY => RData::DNSKEY(DNSKEY::read(reader, rdlength)?),
+            Type::NSEC3 => RData::NSEC3(NSEC3::read(reader, rdlength)?),
+            Type::NSEC3PARAM => RData::NSEC3PARAM(NSEC3PARAM::read(reader)?),
+            _ => RData::Unknown(reader.next_bytes(rdlength as usize)?.to_vec()), // TODO implement other RData formats
+        };
+
+        if reader.get_pointer() != end {
+            return Err(DnsError::ParseError("RDATA does not match rdlength"));
+        }
+
+        Ok(rdata)
+    }
+
+    /// Return the wire format of the RDATA
+    pub fn as_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        let mut writer = BitWriter::new();
+        self.write(&mut writer)?;
+        Ok(writer.into_bytes())
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        match self {
+            RData::A(addr) => writer.write_u32(u32::from(*addr), 32),
+            RData::AAAA(addr) => writer.write_u128(u128::from(*addr), 128),
+            RData::NS(name) => write_name(writer, name)?,
+            RData::CNAME(name) => write_name(writer, name)?,
+            RData::PTR(name) => write_name(writer, name)?,
+            RData::SOA(soa) => soa.write(writer)?,
+            RData::MX(mx) => mx.write(writer)?,
+            RData::SRV(srv) => srv.write(writer)?,
+            RData::NAPTR(naptr) => naptr.write(writer)?,
+            RData::SSHFP(sshfp) => sshfp.write(writer),
+            RData::TLSA(tlsa) => tlsa.write(writer),
+            RData::CAA(caa) => caa.write(writer)?,
+            RData::DS(ds) => ds.write(writer),
+            RData::RRSIG(rrsig) => rrsig.write(writer)?,
+            RData::NSEC(nsec) => nsec.write(writer)?,
+            RData::DNSKEY(dnskey) => dnskey.write(writer),
+            RData::NSEC3(nsec3) => nsec3.write(writer)?,
+            RData::NSEC3PARAM(nsec3param) => nsec3param.write(writer)?,
+            RData::Unknown(bytes) => writer.write_bytes(bytes),
+        }
+        Ok(())
+    }
+
+    /// Wire format with the names lowercased: https://datatracker.ietf.org/doc/html/rfc4034#section-6.2
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        let mut writer = BitWriter::new();
+        match self {
+            RData::NS(name) | RData::CNAME(name) | RData::PTR(name) => {
+                writer.write_bytes(&canonical_name_bytes(name)?)
+            }
+            RData::SOA(soa) => SOA {
+                mname: canonical_name(&soa.mname)?,
+                rname: canonical_name(&soa.rname)?,
+                ..soa.clone()
+            }
+            .write(&mut writer)?,
+            RData::MX(mx) => MX {
+                exchange: canonical_name(&mx.exchange)?,
+                ..mx.clone()
+            }
+            .write(&mut writer)?,
+            RData::SRV(srv) => SRV {
+                target: canonical_name(&srv.target)?,
+                ..srv.clone()
+            }
+            .write(&mut writer)?,
+            RData::NAPTR(naptr) => NAPTR {
+                replacement: canonical_name(&naptr.replacement)?,
+                ..naptr.clone()
+            }
+            .write(&mut writer)?,
+            // The next name of NSEC is left as is: https://datatracker.ietf.org/doc/html/rfc6840#section-5.1
+            RData::RRSIG(rrsig) => RRSIG {
+                signer_name: canonical_name(&rrsig.signer_name)?,
+                ..rrsig.clone()
+            }
+            .write(&mut writer)?,
+            _ => self.write(&mut writer)?,
+        }
+        Ok(writer.into_bytes())
+    }
+
+    /// Position of the variant, orders RDATA of different kinds with the same wire format
+    fn variant(&self) -> u8 {
+        match self {
+            RData::A(_) => 0,
+            RData::AAAA(_) => 1,
+            RData::NS(_) => 2,
+            RData::CNAME(_) => 3,
+            RData::PTR(_) => 4,
+            RData::SOA(_) => 5,
+            RData::MX(_) => 6,
+            RData::SRV(_) => 7,
+            RData::NAPTR(_) => 8,
+            RData::SSHFP(_) => 9,
+            RData::TLSA(_) => 10,
+            RData::CAA(_) => 11,
+            RData::DS(_) => 12,
+            RData::RRSIG(_) => 13,
+            RData::NSEC(_) => 14,
+            RData::DNSKEY(_) => 15,
+            RData::NSEC3(_) => 16,
+            RData::NSEC3PARAM(_) => 17,
+            RData::Unknown(_) => 18,
+        }
+    }
+}
+
+// Names are compared case insensitively
+impl PartialEq for RData {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RData::A(a), RData::A(b)) => a == b,
+            (RData::AAAA(a), RData::AAAA(b)) => a == b,
+            (RData::NS(a), RData::NS(b)) => names_eq(a, b),
+            (RData::CNAME(a), RData::CNAME(b)) => names_eq(a, b),
+            (RData::PTR(a), RData::PTR(b)) => names_eq(a, b),
+            (RData::SOA(a), RData::SOA(b)) => a == b,
+            (RData::MX(a), RData::MX(b)) => a == b,
+            (RData::SRV(a), RData::SRV(b)) => a == b,
+            (RData::NAPTR(a), RData::NAPTR(b)) => a == b,
+            (RData::SSHFP(a), RData::SSHFP(b)) => a == b,
+            (RData::TLSA(a), RData::TLSA(b)) => a == b,
+            (RData::CAA(a), RData::CAA(b)) => a == b,
+            (RData::DS(a), RData::DS(b)) => a == b,
+            (RData::RRSIG(a), RData::RRSIG(b)) => a == b,
+            (RData::NSEC(a), RData::NSEC(b)) => a == b,
+            (RData::DNSKEY(a), RData::DNSKEY(b)) => a == b,
+            (RData::NSEC3(a), RData::NSEC3(b)) => a == b,
+            (RData::NSEC3PARAM(a), RData::NSEC3PARAM(b)) => a == b,
+            (RData::Unknown(a), RData::Unknown(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for RData {}
+
+impl Hash for RData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.variant().hash(state);
+        match self {
+            RData::A(addr) => addr.hash(state),
+            RData::AAAA(addr) => addr.hash(state),
+            RData::NS(name) | RData::CNAME(name) | RData::PTR(name) => hash_name(name, state),
+            RData::SOA(soa) => soa.hash(state),
+            RData::MX(mx) => mx.hash(state),
+            RData::SRV(srv) => srv.hash(state),
+            RData::NAPTR(naptr) => naptr.hash(state),
+            RData::SSHFP(sshfp) => sshfp.hash(state),
+            RData::TLSA(tlsa) => tlsa.hash(state),
+            RData::CAA(caa) => caa.hash(state),
+            RData::DS(ds) => ds.hash(state),
+            RData::RRSIG(rrsig) => rrsig.hash(state),
+            RData::NSEC(nsec) => nsec.hash(state),
+            RData::DNSKEY(dnskey) => dnskey.hash(state),
+            RData::NSEC3(nsec3) => nsec3.hash(state),
+            RData::NSEC3PARAM(nsec3param) => nsec3param.hash(state),
+            RData::Unknown(bytes) => bytes.hash(state),
+        }
+    }
+}
+
+/// RDATA is ordered by its canonical form as a left-justified unsigned octet sequence: https://datatracker.ietf.org/doc/html/rfc4034#section-6.3
+impl Ord for RData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.canonical_bytes()
+            .ok()
+            .cmp(&other.canonical_bytes().ok())
+            .then(self.variant().cmp(&other.variant()))
+    }
+}
+
+impl PartialOrd for RData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for RData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RData::A(addr) => write!(f, "{addr}"),
+            RData::AAAA(addr) => write!(f, "{addr}"),
+            RData::NS(name) => write!(f, "{name}"),
+            RData::CNAME(name) => write!(f, "{name}"),
+            RData::PTR(name) => write!(f, "{name}"),
+            RData::SOA(soa) => write!(f, "{soa}"),
+            RData::MX(mx) => write!(f, "{mx}"),
+            RData::SRV(srv) => write!(f, "{srv}"),
+            RData::NAPTR(naptr) => write!(f, "{naptr}"),
+            RData::SSHFP(sshfp) => write!(f, "{sshfp}"),
+            RData::TLSA(tlsa) => write!(f, "{tlsa}"),
+            RData::CAA(caa) => write!(f, "{caa}"),
+            RData::DS(ds) => write!(f, "{ds}"),
+            RData::RRSIG(rrsig) => write!(f, "{rrsig}"),
+            RData::NSEC(nsec) => write!(f, "{nsec}"),
+            RData::DNSKEY(dnskey) => write!(f, "{dnskey}"),
+            RData::NSEC3(nsec3) => write!(f, "{nsec3}"),
+            RData::NSEC3PARAM(nsec3param) => write!(f, "{nsec3param}"),
+            // https://datatracker.ietf.org/doc/html/rfc3597#section-5
+            RData::Unknown(bytes) if bytes.is_empty() => write!(f, "\\# 0"),
+            RData::Unknown(bytes) => write!(f, "\\# {} {}", bytes.len(), Hex(bytes)),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::fmt_character_string;
+
+/// Certification Authority Authorization: https://datatracker.ietf.org/doc/html/rfc8659#section-4.1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CAA {
+    /// Bit 0 (128) is the Issuer Critical flag, the others are reserved
+    pub flags: u8,
+    /// Property identifier such as "issue", "issuewild" or "iodef"
+    pub tag: String,
+    pub value: Vec<u8>,
+}
+
+impl CAA {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<CAA, DnsError> {
+        let flags = reader.next_u8()?;
+        // The tag is at least one byte long: https://datatracker.ietf.org/doc/html/rfc8659#section-4.1
+        let tag_length = reader.next_u8()?;
+        if tag_length == 0 {
+            return Err(DnsError::ParseError("Empty CAA tag"));
+        }
+
+        let tag = reader.next_bytes(tag_length as usize)?.to_vec();
+        let tag = String::from_utf8(tag).map_err(|_| DnsError::ParseError("Invalid CAA tag"))?;
+
+        let value_length = (rdlength as usize)
+            .checked_sub(2 + tag_length as usize)
+            .ok_or(DnsError::ParseError("Invalid CAA tag length"))?;
+        let value = reader.next_bytes(value_length)?.to_vec();
+
+        Ok(CAA { flags, tag, value })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        let tag_length = u8::try_from(self.tag.len())
+            .ok()
+            .filter(|length| *length > 0)
+            .ok_or(DnsError::ParseError("Invalid CAA tag length"))?;
+        writer.write_u8(self.flags, 8);
+        writer.write_u8(tag_length, 8);
+        writer.write_bytes(self.tag.as_bytes());
+        writer.write_bytes(&self.value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for CAA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.flags, self.tag)?;
+        fmt_character_string(f, &self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_works() {
+        let bytes = b"\x80\x05issueletsencrypt.org";
+        let mut reader = ByteReader::from_bytes(bytes);
+
+        let caa = CAA::read(&mut reader, bytes.len() as u16).unwrap();
+
+        assert_eq!(caa.flags, 128);
+        assert_eq!(caa.tag, "issue");
+        assert_eq!(caa.value, b"letsencrypt.org");
+        let mut writer = BitWriter::new();
+        caa.write(&mut writer).unwrap();
+        assert_eq!(writer.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn read_fails_on_invalid_tag_length() {
+        let bytes = b"\x00\x09issue";
+        let mut reader = ByteReader::from_bytes(bytes);
+
+        assert!(CAA::read(&mut reader, bytes.len() as u16).is_err());
+
+        let bytes = b"\x00\x00letsencrypt.org";
+        let mut reader = ByteReader::from_bytes(bytes);
+        assert!(CAA::read(&mut reader, bytes.len() as u16).is_err());
+    }
+
+    #[test]
+    fn write_fails_on_invalid_tag_length() {
+        let caa = |tag: String| CAA {
+            flags: 0,
+            tag,
+            value: b"letsencrypt.org".to_vec(),
+        };
+
+        assert!(caa(String::new()).write(&mut BitWriter::new()).is_err());
+        assert!(caa("a".repeat(256)).write(&mut BitWriter::new()).is_err());
+        assert!(caa("a".repeat(255)).write(&mut BitWriter::new()).is_ok());
+    }
+
+    #[test]
+    fn display_works() {
+        let caa = CAA {
+            flags: 0,
+            tag: String::from("iodef"),
+            value: b"mailto:\"security\"@example.com".to_vec(),
+        };
+
+        assert_eq!(
+            caa.to_string(),
+            "0 iodef \"mailto:\\\"security\\\"@example.com\""
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Base64};
+
+/// Public key of a zone: https://datatracker.ietf.org/doc/html/rfc4034#section-2
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DNSKEY {
+    /// See [`DNSKEY::ZONE_KEY`] and [`DNSKEY::SECURE_ENTRY_POINT`]
+    pub flags: u16,
+    /// Always 3
+    pub protocol: u8,
+    /// Signature algorithm, for example 8 for RSA/SHA-256 and 15 for Ed25519
+    pub algorithm: u8,
+    pub public_key: Vec<u8>,
+}
+
+impl DNSKEY {
+    /// Set on keys that may sign the records of the zone
+    pub const ZONE_KEY: u16 = 0x0100;
+    /// Set on keys meant to be referenced by DS records, usually key signing keys
+    pub const SECURE_ENTRY_POINT: u16 = 0x0001;
+
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<DNSKEY, DnsError> {
+        let flags = reader.next_u16()?;
+        let protocol = reader.next_u8()?;
+        let algorithm = reader.next_u8()?;
+
+        let key_length = (rdlength as usize)
+            .checked_sub(4)
+            .ok_or(DnsError::ParseError("Invalid DNSKEY length"))?;
+        let public_key = reader.next_bytes(key_length)?.to_vec();
+
+        Ok(DNSKEY {
+            flags,
+            protocol,
+            algorithm,
+            public_key,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u16(self.flags, 16);
+        writer.write_u8(self.protocol, 8);
+        writer.write_u8(self.algorithm, 8);
+        writer.write_bytes(&self.public_key);
+    }
+
+    pub fn is_zone_key(&self) -> bool {
+        self.flags & DNSKEY::ZONE_KEY != 0
+    }
+
+    pub fn is_secure_entry_point(&self) -> bool {
+        self.flags & DNSKEY::SECURE_ENTRY_POINT != 0
+    }
+
+    /// Identifier used by RRSIG and DS records to refer to the key: https://datatracker.ietf.org/doc/html/rfc4034#appendix-B
+    pub fn key_tag(&self) -> u16 {
+        // RSA/MD5 keys use the third to last and second to last bytes of the modulus instead
+        if self.algorithm == 1 {
+            let key = &self.public_key;
+            return match key.len() {
+                0..=2 => 0,
+                length => u16::from_be_bytes([key[length - 3], key[length - 2]]),
+            };
+        }
+
+        let mut writer = BitWriter::new();
+        self.write(&mut writer);
+
+        let mut sum: u32 = 0;
+        for (i, &byte) in writer.into_bytes().iter().enumerate() {
+            sum += if i.is_multiple_of(2) {
+                (byte as u32) << 8
+            } else {
+                byte as u32
+            };
+        }
+        sum += (sum >> 16) & 0xffff;
+
+        sum as u16
+    }
+}
+
+impl fmt::Display for DNSKEY {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            self.algorithm,
+            Base64(&self.public_key)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::from_base64;
+
+    use super::*;
+
+    /// https://datatracker.ietf.org/doc/html/rfc4034#section-5.4
+    const RFC4034_KEY: &str = "AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==";
+
+    fn dnskey() -> DNSKEY {
+        DNSKEY {
+            flags: 256,
+            protocol: 3,
+            algorithm: 5,
+            public_key: from_base64(RFC4034_KEY).unwrap(),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut writer = BitWriter::new();
+        dnskey().write(&mut writer);
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes[..4], [0x01, 0x00, 0x03, 0x05]);
+
+        let mut reader = ByteReader::from_bytes(&bytes);
+        assert_eq!(
+            DNSKEY::read(&mut reader, bytes.len() as u16).unwrap(),
+            dnskey()
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(dnskey().to_string(), format!("256 3 5 {RFC4034_KEY}"));
+    }
+
+    #[test]
+    fn key_tag_works() {
+        assert_eq!(dnskey().key_tag(), 60485);
+        assert!(dnskey().is_zone_key());
+        assert!(!dnskey().is_secure_entry_point());
+
+        let rsa_md5 = DNSKEY {
+            algorithm: 1,
+            public_key: vec![0x01, 0x02, 0xab, 0xcd, 0xef],
+            ..dnskey()
+        };
+        assert_eq!(rsa_md5.key_tag(), 0xabcd);
+    }
+}
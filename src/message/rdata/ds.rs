@@ -0,0 +1,92 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Hex};
+
+/// Delegation Signer, the digest of a DNSKEY of the child zone: https://datatracker.ietf.org/doc/html/rfc4034#section-5
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DS {
+    /// Key tag of the referenced DNSKEY
+    pub key_tag: u16,
+    pub algorithm: u8,
+    /// Digest algorithm, 1 for SHA-1 and 2 for SHA-256
+    pub digest_type: u8,
+    pub digest: Vec<u8>,
+}
+
+impl DS {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<DS, DnsError> {
+        let key_tag = reader.next_u16()?;
+        let algorithm = reader.next_u8()?;
+        let digest_type = reader.next_u8()?;
+
+        let digest_length = (rdlength as usize)
+            .checked_sub(4)
+            .ok_or(DnsError::ParseError("Invalid DS length"))?;
+        let digest = reader.next_bytes(digest_length)?.to_vec();
+
+        Ok(DS {
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u16(self.key_tag, 16);
+        writer.write_u8(self.algorithm, 8);
+        writer.write_u8(self.digest_type, 8);
+        writer.write_bytes(&self.digest);
+    }
+}
+
+impl fmt::Display for DS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            self.digest_type,
+            Hex(&self.digest)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::from_hex;
+
+    use super::*;
+
+    // https://datatracker.ietf.org/doc/html/rfc4034#section-5.4
+    fn ds() -> DS {
+        DS {
+            key_tag: 60485,
+            algorithm: 5,
+            digest_type: 1,
+            digest: from_hex("2BB183AF5F22588179A53B0A98631FAD1A292118").unwrap(),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut writer = BitWriter::new();
+        ds().write(&mut writer);
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes[..5], [0xec, 0x45, 0x05, 0x01, 0x2b]);
+
+        let mut reader = ByteReader::from_bytes(&bytes);
+        assert_eq!(DS::read(&mut reader, bytes.len() as u16).unwrap(), ds());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            ds().to_string(),
+            "60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{hash_name, names_eq, read_name, write_name};
+
+/// Mail server of a domain: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.9
+#[derive(Debug, Clone)]
+pub struct MX {
+    /// Exchanges with the lowest preference must be tried first
+    pub preference: u16,
+    /// Host accepting mail for the domain, the root when the domain accepts no mail: https://datatracker.ietf.org/doc/html/rfc7505
+    pub exchange: String,
+}
+
+impl MX {
+    pub fn read(reader: &mut ByteReader) -> Result<MX, DnsError> {
+        Ok(MX {
+            preference: reader.next_u16()?,
+            exchange: read_name(reader)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u16(self.preference, 16);
+        write_name(writer, &self.exchange)
+    }
+}
+
+impl PartialEq for MX {
+    fn eq(&self, other: &Self) -> bool {
+        self.preference == other.preference && names_eq(&self.exchange, &other.exchange)
+    }
+}
+
+impl Eq for MX {}
+
+impl Hash for MX {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.preference.hash(state);
+        hash_name(&self.exchange, state);
+    }
+}
+
+impl fmt::Display for MX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exchange = if self.exchange.is_empty() {
+            "."
+        } else {
+            &self.exchange
+        };
+        write!(f, "{} {exchange}", self.preference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mx() -> MX {
+        MX {
+            preference: 10,
+            exchange: String::from("mail.example."),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let expected = b"\x00\x0a\x04mail\x07example\x00";
+
+        let mut writer = BitWriter::new();
+        mx().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(expected);
+        assert_eq!(MX::read(&mut reader).unwrap(), mx());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(mx().to_string(), "10 mail.example.");
+        assert_eq!(
+            MX {
+                preference: 0,
+                exchange: String::new()
+            }
+            .to_string(),
+            "0 ."
+        );
+    }
+}
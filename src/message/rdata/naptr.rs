@@ -0,0 +1,163 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{
+    fmt_character_string, hash_name, names_eq, read_character_string, read_name,
+    write_character_string, write_name,
+};
+
+/// Naming Authority Pointer: https://datatracker.ietf.org/doc/html/rfc3403#section-4.1
+///
+/// The character-strings are kept as bytes, they are not necessarily UTF-8.
+#[derive(Debug, Clone)]
+pub struct NAPTR {
+    /// Order in which the records must be processed, lowest first
+    pub order: u16,
+    /// Order in which records with an equal `order` should be processed
+    pub preference: u16,
+    pub flags: Vec<u8>,
+    pub services: Vec<u8>,
+    pub regexp: Vec<u8>,
+    /// Next domain name to query, the root when `regexp` is used instead
+    pub replacement: String,
+}
+
+impl NAPTR {
+    pub fn read(reader: &mut ByteReader) -> Result<NAPTR, DnsError> {
+        let order = reader.next_u16()?;
+        let preference = reader.next_u16()?;
+
+        Ok(NAPTR {
+            order,
+            preference,
+            flags: read_character_string(reader)?,
+            services: read_character_string(reader)?,
+            regexp: read_character_string(reader)?,
+            replacement: read_name(reader)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u16(self.order, 16);
+        writer.write_u16(self.preference, 16);
+
+        write_character_string(writer, &self.flags)?;
+        write_character_string(writer, &self.services)?;
+        write_character_string(writer, &self.regexp)?;
+
+        // The replacement field must not be compressed: https://datatracker.ietf.org/doc/html/rfc3403#section-4.1
+        write_name(writer, &self.replacement)
+    }
+}
+
+// The replacement is a name and compared case insensitively, the other fields are not
+impl PartialEq for NAPTR {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order
+            && self.preference == other.preference
+            && self.flags == other.flags
+            && self.services == other.services
+            && self.regexp == other.regexp
+            && names_eq(&self.replacement, &other.replacement)
+    }
+}
+
+impl Eq for NAPTR {}
+
+impl Hash for NAPTR {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.order.hash(state);
+        self.preference.hash(state);
+        self.flags.hash(state);
+        self.services.hash(state);
+        self.regexp.hash(state);
+        hash_name(&self.replacement, state);
+    }
+}
+
+impl fmt::Display for NAPTR {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.order, self.preference)?;
+        fmt_character_string(f, &self.flags)?;
+        write!(f, " ")?;
+        fmt_character_string(f, &self.services)?;
+        write!(f, " ")?;
+        fmt_character_string(f, &self.regexp)?;
+
+        if self.replacement.is_empty() {
+            write!(f, " .")
+        } else {
+            write!(f, " {}", self.replacement)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naptr() -> NAPTR {
+        NAPTR {
+            order: 100,
+            preference: 10,
+            flags: b"u".to_vec(),
+            services: b"E2U+sip".to_vec(),
+            regexp: b"!^.*$!sip:info@example.com!".to_vec(),
+            replacement: String::new(),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut expected = vec![0, 100, 0, 10, 1, b'u', 7];
+        expected.extend(b"E2U+sip");
+        expected.push(27);
+        expected.extend(b"!^.*$!sip:info@example.com!");
+        expected.push(0);
+
+        let mut writer = BitWriter::new();
+        naptr().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(&expected);
+        assert_eq!(NAPTR::read(&mut reader).unwrap(), naptr());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            naptr().to_string(),
+            "100 10 \"u\" \"E2U+sip\" \"!^.*$!sip:info@example.com!\" ."
+        );
+    }
+
+    #[test]
+    fn non_utf8_strings_are_escaped() {
+        let naptr = NAPTR {
+            regexp: vec![b'"', 0xff, b'\\'],
+            ..naptr()
+        };
+        let mut writer = BitWriter::new();
+        naptr.write(&mut writer).unwrap();
+
+        let mut reader = ByteReader::from_bytes(writer.as_bytes());
+        let decoded = NAPTR::read(&mut reader).unwrap();
+        assert_eq!(decoded, naptr);
+        assert_eq!(
+            decoded.to_string(),
+            "100 10 \"u\" \"E2U+sip\" \"\\\"\\255\\\\\" ."
+        );
+
+        let naptr = NAPTR {
+            regexp: vec![b'a'; 256],
+            ..naptr
+        };
+        assert!(naptr.write(&mut BitWriter::new()).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{
+    hash_name, names_eq, read_name,
+    type_bitmap::{fmt_type_bitmap, read_type_bitmap, write_type_bitmap},
+    write_name,
+};
+
+/// Next secure record, proving that no names exist between its owner and the next name: https://datatracker.ietf.org/doc/html/rfc4034#section-4
+#[derive(Debug, Clone)]
+pub struct NSEC {
+    /// Next owner name in canonical order, the zone apex for the last name of the zone
+    pub next_domain_name: String,
+    /// Types present at the owner name, sorted
+    pub types: Vec<u16>,
+}
+
+impl NSEC {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<NSEC, DnsError> {
+        let end = reader.get_pointer() + rdlength as usize;
+        let next_domain_name = read_name(reader)?;
+
+        let bitmap_length = end
+            .checked_sub(reader.get_pointer())
+            .ok_or(DnsError::ParseError("Invalid NSEC length"))?;
+
+        Ok(NSEC {
+            next_domain_name,
+            types: read_type_bitmap(reader, bitmap_length)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        write_name(writer, &self.next_domain_name)?;
+        write_type_bitmap(writer, &self.types);
+        Ok(())
+    }
+}
+
+impl PartialEq for NSEC {
+    fn eq(&self, other: &Self) -> bool {
+        names_eq(&self.next_domain_name, &other.next_domain_name) && self.types == other.types
+    }
+}
+
+impl Eq for NSEC {}
+
+impl Hash for NSEC {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_name(&self.next_domain_name, state);
+        self.types.hash(state);
+    }
+}
+
+impl fmt::Display for NSEC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let next_domain_name = if self.next_domain_name.is_empty() {
+            "."
+        } else {
+            &self.next_domain_name
+        };
+        write!(f, "{next_domain_name}")?;
+
+        if !self.types.is_empty() {
+            write!(f, " ")?;
+            fmt_type_bitmap(f, &self.types)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://datatracker.ietf.org/doc/html/rfc4034#section-4.3
+    fn nsec() -> NSEC {
+        NSEC {
+            next_domain_name: String::from("host.example.com."),
+            types: vec![1, 15, 46, 47, 1234],
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut expected = b"\x04host\x07example\x03com\x00".to_vec();
+        expected.extend(b"\x00\x06\x40\x01\x00\x00\x00\x03\x04\x1b");
+        expected.extend([0; 26]);
+        expected.push(0x20);
+
+        let mut writer = BitWriter::new();
+        nsec().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(&expected);
+        assert_eq!(
+            NSEC::read(&mut reader, expected.len() as u16).unwrap(),
+            nsec()
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            nsec().to_string(),
+            "host.example.com. A MX RRSIG NSEC TYPE1234"
+        );
+    }
+}
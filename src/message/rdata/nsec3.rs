@@ -0,0 +1,134 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Base32Hex};
+
+use super::{
+    fmt_salt, read_character_string,
+    type_bitmap::{fmt_type_bitmap, read_type_bitmap, write_type_bitmap},
+    write_character_string,
+};
+
+/// Hashed next secure record, NSEC over hashed owner names: https://datatracker.ietf.org/doc/html/rfc5155#section-3
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NSEC3 {
+    /// 1 for SHA-1, the only algorithm defined
+    pub hash_algorithm: u8,
+    /// See [`NSEC3::OPT_OUT`]
+    pub flags: u8,
+    /// Additional times the hash is applied
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+    /// Next hashed owner name in hash order, without the zone
+    pub next_hashed_owner: Vec<u8>,
+    /// Types present at the original owner name, sorted
+    pub types: Vec<u16>,
+}
+
+impl NSEC3 {
+    /// Set when the span may contain insecure delegations
+    pub const OPT_OUT: u8 = 0x01;
+
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<NSEC3, DnsError> {
+        let end = reader.get_pointer() + rdlength as usize;
+
+        let hash_algorithm = reader.next_u8()?;
+        let flags = reader.next_u8()?;
+        let iterations = reader.next_u16()?;
+        let salt = read_character_string(reader)?;
+        let next_hashed_owner = read_character_string(reader)?;
+        if next_hashed_owner.is_empty() {
+            return Err(DnsError::ParseError("Empty NSEC3 hash"));
+        }
+
+        let bitmap_length = end
+            .checked_sub(reader.get_pointer())
+            .ok_or(DnsError::ParseError("Invalid NSEC3 length"))?;
+
+        Ok(NSEC3 {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner,
+            types: read_type_bitmap(reader, bitmap_length)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u8(self.hash_algorithm, 8);
+        writer.write_u8(self.flags, 8);
+        writer.write_u16(self.iterations, 16);
+        write_character_string(writer, &self.salt)?;
+        write_character_string(writer, &self.next_hashed_owner)?;
+        write_type_bitmap(writer, &self.types);
+        Ok(())
+    }
+
+    pub fn is_opt_out(&self) -> bool {
+        self.flags & NSEC3::OPT_OUT != 0
+    }
+}
+
+impl fmt::Display for NSEC3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            self.hash_algorithm, self.flags, self.iterations
+        )?;
+        fmt_salt(f, &self.salt)?;
+        write!(f, " {}", Base32Hex(&self.next_hashed_owner))?;
+
+        if !self.types.is_empty() {
+            write!(f, " ")?;
+            fmt_type_bitmap(f, &self.types)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::from_base32hex;
+
+    use super::*;
+
+    // https://datatracker.ietf.org/doc/html/rfc5155#appendix-A
+    fn nsec3() -> NSEC3 {
+        NSEC3 {
+            hash_algorithm: 1,
+            flags: 1,
+            iterations: 12,
+            salt: vec![0xaa, 0xbb, 0xcc, 0xdd],
+            next_hashed_owner: from_base32hex("2t7b4g4vsa5smi47k61mv5bv1a22bojr").unwrap(),
+            types: vec![2, 6, 15, 46, 48, 51],
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut writer = BitWriter::new();
+        nsec3().write(&mut writer).unwrap();
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes[..10], *b"\x01\x01\x00\x0c\x04\xaa\xbb\xcc\xdd\x14");
+        assert_eq!(bytes[30..], *b"\x00\x07\x22\x01\x00\x00\x00\x02\x90");
+
+        let mut reader = ByteReader::from_bytes(&bytes);
+        assert_eq!(
+            NSEC3::read(&mut reader, bytes.len() as u16).unwrap(),
+            nsec3()
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            nsec3().to_string(),
+            "1 1 12 AABBCCDD 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR NS SOA MX RRSIG DNSKEY NSEC3PARAM"
+        );
+        assert!(nsec3().is_opt_out());
+    }
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{fmt_salt, read_character_string, write_character_string};
+
+/// Parameters an authoritative server uses to compute NSEC3 hashes: https://datatracker.ietf.org/doc/html/rfc5155#section-4
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NSEC3PARAM {
+    /// 1 for SHA-1, the only algorithm defined
+    pub hash_algorithm: u8,
+    /// Zero in NSEC3PARAM records
+    pub flags: u8,
+    /// Additional times the hash is applied
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+}
+
+impl NSEC3PARAM {
+    pub fn read(reader: &mut ByteReader) -> Result<NSEC3PARAM, DnsError> {
+        Ok(NSEC3PARAM {
+            hash_algorithm: reader.next_u8()?,
+            flags: reader.next_u8()?,
+            iterations: reader.next_u16()?,
+            salt: read_character_string(reader)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u8(self.hash_algorithm, 8);
+        writer.write_u8(self.flags, 8);
+        writer.write_u16(self.iterations, 16);
+        write_character_string(writer, &self.salt)
+    }
+}
+
+impl fmt::Display for NSEC3PARAM {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            self.hash_algorithm, self.flags, self.iterations
+        )?;
+        fmt_salt(f, &self.salt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nsec3param() -> NSEC3PARAM {
+        NSEC3PARAM {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 12,
+            salt: vec![0xaa, 0xbb, 0xcc, 0xdd],
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let expected = b"\x01\x00\x00\x0c\x04\xaa\xbb\xcc\xdd";
+
+        let mut writer = BitWriter::new();
+        nsec3param().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(expected);
+        assert_eq!(NSEC3PARAM::read(&mut reader).unwrap(), nsec3param());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(nsec3param().to_string(), "1 0 12 AABBCCDD");
+
+        let unsalted = NSEC3PARAM {
+            salt: vec![],
+            ..nsec3param()
+        };
+        assert_eq!(unsalted.to_string(), "1 0 12 -");
+    }
+}
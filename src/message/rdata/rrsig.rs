@@ -0,0 +1,224 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Base64};
+
+use super::{fmt_type, hash_name, names_eq, read_name, write_name};
+
+/// Signature over an RRset: https://datatracker.ietf.org/doc/html/rfc4034#section-3
+#[derive(Debug, Clone)]
+pub struct RRSIG {
+    /// Type of the signed RRset, kept as a number since any type can be signed
+    pub type_covered: u16,
+    pub algorithm: u8,
+    /// Labels of the owner name without the root and a leading wildcard
+    pub labels: u8,
+    pub original_ttl: u32,
+    /// Seconds since the epoch, compared with serial number arithmetic
+    pub expiration: u32,
+    pub inception: u32,
+    pub key_tag: u16,
+    /// Zone of the DNSKEY that made the signature
+    pub signer_name: String,
+    pub signature: Vec<u8>,
+}
+
+impl RRSIG {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<RRSIG, DnsError> {
+        let end = reader.get_pointer() + rdlength as usize;
+
+        let type_covered = reader.next_u16()?;
+        let algorithm = reader.next_u8()?;
+        let labels = reader.next_u8()?;
+        let original_ttl = reader.next_u32()?;
+        let expiration = reader.next_u32()?;
+        let inception = reader.next_u32()?;
+        let key_tag = reader.next_u16()?;
+        let signer_name = read_name(reader)?;
+
+        let signature_length = end
+            .checked_sub(reader.get_pointer())
+            .ok_or(DnsError::ParseError("Invalid RRSIG length"))?;
+        let signature = reader.next_bytes(signature_length)?.to_vec();
+
+        Ok(RRSIG {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer_name,
+            signature,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        self.write_without_signature(writer)?;
+        writer.write_bytes(&self.signature);
+        Ok(())
+    }
+
+    /// The RDATA up to the signer's name, the start of the data being signed: https://datatracker.ietf.org/doc/html/rfc4034#section-3.1.8.1
+    pub fn write_without_signature(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u16(self.type_covered, 16);
+        writer.write_u8(self.algorithm, 8);
+        writer.write_u8(self.labels, 8);
+        writer.write_u32(self.original_ttl, 32);
+        writer.write_u32(self.expiration, 32);
+        writer.write_u32(self.inception, 32);
+        writer.write_u16(self.key_tag, 16);
+        write_name(writer, &self.signer_name)
+    }
+}
+
+impl PartialEq for RRSIG {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_covered == other.type_covered
+            && self.algorithm == other.algorithm
+            && self.labels == other.labels
+            && self.original_ttl == other.original_ttl
+            && self.expiration == other.expiration
+            && self.inception == other.inception
+            && self.key_tag == other.key_tag
+            && names_eq(&self.signer_name, &other.signer_name)
+            && self.signature == other.signature
+    }
+}
+
+impl Eq for RRSIG {}
+
+impl Hash for RRSIG {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_covered.hash(state);
+        self.algorithm.hash(state);
+        self.labels.hash(state);
+        self.original_ttl.hash(state);
+        self.expiration.hash(state);
+        self.inception.hash(state);
+        self.key_tag.hash(state);
+        hash_name(&self.signer_name, state);
+        self.signature.hash(state);
+    }
+}
+
+/// Write seconds since the epoch as YYYYMMDDHHmmSS in UTC: https://datatracker.ietf.org/doc/html/rfc4034#section-3.2
+fn fmt_timestamp(f: &mut fmt::Formatter<'_>, timestamp: u32) -> fmt::Result {
+    let days = timestamp as u64 / 86400;
+    let seconds = timestamp as u64 % 86400;
+
+    // Civil date from days since the epoch: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+
+    write!(
+        f,
+        "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+impl fmt::Display for RRSIG {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_type(f, self.type_covered)?;
+        write!(
+            f,
+            " {} {} {} ",
+            self.algorithm, self.labels, self.original_ttl
+        )?;
+        fmt_timestamp(f, self.expiration)?;
+        write!(f, " ")?;
+        fmt_timestamp(f, self.inception)?;
+
+        let signer_name = if self.signer_name.is_empty() {
+            "."
+        } else {
+            &self.signer_name
+        };
+        write!(
+            f,
+            " {} {signer_name} {}",
+            self.key_tag,
+            Base64(&self.signature)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::from_base64;
+
+    use super::*;
+
+    const SIGNATURE: &str = "oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCHX3DDKdfb+v6oB9wfuh3DTJXUAfI/M0zmO/zz8bW0Rznl8O3tGNazPwQKkRN20XPXV6nwwfoXmJQbsLNrLfkGJ5D6fwFm8nN+6pBzeDQfsS3Ap3o=";
+
+    // https://datatracker.ietf.org/doc/html/rfc4034#section-3.3
+    fn rrsig() -> RRSIG {
+        RRSIG {
+            type_covered: 1,
+            algorithm: 5,
+            labels: 3,
+            original_ttl: 86400,
+            expiration: 1048354263,
+            inception: 1045762263,
+            key_tag: 2642,
+            signer_name: String::from("example.com."),
+            signature: from_base64(SIGNATURE).unwrap(),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let mut writer = BitWriter::new();
+        rrsig().write(&mut writer).unwrap();
+        let bytes = writer.into_bytes();
+        assert_eq!(
+            bytes[..31],
+            *b"\x00\x01\x05\x03\x00\x01\x51\x80\x3e\x7c\x9d\xd7\x3e\x55\x10\xd7\x0a\x52\x07example\x03com\x00"
+        );
+
+        let mut reader = ByteReader::from_bytes(&bytes);
+        assert_eq!(
+            RRSIG::read(&mut reader, bytes.len() as u16).unwrap(),
+            rrsig()
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            rrsig().to_string(),
+            format!("A 5 3 86400 20030322173103 20030220173103 2642 example.com. {SIGNATURE}")
+        );
+
+        let rrsig = RRSIG {
+            type_covered: 65280,
+            expiration: u32::MAX,
+            inception: 0,
+            signer_name: String::new(),
+            ..rrsig()
+        };
+        assert!(rrsig
+            .to_string()
+            .starts_with("TYPE65280 5 3 86400 21060207062815 19700101000000 2642 . "));
+    }
+}
@@ -0,0 +1,141 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{hash_name, names_eq, read_name, write_name};
+
+/// Start of a zone of authority: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.13
+#[derive(Debug, Clone)]
+pub struct SOA {
+    /// Primary name server of the zone
+    pub mname: String,
+    /// Mailbox of the person responsible for the zone, the first label is the local part
+    pub rname: String,
+    pub serial: u32,
+    pub refresh: u32,
+    pub retry: u32,
+    pub expire: u32,
+    /// TTL of negative responses: https://datatracker.ietf.org/doc/html/rfc2308#section-4
+    pub minimum: u32,
+}
+
+impl SOA {
+    pub fn read(reader: &mut ByteReader) -> Result<SOA, DnsError> {
+        Ok(SOA {
+            mname: read_name(reader)?,
+            rname: read_name(reader)?,
+            serial: reader.next_u32()?,
+            refresh: reader.next_u32()?,
+            retry: reader.next_u32()?,
+            expire: reader.next_u32()?,
+            minimum: reader.next_u32()?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        write_name(writer, &self.mname)?;
+        write_name(writer, &self.rname)?;
+        for value in [
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum,
+        ] {
+            writer.write_u32(value, 32);
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for SOA {
+    fn eq(&self, other: &Self) -> bool {
+        names_eq(&self.mname, &other.mname)
+            && names_eq(&self.rname, &other.rname)
+            && self.serial == other.serial
+            && self.refresh == other.refresh
+            && self.retry == other.retry
+            && self.expire == other.expire
+            && self.minimum == other.minimum
+    }
+}
+
+impl Eq for SOA {}
+
+impl Hash for SOA {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_name(&self.mname, state);
+        hash_name(&self.rname, state);
+        self.serial.hash(state);
+        self.refresh.hash(state);
+        self.retry.hash(state);
+        self.expire.hash(state);
+        self.minimum.hash(state);
+    }
+}
+
+impl fmt::Display for SOA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |name: &str| {
+            if name.is_empty() {
+                String::from(".")
+            } else {
+                String::from(name)
+            }
+        };
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            name(&self.mname),
+            name(&self.rname),
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn soa() -> SOA {
+        SOA {
+            mname: String::from("ns.example."),
+            rname: String::from("admin.example."),
+            serial: 2024010101,
+            refresh: 7200,
+            retry: 3600,
+            expire: 1209600,
+            minimum: 300,
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let expected = b"\x02ns\x07example\x00\x05admin\x07example\x00\
+            \x78\xa3\xf1\x75\x00\x00\x1c\x20\x00\x00\x0e\x10\x00\x12\x75\x00\x00\x00\x01\x2c";
+
+        let mut writer = BitWriter::new();
+        soa().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(expected);
+        assert_eq!(SOA::read(&mut reader).unwrap(), soa());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(
+            soa().to_string(),
+            "ns.example. admin.example. 2024010101 7200 3600 1209600 300"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::{hash_name, names_eq, read_name, write_name};
+
+/// Location of a service: https://datatracker.ietf.org/doc/html/rfc2782
+#[derive(Debug, Clone)]
+pub struct SRV {
+    /// Targets with the lowest priority must be tried first
+    pub priority: u16,
+    /// Relative chance of a target to be picked among those of equal priority
+    pub weight: u16,
+    pub port: u16,
+    /// Host providing the service, the root when the service is not available
+    pub target: String,
+}
+
+impl SRV {
+    pub fn read(reader: &mut ByteReader) -> Result<SRV, DnsError> {
+        Ok(SRV {
+            priority: reader.next_u16()?,
+            weight: reader.next_u16()?,
+            port: reader.next_u16()?,
+            target: read_name(reader)?,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        writer.write_u16(self.priority, 16);
+        writer.write_u16(self.weight, 16);
+        writer.write_u16(self.port, 16);
+        write_name(writer, &self.target)
+    }
+}
+
+impl PartialEq for SRV {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+            && self.weight == other.weight
+            && self.port == other.port
+            && names_eq(&self.target, &other.target)
+    }
+}
+
+impl Eq for SRV {}
+
+impl Hash for SRV {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.priority.hash(state);
+        self.weight.hash(state);
+        self.port.hash(state);
+        hash_name(&self.target, state);
+    }
+}
+
+impl fmt::Display for SRV {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = if self.target.is_empty() {
+            "."
+        } else {
+            &self.target
+        };
+        write!(
+            f,
+            "{} {} {} {target}",
+            self.priority, self.weight, self.port
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn srv() -> SRV {
+        SRV {
+            priority: 10,
+            weight: 60,
+            port: 5060,
+            target: String::from("sip.example."),
+        }
+    }
+
+    #[test]
+    fn write_works() {
+        let expected = b"\x00\x0a\x00\x3c\x13\xc4\x03sip\x07example\x00";
+
+        let mut writer = BitWriter::new();
+        srv().write(&mut writer).unwrap();
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(expected);
+        assert_eq!(SRV::read(&mut reader).unwrap(), srv());
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(srv().to_string(), "10 60 5060 sip.example.");
+    }
+}
@@ -0,0 +1,93 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Hex};
+
+/// SSH Public Key Fingerprint: https://datatracker.ietf.org/doc/html/rfc4255#section-3.1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SSHFP {
+    /// Public key algorithm, for example 1 for RSA and 4 for Ed25519
+    pub algorithm: u8,
+    /// Fingerprint hash, 1 for SHA-1 and 2 for SHA-256
+    pub fingerprint_type: u8,
+    pub fingerprint: Vec<u8>,
+}
+
+impl SSHFP {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<SSHFP, DnsError> {
+        let algorithm = reader.next_u8()?;
+        let fingerprint_type = reader.next_u8()?;
+
+        let fingerprint_length = (rdlength as usize)
+            .checked_sub(2)
+            .ok_or(DnsError::ParseError("Invalid SSHFP length"))?;
+        let fingerprint = reader.next_bytes(fingerprint_length)?.to_vec();
+
+        Ok(SSHFP {
+            algorithm,
+            fingerprint_type,
+            fingerprint,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u8(self.algorithm, 8);
+        writer.write_u8(self.fingerprint_type, 8);
+        writer.write_bytes(&self.fingerprint);
+    }
+}
+
+impl fmt::Display for SSHFP {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.algorithm,
+            self.fingerprint_type,
+            Hex(&self.fingerprint)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_works() {
+        let bytes = b"\x04\x02\x12\x34\x56\x78";
+        let mut reader = ByteReader::from_bytes(bytes);
+
+        let sshfp = SSHFP::read(&mut reader, bytes.len() as u16).unwrap();
+
+        assert_eq!(sshfp.algorithm, 4);
+        assert_eq!(sshfp.fingerprint_type, 2);
+        assert_eq!(sshfp.fingerprint, [0x12, 0x34, 0x56, 0x78]);
+        let mut writer = BitWriter::new();
+        sshfp.write(&mut writer);
+        assert_eq!(writer.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn read_fails_on_invalid_length() {
+        let bytes = b"\x04\x02\x12";
+        let mut reader = ByteReader::from_bytes(bytes);
+        assert!(SSHFP::read(&mut reader, 1).is_err());
+
+        // The fingerprint runs past the end of the bytes
+        let mut reader = ByteReader::from_bytes(bytes);
+        assert!(SSHFP::read(&mut reader, 4).is_err());
+    }
+
+    #[test]
+    fn display_works() {
+        let sshfp = SSHFP {
+            algorithm: 1,
+            fingerprint_type: 1,
+            fingerprint: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+
+        assert_eq!(sshfp.to_string(), "1 1 DEADBEEF");
+    }
+}
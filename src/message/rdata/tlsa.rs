@@ -0,0 +1,101 @@
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::{error::DnsError, util::Hex};
+
+/// TLSA certificate association: https://datatracker.ietf.org/doc/html/rfc6698#section-2.1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TLSA {
+    /// How the association is used, for example 3 for DANE-EE
+    pub usage: u8,
+    /// 0 when matching the full certificate, 1 for only the SubjectPublicKeyInfo
+    pub selector: u8,
+    /// 0 for an exact match, 1 for SHA-256 and 2 for SHA-512
+    pub matching_type: u8,
+    pub data: Vec<u8>,
+}
+
+impl TLSA {
+    pub fn read(reader: &mut ByteReader, rdlength: u16) -> Result<TLSA, DnsError> {
+        let usage = reader.next_u8()?;
+        let selector = reader.next_u8()?;
+        let matching_type = reader.next_u8()?;
+
+        let data_length = (rdlength as usize)
+            .checked_sub(3)
+            .ok_or(DnsError::ParseError("Invalid TLSA length"))?;
+        let data = reader.next_bytes(data_length)?.to_vec();
+
+        Ok(TLSA {
+            usage,
+            selector,
+            matching_type,
+            data,
+        })
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u8(self.usage, 8);
+        writer.write_u8(self.selector, 8);
+        writer.write_u8(self.matching_type, 8);
+        writer.write_bytes(&self.data);
+    }
+}
+
+impl fmt::Display for TLSA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.usage,
+            self.selector,
+            self.matching_type,
+            Hex(&self.data)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_works() {
+        let bytes = b"\x03\x01\x01\x0c\x72\xac\x70";
+        let mut reader = ByteReader::from_bytes(bytes);
+
+        let tlsa = TLSA::read(&mut reader, bytes.len() as u16).unwrap();
+
+        assert_eq!(tlsa.usage, 3);
+        assert_eq!(tlsa.selector, 1);
+        assert_eq!(tlsa.matching_type, 1);
+        assert_eq!(tlsa.data, [0x0c, 0x72, 0xac, 0x70]);
+        let mut writer = BitWriter::new();
+        tlsa.write(&mut writer);
+        assert_eq!(writer.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn read_fails_on_invalid_length() {
+        let bytes = b"\x03\x01\x01\x0c";
+        let mut reader = ByteReader::from_bytes(bytes);
+        assert!(TLSA::read(&mut reader, 2).is_err());
+
+        // The data runs past the end of the bytes
+        let mut reader = ByteReader::from_bytes(bytes);
+        assert!(TLSA::read(&mut reader, 5).is_err());
+    }
+
+    #[test]
+    fn display_works() {
+        let tlsa = TLSA {
+            usage: 3,
+            selector: 1,
+            matching_type: 1,
+            data: vec![0x0c, 0x72, 0xac, 0x70],
+        };
+
+        assert_eq!(tlsa.to_string(), "3 1 1 0C72AC70");
+    }
+}
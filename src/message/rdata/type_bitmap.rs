@@ -0,0 +1,117 @@
+//! Type bitmaps listing the types present at a name: https://datatracker.ietf.org/doc/html/rfc4034#section-4.1.2
+
+use core::fmt;
+
+use bitreader::{BitWriter, ByteReader};
+
+use crate::error::DnsError;
+
+use super::fmt_type;
+
+/// Read `length` bytes of windows into the sorted list of types they contain
+pub(super) fn read_type_bitmap(
+    reader: &mut ByteReader,
+    length: usize,
+) -> Result<Vec<u16>, DnsError> {
+    let end = reader.get_pointer() + length;
+    let mut types = vec![];
+    let mut previous_window = None;
+
+    while reader.get_pointer() < end {
+        let window = reader.next_u8()?;
+        let bitmap_length = reader.next_u8()?;
+        if previous_window.is_some_and(|previous| window <= previous) {
+            return Err(DnsError::ParseError("Type bitmap windows out of order"));
+        }
+        if !(1..=32).contains(&bitmap_length) {
+            return Err(DnsError::ParseError("Invalid type bitmap length"));
+        }
+        previous_window = Some(window);
+
+        for (i, &byte) in reader
+            .next_bytes(bitmap_length as usize)?
+            .iter()
+            .enumerate()
+        {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    types.push(((window as u16) << 8) | (i * 8 + bit) as u16);
+                }
+            }
+        }
+    }
+
+    if reader.get_pointer() != end {
+        return Err(DnsError::ParseError("Type bitmap exceeds RDATA"));
+    }
+
+    Ok(types)
+}
+
+/// Write `types` as windows, each only as long as needed for its highest type
+pub(super) fn write_type_bitmap(writer: &mut BitWriter, types: &[u16]) {
+    let mut types = types.to_vec();
+    types.sort_unstable();
+    types.dedup();
+
+    for window in types.chunk_by(|a, b| a >> 8 == b >> 8) {
+        let mut bitmap = [0u8; 32];
+        for &r#type in window {
+            let low = (r#type & 0xff) as usize;
+            bitmap[low / 8] |= 0x80 >> (low % 8);
+        }
+        let length = (window[window.len() - 1] & 0xff) as usize / 8 + 1;
+
+        writer.write_u8((window[0] >> 8) as u8, 8);
+        writer.write_u8(length as u8, 8);
+        writer.write_bytes(&bitmap[..length]);
+    }
+}
+
+/// Write the mnemonics of `types` separated by spaces
+pub(super) fn fmt_type_bitmap(f: &mut fmt::Formatter<'_>, types: &[u16]) -> fmt::Result {
+    for (i, &r#type) in types.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        fmt_type(f, r#type)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_bitmap_works() {
+        // https://datatracker.ietf.org/doc/html/rfc4034#section-4.3: A MX RRSIG NSEC TYPE1234
+        let mut expected = b"\x00\x06\x40\x01\x00\x00\x00\x03\x04\x1b".to_vec();
+        expected.extend([0; 26]);
+        expected.push(0x20);
+
+        let mut writer = BitWriter::new();
+        write_type_bitmap(&mut writer, &[1234, 47, 1, 46, 15]);
+        assert_eq!(writer.into_bytes(), expected);
+
+        let mut reader = ByteReader::from_bytes(&expected);
+        assert_eq!(
+            read_type_bitmap(&mut reader, expected.len()).unwrap(),
+            [1, 15, 46, 47, 1234]
+        );
+    }
+
+    #[test]
+    fn invalid_type_bitmaps_are_rejected() {
+        for bytes in [
+            &b"\x00\x00"[..],
+            b"\x00\x21",
+            b"\x01\x01\x40\x00\x01\x40",
+            b"\x00\x02\x40",
+        ] {
+            let mut reader = ByteReader::from_bytes(bytes);
+            assert!(read_type_bitmap(&mut reader, bytes.len()).is_err());
+        }
+    }
+}
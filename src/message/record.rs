@@ -1,11 +1,21 @@
-use core::fmt;
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
-#[derive(Debug)]
+use bitreader::BitWriter;
+
+use crate::error::DnsError;
+
+use super::{canonical_name_bytes, cmp_names, hash_name, names_eq, write_name, RData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Class {
-    IN,
-    CS,
-    CH,
-    HS,
+    IN = 1,
+    CS = 2,
+    CH = 3,
+    HS = 4,
 }
 impl Class {
     pub fn from_u16(value: u16) -> Option<Class> {
@@ -25,25 +35,37 @@ impl fmt::Display for Class {
 }
 
 // TODO implement all https://en.wikipedia.org/wiki/List_of_DNS_record_types
-#[derive(Debug)]
+// Variants are declared in increasing numeric order, which makes the derived ordering match their values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Type {
-    A,
-    AAAA,
-    NS,
-    MD,
-    MF,
-    CNAME,
-    SOA,
-    MB,
-    MG,
-    MR,
-    NULL,
-    WKS,
-    PTR,
-    HINFO,
-    MINFO,
-    MX,
-    TXT,
+    A = 1,
+    NS = 2,
+    MD = 3,
+    MF = 4,
+    CNAME = 5,
+    SOA = 6,
+    MB = 7,
+    MG = 8,
+    MR = 9,
+    NULL = 10,
+    WKS = 11,
+    PTR = 12,
+    HINFO = 13,
+    MINFO = 14,
+    MX = 15,
+    TXT = 16,
+    AAAA = 28,
+    SRV = 33,
+    NAPTR = 35,
+    DS = 43,
+    SSHFP = 44,
+    RRSIG = 46,
+    NSEC = 47,
+    DNSKEY = 48,
+    NSEC3 = 50,
+    NSEC3PARAM = 51,
+    TLSA = 52,
+    CAA = 257,
 }
 impl Type {
     pub fn from_u16(value: u16) -> Option<Type> {
@@ -65,6 +87,17 @@ impl Type {
             15 => Some(Type::MX),
             16 => Some(Type::TXT),
             28 => Some(Type::AAAA),
+            33 => Some(Type::SRV),
+            35 => Some(Type::NAPTR),
+            43 => Some(Type::DS),
+            44 => Some(Type::SSHFP),
+            46 => Some(Type::RRSIG),
+            47 => Some(Type::NSEC),
+            48 => Some(Type::DNSKEY),
+            50 => Some(Type::NSEC3),
+            51 => Some(Type::NSEC3PARAM),
+            52 => Some(Type::TLSA),
+            257 => Some(Type::CAA),
             _ => None,
         }
     }
@@ -75,23 +108,116 @@ impl fmt::Display for Type {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "super::json::RecordJson", try_from = "super::json::RecordJson")
+)]
 pub struct Record {
     pub name: String,
     pub r#type: Type,
     pub class: Class,
     pub ttl: u32,
-    // TODO give own struct with known structure for example for A records specify an Ipv4 struct
-    pub rdata: String,
+    pub rdata: RData,
+}
+
+impl Record {
+    /// Return the wire format of the record: https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.3
+    pub fn as_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        let mut writer = BitWriter::new();
+        self.write(&mut writer)?;
+        Ok(writer.into_bytes())
+    }
+
+    pub fn write(&self, writer: &mut BitWriter) -> Result<(), DnsError> {
+        write_name(writer, &self.name)?;
+        writer.write_u16(self.r#type as u16, 16);
+        writer.write_u16(self.class as u16, 16);
+        writer.write_u32(self.ttl, 32);
+
+        // RDLENGTH is filled in once the RDATA has been written
+        let rdlength_pointer = writer.get_pointer();
+        writer.write_u16(0, 16);
+        self.rdata.write(writer)?;
+        let end = writer.get_pointer();
+
+        let rdlength = u16::try_from((end - rdlength_pointer) / 8 - 2)
+            .map_err(|_| DnsError::ParseError("RDATA too long"))?;
+        writer.set_pointer(rdlength_pointer);
+        writer.write_u16(rdlength, 16);
+        writer.set_pointer(end);
+        Ok(())
+    }
+
+    /// Wire format with the owner name lowercased and canonical RDATA, as covered by signatures: https://datatracker.ietf.org/doc/html/rfc4034#section-6.2
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        let rdata = self.rdata.canonical_bytes()?;
+
+        let mut writer = BitWriter::new();
+        writer.write_bytes(&canonical_name_bytes(&self.name)?);
+        writer.write_u16(self.r#type as u16, 16);
+        writer.write_u16(self.class as u16, 16);
+        writer.write_u32(self.ttl, 32);
+        let rdlength =
+            u16::try_from(rdata.len()).map_err(|_| DnsError::ParseError("RDATA too long"))?;
+        writer.write_u16(rdlength, 16);
+        writer.write_bytes(&rdata);
+        Ok(writer.into_bytes())
+    }
+}
+
+// Names are compared case insensitively
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        names_eq(&self.name, &other.name)
+            && self.r#type == other.r#type
+            && self.class == other.class
+            && self.ttl == other.ttl
+            && self.rdata == other.rdata
+    }
+}
+
+impl Eq for Record {}
+
+impl Hash for Record {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_name(&self.name, state);
+        self.r#type.hash(state);
+        self.class.hash(state);
+        self.ttl.hash(state);
+        self.rdata.hash(state);
+    }
+}
+
+/// Records are ordered by canonical name, type and class, records within an RRset by their canonical
+/// RDATA: https://datatracker.ietf.org/doc/html/rfc4034#section-6.3
+impl Ord for Record {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_names(&self.name, &other.name)
+            .then(self.r#type.cmp(&other.r#type))
+            .then(self.class.cmp(&other.class))
+            .then_with(|| self.rdata.cmp(&other.rdata))
+            .then(self.ttl.cmp(&other.ttl))
+    }
+}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl fmt::Display for Record {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}\t{}{}\t{}\t{}\t{}",
-            self.name,
-            if self.name.len() < 30 { "\t" } else { "" }, // TODO use a more solid way of formatting, using this hack now to pass tests
+            "{}\t{}\t{}\t{}\t{}",
+            if self.name.is_empty() {
+                "."
+            } else {
+                &self.name
+            },
             self.ttl,
             self.class,
             self.r#type,
@@ -99,3 +225,122 @@ impl fmt::Display for Record {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, net::Ipv4Addr};
+
+    use crate::message::MX;
+
+    use super::*;
+
+    fn record(name: &str, r#type: Type, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl: 300,
+            rdata,
+        }
+    }
+
+    #[test]
+    fn canonical_bytes_lowercase_names() {
+        let record = record(
+            "WWW.Example.",
+            Type::CNAME,
+            RData::CNAME(String::from("CDN.Example.")),
+        );
+
+        assert_eq!(
+            record.canonical_bytes().unwrap(),
+            b"\x03www\x07example\x00\x00\x05\x00\x01\x00\x00\x01\x2c\x00\x0d\x03cdn\x07example\x00"
+        );
+    }
+
+    #[test]
+    fn escaped_letters_are_lowercased() {
+        let escaped = record(
+            "\\087WW.Example.",
+            Type::CNAME,
+            RData::CNAME(String::from("\\067DN.Example.")),
+        );
+        let plain = record(
+            "www.example.",
+            Type::CNAME,
+            RData::CNAME(String::from("cdn.example.")),
+        );
+
+        assert_eq!(
+            escaped.canonical_bytes().unwrap(),
+            plain.canonical_bytes().unwrap()
+        );
+        assert_eq!(escaped, plain);
+        assert_eq!(escaped.cmp(&plain), Ordering::Equal);
+
+        let mx = |exchange: &str| {
+            RData::MX(MX {
+                preference: 10,
+                exchange: String::from(exchange),
+            })
+        };
+        assert_eq!(
+            mx("\\077X.example.").cmp(&mx("mx.example.")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn rdata_length_is_checked() {
+        let unknown = |length: usize| record("example.", Type::NS, RData::Unknown(vec![0; length]));
+
+        assert!(unknown(0xffff).as_bytes().is_ok());
+        assert!(unknown(0xffff).canonical_bytes().is_ok());
+        assert!(unknown(0x10000).as_bytes().is_err());
+        assert!(unknown(0x10000).canonical_bytes().is_err());
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        let a = record(
+            "WWW.Example.com.",
+            Type::CNAME,
+            RData::CNAME(String::from("CDN.example.net.")),
+        );
+        let b = record(
+            "www.example.com.",
+            Type::CNAME,
+            RData::CNAME(String::from("cdn.example.net.")),
+        );
+
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(HashSet::from([a, b]).len(), 1);
+    }
+
+    #[test]
+    fn records_sort_canonically() {
+        let a = |name: &str, octet: u8| {
+            record(name, Type::A, RData::A(Ipv4Addr::new(192, 0, 2, octet)))
+        };
+        let mut records = vec![
+            a("b.example.", 1),
+            a("example.", 2),
+            record("example.", Type::NS, RData::Unknown(vec![0])),
+            a("example.", 1),
+            a("A.example.", 3),
+        ];
+        records.sort();
+
+        assert_eq!(
+            records,
+            vec![
+                a("example.", 1),
+                a("example.", 2),
+                record("example.", Type::NS, RData::Unknown(vec![0])),
+                a("a.example.", 3),
+                a("b.example.", 1),
+            ]
+        );
+    }
+}
@@ -1,22 +1,692 @@
 use std::{
-    io::Write,
-    net::{IpAddr, Ipv4Addr, TcpStream},
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, TcpStream, UdpSocket},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
 };
 
-use crate::message::Message;
+use rand::{OsRng, Rng};
 
-pub struct Resolver;
+use crate::{
+    error::DnsError,
+    message::{names_eq, Message, QType, RCode, RData, Record, SRV},
+    reverse::reverse_name,
+};
+
+mod address_selection;
+pub use address_selection::sort_addresses;
+
+mod case_randomization;
+pub use case_randomization::randomize_case;
+use case_randomization::{echoes_case, randomize_query_case};
+
+mod srv_selection;
+pub use srv_selection::sort_srv;
+
+mod trace;
+pub use trace::TraceStep;
+
+mod validation;
+pub use validation::{root_trust_anchors, ValidatedResponse, ValidatingResolver};
+
+#[cfg(feature = "tokio")]
+mod async_resolver;
+#[cfg(feature = "tokio")]
+pub use async_resolver::AsyncResolver;
+
+/// Maximum amount of CNAME records followed before giving up
+const MAX_CNAME_CHAIN: usize = 8;
+
+/// Name server used when none is given
+pub const DEFAULT_SERVER: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(1, 1, 1, 1), 53));
+
+/// How long to wait for a response before giving up
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Protocol used to send a query to a name server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+/// Whether `response` answers `query`, anything else could be spoofed or a late reply to an earlier query
+fn is_response_to(response: &Message, query: &Message) -> bool {
+    response.header().response
+        && response.header().id == query.header().id
+        && response.questions() == query.questions()
+}
+
+/// Fail with the RCODE of `response` unless it is NOERROR
+fn check_rcode(response: Message) -> crate::Result<Message> {
+    match response.header().rcode {
+        RCode::NoError => Ok(response),
+        rcode => Err(DnsError::ResponseCode(rcode)),
+    }
+}
+
+/// Length prefix of a message sent over TCP, which limits it to 65535 bytes
+fn tcp_length(message: &[u8]) -> crate::Result<u16> {
+    u16::try_from(message.len()).map_err(|_| DnsError::ParseError("Message too long for TCP"))
+}
+
+fn timed_out() -> DnsError {
+    DnsError::IoError(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "No response from server",
+    ))
+}
+
+/// Attempts at binding a random source port before leaving the choice to the OS
+const BIND_ATTEMPTS: usize = 8;
+
+/// Local address on `port` to send from to reach `server`
+fn local_address(server: SocketAddr, port: u16) -> SocketAddr {
+    match server {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port),
+    }
+}
+
+/// Unprivileged port picked with the CSPRNG, making responses harder to spoof: https://datatracker.ietf.org/doc/html/rfc5452#section-9.2
+fn random_port() -> u16 {
+    loop {
+        let port = OsRng.next_u16();
+        if port >= 1024 {
+            return port;
+        }
+    }
+}
+
+/// Bind a UDP socket on a random source port for queries to `server`
+fn bind_udp(server: SocketAddr) -> io::Result<UdpSocket> {
+    for _ in 0..BIND_ATTEMPTS {
+        match UdpSocket::bind(local_address(server, random_port())) {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => continue,
+            result => return result,
+        }
+    }
+
+    UdpSocket::bind(local_address(server, 0))
+}
+
+/// An address returned by a lookup together with the TTL of its record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRecord {
+    pub addr: IpAddr,
+    pub ttl: u32,
+}
+
+/// Follow the CNAME chain starting at `name` within `answers`, returning the
+/// last name in the chain and the addresses found for it
+fn follow_cnames(name: &str, answers: &[Record]) -> (String, Vec<IpRecord>) {
+    let mut name = String::from(name);
+
+    // Records of a chain are not guaranteed to be in order, bounding by the amount of answers stops loops
+    for _ in 0..answers.len() {
+        let target = answers.iter().find_map(|record| match &record.rdata {
+            RData::CNAME(target) if names_eq(&record.name, &name) => Some(target),
+            _ => None,
+        });
+
+        match target {
+            Some(target) => name = target.clone(),
+            None => break,
+        }
+    }
+
+    let addresses = answers
+        .iter()
+        .filter(|record| names_eq(&record.name, &name))
+        .filter_map(|record| {
+            let addr = match record.rdata {
+                RData::A(addr) => IpAddr::V4(addr),
+                RData::AAAA(addr) => IpAddr::V6(addr),
+                _ => return None,
+            };
+            Some(IpRecord {
+                addr,
+                ttl: record.ttl,
+            })
+        })
+        .collect();
+
+    (name, addresses)
+}
+
+/// Combine the results of A and AAAA lookups, failing only when both failed
+fn merge_addresses(
+    ipv4: crate::Result<Vec<IpRecord>>,
+    ipv6: crate::Result<Vec<IpRecord>>,
+) -> crate::Result<Vec<IpRecord>> {
+    match (ipv4, ipv6) {
+        (Err(e), Err(_)) => Err(e),
+        (ipv4, ipv6) => Ok(ipv4
+            .unwrap_or_default()
+            .into_iter()
+            .chain(ipv6.unwrap_or_default())
+            .collect()),
+    }
+}
+
+/// Stub resolver sending its queries to a recursive server
+///
+/// Message IDs, the order of SRV records of equal priority and the servers queried by
+/// [`Resolver::trace`] are picked using `R`, tests can give a deterministic generator with
+/// [`Resolver::with_rng`].
+pub struct Resolver<R = OsRng> {
+    server: SocketAddr,
+    protocol: Protocol,
+    randomize_case: bool,
+    rng: Mutex<R>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Resolver::new(DEFAULT_SERVER)
+    }
+}
+
+impl Resolver {
+    /// Create a resolver sending its queries to `server`
+    pub fn new(server: SocketAddr) -> Resolver {
+        Resolver::with_rng(server, OsRng)
+    }
+}
+
+impl<R> Resolver<R> {
+    /// Like [`Resolver::new`], picking message IDs and SRV records using `rng`
+    pub fn with_rng(server: SocketAddr, rng: R) -> Resolver<R> {
+        Resolver {
+            server,
+            protocol: Protocol::Udp,
+            randomize_case: false,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Send queries over `protocol`, UDP by default
+    pub fn protocol(mut self, protocol: Protocol) -> Resolver<R> {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Randomize the case of question names and discard responses that don't echo it exactly,
+    /// making spoofed responses harder to get accepted: https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00
+    pub fn randomize_case(mut self, randomize_case: bool) -> Resolver<R> {
+        self.randomize_case = randomize_case;
+        self
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+}
 
 // https://datatracker.ietf.org/doc/html/rfc1035#section-7
 // https://datatracker.ietf.org/doc/html/rfc1034
+impl<R: Rng + Send> Resolver<R> {
+    /// Return the address of `name` that should be tried first, `None` when it has no addresses
+    pub fn lookup(&self, name: &str) -> crate::Result<Option<IpAddr>> {
+        let addresses = self.lookup_ip_sorted(name)?;
+        Ok(addresses.first().map(|record| record.addr))
+    }
+
+    /// Return all IPv4 and IPv6 addresses of `name`, querying A and AAAA records concurrently
+    ///
+    /// Names that don't exist fail with [`DnsError::ResponseCode`], names without addresses return none.
+    pub fn lookup_ip(&self, name: &str) -> crate::Result<Vec<IpRecord>> {
+        let (ipv4, ipv6) = thread::scope(|scope| {
+            let ipv4 = scope.spawn(|| self.lookup_addresses(name, QType::A));
+            let ipv6 = scope.spawn(|| self.lookup_addresses(name, QType::AAAA));
+            (
+                ipv4.join().expect("A lookup panicked"),
+                ipv6.join().expect("AAAA lookup panicked"),
+            )
+        });
+
+        merge_addresses(ipv4, ipv6)
+    }
+
+    /// Like [`Resolver::lookup_ip`] with the addresses sorted in the order they should be tried
+    pub fn lookup_ip_sorted(&self, name: &str) -> crate::Result<Vec<IpRecord>> {
+        let mut addresses = self.lookup_ip(name)?;
+        sort_addresses(&mut addresses);
+        Ok(addresses)
+    }
+
+    /// Query `qtype` addresses of `name`, querying again when the answer ends in a CNAME without addresses
+    fn lookup_addresses(&self, name: &str, qtype: QType) -> crate::Result<Vec<IpRecord>> {
+        let mut name = String::from(name);
+
+        for _ in 0..MAX_CNAME_CHAIN {
+            let response = self.query(&name, qtype)?;
+            let (target, addresses) = follow_cnames(&name, response.answers());
+
+            if !addresses.is_empty() || names_eq(&target, &name) {
+                return Ok(addresses);
+            }
+            name = target;
+        }
+
+        Err(DnsError::ParseError("CNAME chain too long"))
+    }
+
+    /// Return the host names `addr` points back to using PTR queries
+    pub fn reverse_lookup(&self, addr: IpAddr) -> crate::Result<Vec<String>> {
+        let response = self.query(&reverse_name(addr), QType::PTR)?;
+
+        let names = response
+            .answers()
+            .iter()
+            .filter_map(|record| match &record.rdata {
+                RData::PTR(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(names)
+    }
+
+    /// Return the SRV records of `name` in the order their targets should be tried, picking among
+    /// records of equal priority using the resolver's generator
+    pub fn lookup_srv(&self, name: &str) -> crate::Result<Vec<SRV>> {
+        let response = self.query(name, QType::SRV)?;
+        let (name, _) = follow_cnames(name, response.answers());
+
+        let mut records: Vec<SRV> = response
+            .answers()
+            .iter()
+            .filter(|record| names_eq(&record.name, &name))
+            .filter_map(|record| match &record.rdata {
+                RData::SRV(srv) => Some(srv.clone()),
+                _ => None,
+            })
+            .collect();
+
+        // A single record with the root as target means the service is not available
+        if let [srv] = records.as_slice() {
+            if srv.target.is_empty() {
+                return Ok(vec![]);
+            }
+        }
+
+        sort_srv(&mut records, &mut *self.rng.lock().unwrap());
+        Ok(records)
+    }
+
+    /// Query `qtype` records of `name`, responses with an error RCODE fail
+    fn query(&self, name: &str, qtype: QType) -> crate::Result<Message> {
+        let query = {
+            let mut rng = self.rng.lock().unwrap();
+            let query = Message::query(String::from(name), qtype, &mut *rng);
+            if self.randomize_case {
+                randomize_query_case(&query, &mut *rng)
+            } else {
+                query
+            }
+        };
+
+        let (response, _) =
+            Resolver::exchange_matching(&query, self.server, self.protocol, self.randomize_case)?;
+        check_rcode(response)
+    }
+}
+
 impl Resolver {
-    pub fn lookup(name: &str) -> IpAddr {
-        let mut stream = TcpStream::connect("1.1.1.1:53").unwrap();
+    /// Send `query` to `server` and wait for its response, truncated UDP responses are retried over TCP
+    pub fn exchange(
+        query: &Message,
+        server: SocketAddr,
+        protocol: Protocol,
+    ) -> crate::Result<Message> {
+        Resolver::exchange_matching(query, server, protocol, false).map(|(response, _)| response)
+    }
+
+    /// Like [`Resolver::exchange`], also returning the size of the response as it was received
+    pub fn exchange_with_size(
+        query: &Message,
+        server: SocketAddr,
+        protocol: Protocol,
+    ) -> crate::Result<(Message, usize)> {
+        Resolver::exchange_matching(query, server, protocol, false)
+    }
+
+    /// Like [`Resolver::exchange`] with the case of the question names randomized using `rng`,
+    /// responses not echoing it exactly are discarded
+    pub fn exchange_with_random_case(
+        query: &Message,
+        server: SocketAddr,
+        protocol: Protocol,
+        rng: &mut impl Rng,
+    ) -> crate::Result<Message> {
+        let query = randomize_query_case(query, rng);
+        Resolver::exchange_matching(&query, server, protocol, true).map(|(response, _)| response)
+    }
+
+    /// Exchange `query`, with `exact_case` responses have to repeat the case of the question names
+    fn exchange_matching(
+        query: &Message,
+        server: SocketAddr,
+        protocol: Protocol,
+        exact_case: bool,
+    ) -> crate::Result<(Message, usize)> {
+        if protocol == Protocol::Udp {
+            let (response, size) = Resolver::exchange_udp(query, server, exact_case)?;
+            if !response.header().truncated {
+                return Ok((response, size));
+            }
+        }
+
+        Resolver::exchange_tcp(query, server, exact_case)
+    }
+
+    fn exchange_udp(
+        query: &Message,
+        server: SocketAddr,
+        exact_case: bool,
+    ) -> crate::Result<(Message, usize)> {
+        let socket = bind_udp(server)?;
+        socket.connect(server)?;
+        socket.send(&query.as_bytes()?)?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut buffer = vec![0; u16::MAX as usize];
+        loop {
+            let remaining = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|remaining| !remaining.is_zero())
+                .ok_or_else(timed_out)?;
+            socket.set_read_timeout(Some(remaining))?;
+
+            let length = match socket.recv(&mut buffer) {
+                Ok(length) => length,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(timed_out())
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // Keep waiting when something else than the response arrives
+            match Message::from_bytes(&buffer[..length]) {
+                Ok(response)
+                    if is_response_to(&response, query)
+                        && (!exact_case
+                            || echoes_case(response.questions(), query.questions())) =>
+                {
+                    return Ok((response, length))
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Messages over TCP are prefixed with their length: https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.2
+    fn exchange_tcp(
+        query: &Message,
+        server: SocketAddr,
+        exact_case: bool,
+    ) -> crate::Result<(Message, usize)> {
+        let mut stream = TcpStream::connect_timeout(&server, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let bytes = query.as_bytes()?;
+        stream.write_all(&tcp_length(&bytes)?.to_be_bytes())?;
+        stream.write_all(&bytes)?;
+
+        let mut length = [0; 2];
+        stream.read_exact(&mut length)?;
+        let mut response = vec![0; u16::from_be_bytes(length) as usize];
+        stream.read_exact(&mut response)?;
+
+        let size = response.len();
+        let response = Message::from_bytes(&response)?;
+        if !is_response_to(&response, query) {
+            return Err(DnsError::ParseError("Response does not match the query"));
+        }
+        if exact_case && !echoes_case(response.questions(), query.questions()) {
+            return Err(DnsError::ParseError(
+                "Response does not echo the case of the query",
+            ));
+        }
+
+        Ok((response, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use crate::message::{Class, Type};
+
+    use super::*;
+
+    fn record(name: &str, r#type: Type, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl: 60,
+            rdata,
+        }
+    }
+
+    #[test]
+    fn follow_cnames_works() {
+        let answers = vec![
+            record(
+                "www.example.com.",
+                Type::CNAME,
+                RData::CNAME(String::from("cdn.example.net.")),
+            ),
+            record(
+                "cdn.example.net.",
+                Type::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            ),
+            record(
+                "cdn.example.net.",
+                Type::AAAA,
+                RData::AAAA(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+            ),
+            record(
+                "other.example.net.",
+                Type::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 2)),
+            ),
+        ];
+
+        let (name, addresses) = follow_cnames("WWW.example.com", &answers);
+
+        assert_eq!(name, "cdn.example.net.");
+        assert_eq!(
+            addresses,
+            vec![
+                IpRecord {
+                    addr: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                    ttl: 60
+                },
+                IpRecord {
+                    addr: IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+                    ttl: 60
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_cnames_stops_on_loops() {
+        let answers = vec![
+            record(
+                "a.example.",
+                Type::CNAME,
+                RData::CNAME(String::from("b.example.")),
+            ),
+            record(
+                "b.example.",
+                Type::CNAME,
+                RData::CNAME(String::from("a.example.")),
+            ),
+        ];
+
+        let (_, addresses) = follow_cnames("a.example.", &answers);
+
+        assert!(addresses.is_empty());
+    }
+
+    #[test]
+    fn error_rcodes_fail() {
+        let query = Message::query(String::from("example.com."), QType::A, &mut OsRng);
+        let response = |rcode| Message::response_to(&query).rcode(rcode).build();
+
+        assert!(check_rcode(response(RCode::NoError)).is_ok());
+        for rcode in [RCode::NameError, RCode::ServerFailure, RCode::Refused] {
+            assert!(matches!(
+                check_rcode(response(rcode)),
+                Err(DnsError::ResponseCode(error)) if error == rcode
+            ));
+        }
+    }
+
+    #[test]
+    fn lookups_use_the_given_rng() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Answers the A and AAAA queries and returns their IDs
+        let responder = thread::spawn(move || {
+            let mut ids = vec![];
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut length = [0; 2];
+                stream.read_exact(&mut length).unwrap();
+                let mut query = vec![0; u16::from_be_bytes(length) as usize];
+                stream.read_exact(&mut query).unwrap();
+                let query = Message::from_bytes(&query).unwrap();
+                ids.push(query.header().id);
+
+                let mut response = Message::response_to(&query);
+                if query.questions()[0].qtype == QType::A {
+                    response = response.answer(record(
+                        "example.com.",
+                        Type::A,
+                        RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+                    ));
+                }
+                let bytes = response.build().as_bytes().unwrap();
+                stream
+                    .write_all(&(bytes.len() as u16).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&bytes).unwrap();
+            }
+            ids
+        });
+
+        let resolver =
+            Resolver::with_rng(addr, rand::FixedSequence::new([7, 8])).protocol(Protocol::Tcp);
+        let addresses = resolver.lookup_ip("example.com.").unwrap();
+        let mut ids = responder.join().unwrap();
+        ids.sort();
+
+        assert_eq!(ids, [7, 8]);
+        assert_eq!(
+            addresses,
+            [IpRecord {
+                addr: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                ttl: 60
+            }]
+        );
+    }
+
+    #[test]
+    fn bind_udp_uses_random_ports() {
+        let server = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 53);
+        let a = bind_udp(server).unwrap().local_addr().unwrap();
+        let b = bind_udp(server).unwrap().local_addr().unwrap();
+
+        assert!(a.port() >= 1024 && b.port() >= 1024);
+        assert_ne!(a.port(), b.port());
+    }
+
+    #[test]
+    fn exchange_with_random_case_discards_mismatches() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let responder = thread::spawn(move || {
+            let mut buffer = vec![0; 512];
+            let (length, client) = server.recv_from(&mut buffer).unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+
+            let mut lowercased = query.clone();
+            for question in lowercased.questions_mut() {
+                question.qname = question.qname.to_ascii_lowercase();
+            }
+            for response in [&lowercased, &query] {
+                let response = Message::response_to(response).build();
+                server
+                    .send_to(&response.as_bytes().unwrap(), client)
+                    .unwrap();
+            }
+        });
+
+        let query = Message::query(String::from("example.com."), QType::A, &mut OsRng);
+        // Uppercases the first letter only
+        let mut rng = rand::FixedSequence::new([1]);
+        let response =
+            Resolver::exchange_with_random_case(&query, addr, Protocol::Udp, &mut rng).unwrap();
+        responder.join().unwrap();
+
+        assert_eq!(response.questions()[0].qname, "Example.com.");
+    }
+
+    #[test]
+    fn lookups_randomize_case() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let responder = thread::spawn(move || {
+            let mut buffer = vec![0; 512];
+            let (length, client) = server.recv_from(&mut buffer).unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+            let qname = query.questions()[0].qname.clone();
 
-        let bytes = Message::query(String::from(name)).as_bytes();
+            // A spoofed response guessing the case wrong comes first
+            let mut lowercased = query.clone();
+            lowercased.questions_mut()[0].qname = qname.to_ascii_lowercase();
+            let spoofed = Message::response_to(&lowercased).answer(record(
+                &qname,
+                Type::PTR,
+                RData::PTR(String::from("spoofed.example.")),
+            ));
+            let genuine = Message::response_to(&query).answer(record(
+                &qname,
+                Type::PTR,
+                RData::PTR(String::from("localhost.")),
+            ));
+            for response in [spoofed, genuine] {
+                let bytes = response.build().as_bytes().unwrap();
+                server.send_to(&bytes, client).unwrap();
+            }
+            qname
+        });
 
-        stream.write(&bytes).unwrap();
+        // Uppercases the first letter only
+        let resolver = Resolver::with_rng(addr, rand::FixedSequence::new([1])).randomize_case(true);
+        let names = resolver
+            .reverse_lookup(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .unwrap();
 
-        IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))
+        assert_eq!(responder.join().unwrap(), "1.0.0.127.In-addr.arpa.");
+        assert_eq!(names, ["localhost."]);
     }
 }
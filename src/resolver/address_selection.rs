@@ -0,0 +1,190 @@
+use std::{
+    cmp::Ordering,
+    net::{IpAddr, Ipv6Addr, UdpSocket},
+};
+
+use super::IpRecord;
+
+// https://datatracker.ietf.org/doc/html/rfc6724#section-2.1
+const POLICY_TABLE: [(Ipv6Addr, u32, u8, u8); 9] = [
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 128, 50, 0),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0), 96, 35, 4),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 96, 1, 3),
+    (Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0), 32, 5, 5),
+    (Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0), 16, 30, 2),
+    (Ipv6Addr::new(0x3ffe, 0, 0, 0, 0, 0, 0, 0), 16, 1, 12),
+    (Ipv6Addr::new(0xfec0, 0, 0, 0, 0, 0, 0, 0), 10, 1, 11),
+    (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7, 3, 13),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0, 40, 1),
+];
+
+/// IPv4 addresses are represented as IPv4-mapped addresses: https://datatracker.ietf.org/doc/html/rfc6724#section-3.1
+fn to_ipv6(addr: IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+    }
+}
+
+fn common_prefix_length(a: Ipv6Addr, b: Ipv6Addr) -> u32 {
+    (u128::from(a) ^ u128::from(b)).leading_zeros()
+}
+
+/// Return the (precedence, label) of the longest matching prefix in the policy table
+fn policy(addr: IpAddr) -> (u8, u8) {
+    let addr = to_ipv6(addr);
+    POLICY_TABLE
+        .iter()
+        .find(|(prefix, length, _, _)| common_prefix_length(addr, *prefix) >= *length)
+        .map(|(_, _, precedence, label)| (*precedence, *label))
+        .unwrap_or((40, 1))
+}
+
+// https://datatracker.ietf.org/doc/html/rfc6724#section-3.1
+fn scope(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(addr) if addr.is_loopback() || addr.is_link_local() => 0x2,
+        IpAddr::V4(_) => 0xe,
+        IpAddr::V6(addr) if addr.is_multicast() => addr.segments()[0] as u8 & 0xf,
+        IpAddr::V6(addr) if addr.is_loopback() || addr.segments()[0] & 0xffc0 == 0xfe80 => 0x2,
+        IpAddr::V6(addr) if addr.segments()[0] & 0xffc0 == 0xfec0 => 0x5,
+        IpAddr::V6(_) => 0xe,
+    }
+}
+
+/// The source address the operating system would pick to reach `destination`,
+/// connecting a UDP socket does not send any packets
+fn source_address(destination: IpAddr) -> Option<IpAddr> {
+    let bind = match destination {
+        IpAddr::V4(_) => "0.0.0.0:0",
+        IpAddr::V6(_) => "[::]:0",
+    };
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect((destination, 53)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// Compare two destinations by the rules of https://datatracker.ietf.org/doc/html/rfc6724#section-6,
+/// rules 3, 4 and 7 are skipped as they need information the operating system does not expose
+fn compare(a: (IpAddr, Option<IpAddr>), b: (IpAddr, Option<IpAddr>)) -> Ordering {
+    let (da, sa) = a;
+    let (db, sb) = b;
+
+    // Rule 1: Avoid unusable destinations
+    let (sa, sb) = match (sa, sb) {
+        (Some(sa), Some(sb)) => (sa, sb),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => return Ordering::Equal,
+    };
+
+    // Rule 2: Prefer matching scope
+    let matching_scope_a = scope(da) == scope(sa);
+    let matching_scope_b = scope(db) == scope(sb);
+    if matching_scope_a != matching_scope_b {
+        return matching_scope_b.cmp(&matching_scope_a);
+    }
+
+    // Rule 5: Prefer matching label
+    let (precedence_a, label_a) = policy(da);
+    let (precedence_b, label_b) = policy(db);
+    let matching_label_a = label_a == policy(sa).1;
+    let matching_label_b = label_b == policy(sb).1;
+    if matching_label_a != matching_label_b {
+        return matching_label_b.cmp(&matching_label_a);
+    }
+
+    // Rule 6: Prefer higher precedence
+    if precedence_a != precedence_b {
+        return precedence_b.cmp(&precedence_a);
+    }
+
+    // Rule 8: Prefer smaller scope
+    if scope(da) != scope(db) {
+        return scope(da).cmp(&scope(db));
+    }
+
+    // Rule 9: Use longest matching prefix, only for IPv6 like most implementations do
+    if let (IpAddr::V6(da), IpAddr::V6(sa), IpAddr::V6(db), IpAddr::V6(sb)) = (da, sa, db, sb) {
+        return common_prefix_length(db, sb).cmp(&common_prefix_length(da, sa));
+    }
+
+    // Rule 10: Otherwise, leave the order unchanged
+    Ordering::Equal
+}
+
+fn sort_with_sources(records: &mut [IpRecord], source: impl Fn(IpAddr) -> Option<IpAddr>) {
+    let mut keyed: Vec<(IpRecord, Option<IpAddr>)> = records
+        .iter()
+        .map(|record| (*record, source(record.addr)))
+        .collect();
+
+    keyed.sort_by(|(a, sa), (b, sb)| compare((a.addr, *sa), (b.addr, *sb)));
+
+    for (record, (sorted, _)) in records.iter_mut().zip(keyed) {
+        *record = sorted;
+    }
+}
+
+/// Sort addresses in the order they should be tried, following RFC 6724 destination address selection
+pub fn sort_addresses(records: &mut [IpRecord]) {
+    sort_with_sources(records, source_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn records(addrs: &[&str]) -> Vec<IpRecord> {
+        addrs
+            .iter()
+            .map(|addr| IpRecord {
+                addr: addr.parse().unwrap(),
+                ttl: 300,
+            })
+            .collect()
+    }
+
+    fn addrs(records: &[IpRecord]) -> Vec<String> {
+        records
+            .iter()
+            .map(|record| record.addr.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn prefers_ipv6_when_reachable() {
+        let mut records = records(&["198.51.100.121", "2001:db8:1::1"]);
+
+        sort_with_sources(&mut records, |addr| match addr {
+            IpAddr::V4(_) => Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 117))),
+            IpAddr::V6(_) => Some("2001:db8:1::2".parse().unwrap()),
+        });
+
+        assert_eq!(addrs(&records), vec!["2001:db8:1::1", "198.51.100.121"]);
+    }
+
+    #[test]
+    fn avoids_unusable_destinations() {
+        let mut records = records(&["2001:db8:1::1", "198.51.100.121"]);
+
+        sort_with_sources(&mut records, |addr| match addr {
+            IpAddr::V4(_) => Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 117))),
+            IpAddr::V6(_) => None,
+        });
+
+        assert_eq!(addrs(&records), vec!["198.51.100.121", "2001:db8:1::1"]);
+    }
+
+    #[test]
+    fn prefers_matching_scope() {
+        // https://datatracker.ietf.org/doc/html/rfc6724#section-10.2
+        let mut records = records(&["2001:db8:1::1", "fe80::1"]);
+
+        sort_with_sources(&mut records, |_| Some("fe80::2".parse().unwrap()));
+
+        assert_eq!(addrs(&records), vec!["fe80::1", "2001:db8:1::1"]);
+    }
+}
@@ -0,0 +1,837 @@
+//! Resolver for async code running on tokio, queries in flight share the sockets to the server
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use rand::{OsRng, Rng};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream, UdpSocket,
+    },
+    sync::{mpsc, oneshot, Mutex as AsyncMutex},
+    task::JoinHandle,
+    time::timeout,
+};
+
+use crate::{
+    error::DnsError,
+    message::{names_eq, Message, QClass, QType, Question, RData},
+    reverse::reverse_name,
+};
+
+use super::{
+    check_rcode, echoes_case, follow_cnames, local_address, merge_addresses, random_port,
+    randomize_query_case, sort_addresses, tcp_length, timed_out, IpRecord, Protocol, BIND_ATTEMPTS,
+    DEFAULT_SERVER, MAX_CNAME_CHAIN, TIMEOUT,
+};
+
+fn connection_closed() -> DnsError {
+    DnsError::IoError(io::Error::new(
+        io::ErrorKind::ConnectionAborted,
+        "Connection to server closed",
+    ))
+}
+
+/// A query waiting for its response
+struct Waiting {
+    questions: Vec<Question>,
+    /// Whether the response has to repeat the case of the question names
+    exact_case: bool,
+    /// Taken once the query is answered or failed, the ID stays reserved until the query is dropped
+    sender: Option<oneshot::Sender<Message>>,
+}
+
+#[derive(Default)]
+struct Queries {
+    waiting: HashMap<u16, Waiting>,
+    /// Set when the socket can't be used anymore
+    closed: bool,
+}
+
+/// Queries sent on a socket that are waiting for a response, by message ID
+#[derive(Default)]
+struct InFlight {
+    queries: Mutex<Queries>,
+}
+
+impl InFlight {
+    /// Reserve an unused ID picked using `rng` for a query asking `questions`
+    fn register(
+        self: &Arc<Self>,
+        questions: &[Question],
+        exact_case: bool,
+        rng: &mut impl Rng,
+    ) -> crate::Result<Registration> {
+        let mut queries = self.queries.lock().unwrap();
+        if queries.closed {
+            return Err(connection_closed());
+        }
+        if queries.waiting.len() > u16::MAX as usize {
+            return Err(DnsError::ParseError("Too many queries in flight"));
+        }
+
+        let id = loop {
+            let id = rng.next_u16();
+            if !queries.waiting.contains_key(&id) {
+                break id;
+            }
+        };
+        let (sender, receiver) = oneshot::channel();
+        queries.waiting.insert(
+            id,
+            Waiting {
+                questions: questions.to_vec(),
+                exact_case,
+                sender: Some(sender),
+            },
+        );
+
+        Ok(Registration {
+            in_flight: Arc::clone(self),
+            id,
+            receiver,
+        })
+    }
+
+    /// Hand `response` to the query it answers, anything else could be spoofed and is dropped
+    fn dispatch(&self, response: Message) {
+        let mut queries = self.queries.lock().unwrap();
+        let Some(waiting) = queries.waiting.get_mut(&response.header().id) else {
+            return;
+        };
+        if !response.header().response || response.questions() != waiting.questions {
+            return;
+        }
+        if waiting.exact_case && !echoes_case(response.questions(), &waiting.questions) {
+            return;
+        }
+        if let Some(sender) = waiting.sender.take() {
+            let _ = sender.send(response);
+        }
+    }
+
+    /// Fail every query waiting for a response
+    fn fail_all(&self) {
+        let mut queries = self.queries.lock().unwrap();
+        for waiting in queries.waiting.values_mut() {
+            waiting.sender = None;
+        }
+    }
+
+    /// Fail every query waiting for a response and refuse new ones
+    fn close(&self) {
+        self.fail_all();
+        self.queries.lock().unwrap().closed = true;
+    }
+
+    fn is_closed(&self) -> bool {
+        self.queries.lock().unwrap().closed
+    }
+}
+
+/// The ID reserved for a query, released when dropped so a cancelled query doesn't leak it
+struct Registration {
+    in_flight: Arc<InFlight>,
+    id: u16,
+    receiver: oneshot::Receiver<Message>,
+}
+
+impl Registration {
+    /// Wire format of `query` using the reserved ID
+    fn query_bytes(&self, query: &Message) -> crate::Result<Vec<u8>> {
+        let mut bytes = query.as_bytes()?;
+        bytes[..2].copy_from_slice(&self.id.to_be_bytes());
+        Ok(bytes)
+    }
+
+    async fn response(mut self) -> crate::Result<Message> {
+        match timeout(TIMEOUT, &mut self.receiver).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(connection_closed()),
+            Err(_) => Err(timed_out()),
+        }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.in_flight
+            .queries
+            .lock()
+            .unwrap()
+            .waiting
+            .remove(&self.id);
+    }
+}
+
+/// A UDP socket shared by every query to a server
+struct UdpChannel {
+    socket: Arc<UdpSocket>,
+    in_flight: Arc<InFlight>,
+    receiver: JoinHandle<()>,
+}
+
+impl UdpChannel {
+    async fn connect(server: SocketAddr) -> crate::Result<UdpChannel> {
+        let socket = bind_udp(server).await?;
+        socket.connect(server).await?;
+
+        let socket = Arc::new(socket);
+        let in_flight = Arc::<InFlight>::default();
+        let receiver = tokio::spawn(receive_udp(Arc::clone(&socket), Arc::clone(&in_flight)));
+
+        Ok(UdpChannel {
+            socket,
+            in_flight,
+            receiver,
+        })
+    }
+
+    async fn exchange(
+        &self,
+        query: &Message,
+        exact_case: bool,
+        rng: &Mutex<impl Rng>,
+    ) -> crate::Result<Message> {
+        let registration =
+            self.in_flight
+                .register(query.questions(), exact_case, &mut *rng.lock().unwrap())?;
+        self.socket.send(&registration.query_bytes(query)?).await?;
+        registration.response().await
+    }
+}
+
+impl Drop for UdpChannel {
+    fn drop(&mut self) {
+        self.receiver.abort();
+    }
+}
+
+/// Bind a UDP socket on a random source port for queries to `server`
+async fn bind_udp(server: SocketAddr) -> io::Result<UdpSocket> {
+    for _ in 0..BIND_ATTEMPTS {
+        match UdpSocket::bind(local_address(server, random_port())).await {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => continue,
+            result => return result,
+        }
+    }
+
+    UdpSocket::bind(local_address(server, 0)).await
+}
+
+async fn receive_udp(socket: Arc<UdpSocket>, in_flight: Arc<InFlight>) {
+    let mut buffer = vec![0; u16::MAX as usize];
+    loop {
+        match socket.recv(&mut buffer).await {
+            Ok(length) => {
+                if let Ok(response) = Message::from_bytes(&buffer[..length]) {
+                    in_flight.dispatch(response);
+                }
+            }
+            // Connected sockets report ICMP errors, like an unreachable port, on the next receive.
+            // Errors that persist would be reported again right away, so the socket is given up
+            // and replaced by the next query.
+            Err(_) => {
+                in_flight.close();
+                return;
+            }
+        }
+    }
+}
+
+/// A TCP connection shared by every query to a server, queries are pipelined and may be answered out of order: https://datatracker.ietf.org/doc/html/rfc7766#section-6.2.1
+///
+/// Queries are written by a separate task, so a cancelled query can't leave half a message on the stream.
+struct TcpChannel {
+    queue: mpsc::UnboundedSender<Vec<u8>>,
+    in_flight: Arc<InFlight>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl TcpChannel {
+    async fn connect(server: SocketAddr) -> crate::Result<TcpChannel> {
+        let stream = timeout(TIMEOUT, TcpStream::connect(server))
+            .await
+            .map_err(|_| timed_out())??;
+        let (reader, writer) = stream.into_split();
+
+        let in_flight = Arc::<InFlight>::default();
+        let (queue, queued) = mpsc::unbounded_channel();
+        let tasks = [
+            tokio::spawn(receive_tcp(reader, Arc::clone(&in_flight))),
+            tokio::spawn(send_tcp(writer, queued, Arc::clone(&in_flight))),
+        ];
+
+        Ok(TcpChannel {
+            queue,
+            in_flight,
+            tasks,
+        })
+    }
+
+    /// Messages over TCP are prefixed with their length: https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.2
+    async fn exchange(
+        &self,
+        query: &Message,
+        exact_case: bool,
+        rng: &Mutex<impl Rng>,
+    ) -> crate::Result<Message> {
+        let registration =
+            self.in_flight
+                .register(query.questions(), exact_case, &mut *rng.lock().unwrap())?;
+
+        let bytes = registration.query_bytes(query)?;
+        let length = tcp_length(&bytes)?;
+        let mut message = Vec::with_capacity(2 + bytes.len());
+        message.extend_from_slice(&length.to_be_bytes());
+        message.extend_from_slice(&bytes);
+        self.queue.send(message).map_err(|_| connection_closed())?;
+
+        registration.response().await
+    }
+}
+
+impl Drop for TcpChannel {
+    fn drop(&mut self) {
+        for task in self.tasks.iter() {
+            task.abort();
+        }
+    }
+}
+
+async fn receive_tcp(mut reader: OwnedReadHalf, in_flight: Arc<InFlight>) {
+    while let Ok(length) = reader.read_u16().await {
+        let mut buffer = vec![0; length as usize];
+        if reader.read_exact(&mut buffer).await.is_err() {
+            break;
+        }
+        if let Ok(response) = Message::from_bytes(&buffer) {
+            in_flight.dispatch(response);
+        }
+    }
+    in_flight.close();
+}
+
+async fn send_tcp(
+    mut writer: OwnedWriteHalf,
+    mut queue: mpsc::UnboundedReceiver<Vec<u8>>,
+    in_flight: Arc<InFlight>,
+) {
+    while let Some(message) = queue.recv().await {
+        if writer.write_all(&message).await.is_err() {
+            break;
+        }
+    }
+    in_flight.close();
+}
+
+struct Inner<R> {
+    server: SocketAddr,
+    /// Picks the message IDs and the case of randomized names
+    rng: Mutex<R>,
+    /// Replaced by a new socket once receiving fails
+    udp: AsyncMutex<Option<Arc<UdpChannel>>>,
+    /// Replaced by a new connection once the server closes it
+    tcp: AsyncMutex<Option<Arc<TcpChannel>>>,
+}
+
+/// Resolver for async code, concurrent queries share one UDP socket and one TCP connection to the server
+///
+/// Clones share the sockets and the generator. Dropping a future cancels its query without
+/// disturbing the others.
+pub struct AsyncResolver<R = OsRng> {
+    inner: Arc<Inner<R>>,
+    randomize_case: bool,
+}
+
+impl<R> Clone for AsyncResolver<R> {
+    fn clone(&self) -> Self {
+        AsyncResolver {
+            inner: Arc::clone(&self.inner),
+            randomize_case: self.randomize_case,
+        }
+    }
+}
+
+impl Default for AsyncResolver {
+    fn default() -> Self {
+        AsyncResolver::new(DEFAULT_SERVER)
+    }
+}
+
+impl AsyncResolver {
+    /// Create a resolver sending its queries to `server`, sockets are opened when first needed
+    pub fn new(server: SocketAddr) -> AsyncResolver {
+        AsyncResolver::with_rng(server, OsRng)
+    }
+}
+
+impl<R> AsyncResolver<R> {
+    /// Like [`AsyncResolver::new`], picking message IDs and the case of randomized names using `rng`
+    pub fn with_rng(server: SocketAddr, rng: R) -> AsyncResolver<R> {
+        AsyncResolver {
+            inner: Arc::new(Inner {
+                server,
+                rng: Mutex::new(rng),
+                udp: AsyncMutex::new(None),
+                tcp: AsyncMutex::new(None),
+            }),
+            randomize_case: false,
+        }
+    }
+
+    /// Randomize the case of question names and discard responses that don't echo it exactly,
+    /// making spoofed responses harder to get accepted: https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00
+    pub fn randomize_case(mut self, randomize_case: bool) -> AsyncResolver<R> {
+        self.randomize_case = randomize_case;
+        self
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.inner.server
+    }
+}
+
+impl<R: Rng + Send> AsyncResolver<R> {
+    /// Return all IPv4 and IPv6 addresses of `name`, querying A and AAAA records concurrently
+    pub async fn lookup_ip(&self, name: &str) -> crate::Result<Vec<IpRecord>> {
+        let (ipv4, ipv6) = tokio::join!(
+            self.lookup_addresses(name, QType::A),
+            self.lookup_addresses(name, QType::AAAA)
+        );
+
+        merge_addresses(ipv4, ipv6)
+    }
+
+    /// Like [`AsyncResolver::lookup_ip`] with the addresses sorted in the order they should be tried
+    pub async fn lookup_ip_sorted(&self, name: &str) -> crate::Result<Vec<IpRecord>> {
+        let mut addresses = self.lookup_ip(name).await?;
+        sort_addresses(&mut addresses);
+        Ok(addresses)
+    }
+
+    /// Query `qtype` addresses of `name`, querying again when the answer ends in a CNAME without addresses
+    async fn lookup_addresses(&self, name: &str, qtype: QType) -> crate::Result<Vec<IpRecord>> {
+        let mut name = String::from(name);
+
+        for _ in 0..MAX_CNAME_CHAIN {
+            let response = self.query(&name, qtype).await?;
+            let (target, addresses) = follow_cnames(&name, response.answers());
+
+            if !addresses.is_empty() || names_eq(&target, &name) {
+                return Ok(addresses);
+            }
+            name = target;
+        }
+
+        Err(DnsError::ParseError("CNAME chain too long"))
+    }
+
+    /// Return the host names `addr` points back to using PTR queries
+    pub async fn reverse_lookup(&self, addr: IpAddr) -> crate::Result<Vec<String>> {
+        let response = self.query(&reverse_name(addr), QType::PTR).await?;
+
+        let names = response
+            .answers()
+            .iter()
+            .filter_map(|record| match &record.rdata {
+                RData::PTR(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(names)
+    }
+
+    /// Query `qtype` records of `name`, responses with an error RCODE fail
+    async fn query(&self, name: &str, qtype: QType) -> crate::Result<Message> {
+        // The ID is picked when the query is sent
+        let query = Message::builder()
+            .recursion_desired(true)
+            .question(Question {
+                qname: String::from(name),
+                qtype,
+                qclass: QClass::IN,
+            })
+            .build();
+        check_rcode(self.exchange(&query, Protocol::Udp).await?)
+    }
+
+    /// Send `query` to the server and wait for its response, truncated UDP responses are retried over TCP
+    ///
+    /// The ID of `query` is replaced by one not used by any other query in flight, the case of its
+    /// question names is randomized when [`AsyncResolver::randomize_case`] is enabled.
+    pub async fn exchange(&self, query: &Message, protocol: Protocol) -> crate::Result<Message> {
+        let randomized;
+        let query = if self.randomize_case {
+            randomized = randomize_query_case(query, &mut *self.inner.rng.lock().unwrap());
+            &randomized
+        } else {
+            query
+        };
+
+        if protocol == Protocol::Udp {
+            let response = self
+                .udp_channel()
+                .await?
+                .exchange(query, self.randomize_case, &self.inner.rng)
+                .await?;
+            if !response.header().truncated {
+                return Ok(response);
+            }
+        }
+
+        self.tcp_channel()
+            .await?
+            .exchange(query, self.randomize_case, &self.inner.rng)
+            .await
+    }
+
+    /// The open UDP socket to the server, binding one when there is none
+    async fn udp_channel(&self) -> crate::Result<Arc<UdpChannel>> {
+        let mut udp = self.inner.udp.lock().await;
+        if let Some(channel) = udp
+            .as_ref()
+            .filter(|channel| !channel.in_flight.is_closed())
+        {
+            return Ok(Arc::clone(channel));
+        }
+
+        let channel = Arc::new(UdpChannel::connect(self.inner.server).await?);
+        *udp = Some(Arc::clone(&channel));
+        Ok(channel)
+    }
+
+    /// The open TCP connection to the server, connecting when there is none
+    async fn tcp_channel(&self) -> crate::Result<Arc<TcpChannel>> {
+        let mut tcp = self.inner.tcp.lock().await;
+        if let Some(channel) = tcp
+            .as_ref()
+            .filter(|channel| !channel.in_flight.is_closed())
+        {
+            return Ok(Arc::clone(channel));
+        }
+
+        let channel = Arc::new(TcpChannel::connect(self.inner.server).await?);
+        *tcp = Some(Arc::clone(&channel));
+        Ok(channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, time::Duration};
+
+    use rand::FixedSequence;
+    use tokio::net::TcpListener;
+
+    use crate::message::{Class, RCode, Record, Type};
+
+    use super::*;
+
+    fn localhost() -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)
+    }
+
+    /// Answer `query` with an address taken from the first label of its name, `1.example.` gets 192.0.2.1
+    fn answer(query: &Message) -> Message {
+        let name = &query.questions()[0].qname;
+        let octet = name.split('.').next().unwrap().parse().unwrap();
+
+        Message::response_to(query)
+            .answer(Record {
+                name: name.clone(),
+                r#type: Type::A,
+                class: Class::IN,
+                ttl: 60,
+                rdata: RData::A(Ipv4Addr::new(192, 0, 2, octet)),
+            })
+            .build()
+    }
+
+    fn assert_answers(response: crate::Result<Message>, octet: u8) {
+        let response = response.unwrap();
+        assert_eq!(
+            response.answers()[0].rdata,
+            RData::A(Ipv4Addr::new(192, 0, 2, octet))
+        );
+    }
+
+    async fn read_tcp(stream: &mut TcpStream) -> Message {
+        let length = stream.read_u16().await.unwrap();
+        let mut buffer = vec![0; length as usize];
+        stream.read_exact(&mut buffer).await.unwrap();
+        Message::from_bytes(&buffer).unwrap()
+    }
+
+    async fn write_tcp(stream: &mut TcpStream, message: &Message) {
+        let bytes = message.as_bytes().unwrap();
+        stream.write_u16(bytes.len() as u16).await.unwrap();
+        stream.write_all(&bytes).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn udp_queries_are_multiplexed() {
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(server.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 512];
+            let mut queries = vec![];
+            for _ in 0..2 {
+                let (length, client) = server.recv_from(&mut buffer).await.unwrap();
+                queries.push((Message::from_bytes(&buffer[..length]).unwrap(), client));
+            }
+
+            for (query, client) in queries.iter().rev() {
+                // A response with the right ID to a different question has to be ignored
+                let spoofed = Message::builder()
+                    .id(query.header().id)
+                    .response(true)
+                    .question(Question {
+                        qname: String::from("3.example."),
+                        qtype: QType::A,
+                        qclass: crate::message::QClass::IN,
+                    })
+                    .build();
+                server
+                    .send_to(&spoofed.as_bytes().unwrap(), client)
+                    .await
+                    .unwrap();
+                server
+                    .send_to(&answer(query).as_bytes().unwrap(), client)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let first = Message::query(String::from("1.example."), QType::A, &mut OsRng);
+        let second = Message::query(String::from("2.example."), QType::A, &mut OsRng);
+        let (first, second) = tokio::join!(
+            resolver.exchange(&first, Protocol::Udp),
+            resolver.exchange(&second, Protocol::Udp)
+        );
+
+        assert_answers(first, 1);
+        assert_answers(second, 2);
+    }
+
+    #[tokio::test]
+    async fn cancelled_queries_release_their_id() {
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(server.local_addr().unwrap());
+
+        let query = Message::query(String::from("1.example."), QType::A, &mut OsRng);
+        let cancelled = timeout(
+            Duration::from_millis(50),
+            resolver.exchange(&query, Protocol::Udp),
+        )
+        .await;
+
+        assert!(cancelled.is_err());
+        let udp = resolver.inner.udp.lock().await.clone().unwrap();
+        assert!(udp.in_flight.queries.lock().unwrap().waiting.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tcp_connection_is_reused_and_pipelined() {
+        let listener = TcpListener::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(listener.local_addr().unwrap());
+
+        // Only a single connection is accepted, the last query would time out on a new one
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let first = read_tcp(&mut stream).await;
+            let second = read_tcp(&mut stream).await;
+            write_tcp(&mut stream, &answer(&second)).await;
+            write_tcp(&mut stream, &answer(&first)).await;
+
+            let third = read_tcp(&mut stream).await;
+            write_tcp(&mut stream, &answer(&third)).await;
+        });
+
+        let first = Message::query(String::from("1.example."), QType::A, &mut OsRng);
+        let second = Message::query(String::from("2.example."), QType::A, &mut OsRng);
+        let (first, second) = tokio::join!(
+            resolver.exchange(&first, Protocol::Tcp),
+            resolver.exchange(&second, Protocol::Tcp)
+        );
+        assert_answers(first, 1);
+        assert_answers(second, 2);
+
+        let third = Message::query(String::from("3.example."), QType::A, &mut OsRng);
+        assert_answers(resolver.exchange(&third, Protocol::Tcp).await, 3);
+    }
+
+    #[tokio::test]
+    async fn closed_tcp_connections_are_replaced() {
+        let listener = TcpListener::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let query = read_tcp(&mut stream).await;
+                write_tcp(&mut stream, &answer(&query)).await;
+            }
+        });
+
+        let first = Message::query(String::from("1.example."), QType::A, &mut OsRng);
+        assert_answers(resolver.exchange(&first, Protocol::Tcp).await, 1);
+
+        let channel = resolver.inner.tcp.lock().await.clone().unwrap();
+        while !channel.in_flight.is_closed() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        let second = Message::query(String::from("2.example."), QType::A, &mut OsRng);
+        assert_answers(resolver.exchange(&second, Protocol::Tcp).await, 2);
+    }
+
+    #[tokio::test]
+    async fn lookups_use_the_given_rng() {
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let resolver =
+            AsyncResolver::with_rng(server.local_addr().unwrap(), FixedSequence::new([7, 8]));
+        // Spawning also checks that the futures are Send
+        let lookup = tokio::spawn(async move { resolver.lookup_ip("1.example.").await });
+
+        let mut buffer = vec![0; 512];
+        let mut ids = vec![];
+        for _ in 0..2 {
+            let (length, client) = server.recv_from(&mut buffer).await.unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+            ids.push(query.header().id);
+            server
+                .send_to(&answer(&query).as_bytes().unwrap(), client)
+                .await
+                .unwrap();
+        }
+        ids.sort();
+
+        assert_eq!(ids, [7, 8]);
+        assert!(lookup.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn failed_udp_sockets_are_replaced() {
+        // Nothing listens on the port, the query is answered with an ICMP port unreachable
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let addr = server.local_addr().unwrap();
+        drop(server);
+        let resolver = AsyncResolver::new(addr);
+
+        let first = Message::query(String::from("1.example."), QType::A, &mut OsRng);
+        assert!(resolver.exchange(&first, Protocol::Udp).await.is_err());
+        let channel = resolver.inner.udp.lock().await.clone().unwrap();
+        assert!(channel.in_flight.is_closed());
+
+        let server = UdpSocket::bind(addr).await.unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 512];
+            let (length, client) = server.recv_from(&mut buffer).await.unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+            server
+                .send_to(&answer(&query).as_bytes().unwrap(), client)
+                .await
+                .unwrap();
+        });
+
+        let second = Message::query(String::from("2.example."), QType::A, &mut OsRng);
+        assert_answers(resolver.exchange(&second, Protocol::Udp).await, 2);
+    }
+
+    #[tokio::test]
+    async fn error_rcodes_fail_lookups() {
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(server.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 512];
+            for _ in 0..2 {
+                let (length, client) = server.recv_from(&mut buffer).await.unwrap();
+                let query = Message::from_bytes(&buffer[..length]).unwrap();
+                let response = Message::response_to(&query).rcode(RCode::NameError).build();
+                server
+                    .send_to(&response.as_bytes().unwrap(), client)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        assert!(matches!(
+            resolver.lookup_ip("example.").await,
+            Err(DnsError::ResponseCode(RCode::NameError))
+        ));
+    }
+
+    #[tokio::test]
+    async fn responses_have_to_echo_the_random_case() {
+        let server = UdpSocket::bind(localhost()).await.unwrap();
+        let resolver = AsyncResolver::new(server.local_addr().unwrap()).randomize_case(true);
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 512];
+            let (length, client) = server.recv_from(&mut buffer).await.unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+
+            let mut lowercased = query.clone();
+            for question in lowercased.questions_mut() {
+                question.qname = question.qname.to_ascii_lowercase();
+            }
+            server
+                .send_to(&answer(&lowercased).as_bytes().unwrap(), client)
+                .await
+                .unwrap();
+            server
+                .send_to(&answer(&query).as_bytes().unwrap(), client)
+                .await
+                .unwrap();
+        });
+
+        let query = Message::query(
+            String::from("4.case-randomization.example."),
+            QType::A,
+            &mut OsRng,
+        );
+        let response = resolver.exchange(&query, Protocol::Udp).await.unwrap();
+
+        // The lowercased answer arrived first but is discarded, 29 letters all lowercase are unlikely
+        assert_ne!(
+            response.questions()[0].qname,
+            "4.case-randomization.example."
+        );
+        assert_eq!(response.questions(), query.questions());
+    }
+
+    #[tokio::test]
+    async fn truncated_responses_are_retried_over_tcp() {
+        let listener = TcpListener::bind(localhost()).await.unwrap();
+        let server = listener.local_addr().unwrap();
+        let udp = UdpSocket::bind(server).await.unwrap();
+        let resolver = AsyncResolver::new(server);
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 512];
+            let (length, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let query = Message::from_bytes(&buffer[..length]).unwrap();
+            let truncated = Message::response_to(&query).truncated(true).build();
+            udp.send_to(&truncated.as_bytes().unwrap(), client)
+                .await
+                .unwrap();
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let query = read_tcp(&mut stream).await;
+            write_tcp(&mut stream, &answer(&query)).await;
+        });
+
+        let query = Message::query(String::from("7.example."), QType::A, &mut OsRng);
+        assert_answers(resolver.exchange(&query, Protocol::Udp).await, 7);
+    }
+}
@@ -0,0 +1,82 @@
+//! Random case of query names, adding entropy the server has to echo back: https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00
+
+use rand::Rng;
+
+use crate::message::{Message, Question};
+
+/// Give every letter of `name` a random case, one bit of `rng` per letter
+pub fn randomize_case(name: &str, rng: &mut impl Rng) -> String {
+    let mut bits = 0;
+    let mut available = 0;
+
+    name.chars()
+        .map(|c| {
+            if !c.is_ascii_alphabetic() {
+                return c;
+            }
+            if available == 0 {
+                bits = rng.next_u32();
+                available = 32;
+            }
+            let upper = bits & 1 == 1;
+            bits >>= 1;
+            available -= 1;
+
+            if upper {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+/// Copy of `query` with the case of its question names randomized
+pub(super) fn randomize_query_case(query: &Message, rng: &mut impl Rng) -> Message {
+    let mut query = query.clone();
+    for question in query.questions_mut() {
+        question.qname = randomize_case(&question.qname, rng);
+    }
+    query
+}
+
+/// Whether the names of the `response` questions have exactly the case of those of the `query` questions
+pub(super) fn echoes_case(response: &[Question], query: &[Question]) -> bool {
+    response.len() == query.len()
+        && response
+            .iter()
+            .zip(query)
+            .all(|(a, b)| a.qname.trim_end_matches('.') == b.qname.trim_end_matches('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{FixedSequence, OsRng};
+
+    use crate::message::QType;
+
+    use super::*;
+
+    #[test]
+    fn randomize_case_works() {
+        // Bits are used from the least significant one, digits and dots don't use any
+        let mut rng = FixedSequence::new([0b1010_0101]);
+
+        assert_eq!(randomize_case("www1.example.", &mut rng), "WwW1.exAmPle.");
+    }
+
+    #[test]
+    fn echoes_case_is_case_sensitive() {
+        let query = Message::query(String::from("example.com."), QType::A, &mut OsRng);
+        let randomized = randomize_query_case(&query, &mut FixedSequence::new([0b101]));
+        assert_eq!(randomized.questions()[0].qname, "ExAmple.com.");
+
+        let echoed = Message::response_to(&randomized).build();
+        let lowercased = Message::response_to(&query).build();
+
+        assert!(echoes_case(echoed.questions(), randomized.questions()));
+        assert!(!echoes_case(lowercased.questions(), randomized.questions()));
+        // The names are still equal for everything else
+        assert_eq!(lowercased.questions(), randomized.questions());
+    }
+}
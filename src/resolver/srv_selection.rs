@@ -0,0 +1,95 @@
+use rand::Rng;
+
+use crate::message::SRV;
+
+/// Order records of equal priority randomly, records with a higher weight being more likely to come first
+fn order_by_weight(records: &mut [SRV], rng: &mut impl Rng) {
+    // Records of weight zero go first, leaving them a small chance to be picked early
+    records.sort_by_key(|record| record.weight != 0);
+
+    for i in 0..records.len() {
+        let remaining = &mut records[i..];
+        let total: u32 = remaining.iter().map(|record| record.weight as u32).sum();
+        let pick = rng.below(total + 1);
+
+        let mut sum = 0;
+        let selected = remaining
+            .iter()
+            .position(|record| {
+                sum += record.weight as u32;
+                sum >= pick
+            })
+            .unwrap_or(0);
+        remaining[..=selected].rotate_right(1);
+    }
+}
+
+/// Sort records in the order their targets should be tried: https://datatracker.ietf.org/doc/html/rfc2782
+///
+/// Lower priorities come first, records of equal priority are ordered by a weighted random pick using `rng`.
+pub fn sort_srv(records: &mut [SRV], rng: &mut impl Rng) {
+    records.sort_by_key(|record| record.priority);
+
+    for group in records.chunk_by_mut(|a, b| a.priority == b.priority) {
+        order_by_weight(group, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{FixedSequence, XorShift};
+
+    use super::*;
+
+    fn srv(priority: u16, weight: u16, target: &str) -> SRV {
+        SRV {
+            priority,
+            weight,
+            port: 443,
+            target: String::from(target),
+        }
+    }
+
+    fn targets(records: &[SRV]) -> Vec<&str> {
+        records
+            .iter()
+            .map(|record| record.target.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sort_srv_works() {
+        let mut records = vec![
+            srv(20, 5, "backup."),
+            srv(10, 30, "big."),
+            srv(10, 0, "zero."),
+            srv(10, 10, "small."),
+        ];
+
+        // Running sums are 0, 10 and 40 with zero first, 15 picks big., then 0 picks zero.
+        sort_srv(&mut records, &mut FixedSequence::new([15, 0]));
+
+        assert_eq!(targets(&records), ["big.", "zero.", "small.", "backup."]);
+    }
+
+    #[test]
+    fn weights_set_the_odds() {
+        let mut rng = XorShift::new(500);
+        let rounds = 4000;
+
+        let small_first = (0..rounds)
+            .filter(|_| {
+                let mut records = vec![srv(10, 10, "small."), srv(10, 30, "big.")];
+                sort_srv(&mut records, &mut rng);
+                records[0].target == "small."
+            })
+            .count();
+
+        // Picks of 0 to 10 out of 0 to 40 select small.
+        let expected = rounds * 11 / 41;
+        assert!(
+            small_first.abs_diff(expected) < rounds / 20,
+            "{small_first}"
+        );
+    }
+}
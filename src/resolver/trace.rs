@@ -0,0 +1,403 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+use crate::{
+    error::DnsError,
+    message::{is_subdomain, names_eq, Message, QClass, QType, Question, RCode, RData, Record},
+};
+
+use super::{sort_addresses, IpRecord, Resolver};
+
+/// Addresses of the root servers: https://www.iana.org/domains/root/servers
+const ROOT_SERVERS: [(&str, Ipv4Addr); 13] = [
+    ("a.root-servers.net.", Ipv4Addr::new(198, 41, 0, 4)),
+    ("b.root-servers.net.", Ipv4Addr::new(170, 247, 170, 2)),
+    ("c.root-servers.net.", Ipv4Addr::new(192, 33, 4, 12)),
+    ("d.root-servers.net.", Ipv4Addr::new(199, 7, 91, 13)),
+    ("e.root-servers.net.", Ipv4Addr::new(192, 203, 230, 10)),
+    ("f.root-servers.net.", Ipv4Addr::new(192, 5, 5, 241)),
+    ("g.root-servers.net.", Ipv4Addr::new(192, 112, 36, 4)),
+    ("h.root-servers.net.", Ipv4Addr::new(198, 97, 190, 53)),
+    ("i.root-servers.net.", Ipv4Addr::new(192, 36, 148, 17)),
+    ("j.root-servers.net.", Ipv4Addr::new(192, 58, 128, 30)),
+    ("k.root-servers.net.", Ipv4Addr::new(193, 0, 14, 129)),
+    ("l.root-servers.net.", Ipv4Addr::new(199, 7, 83, 42)),
+    ("m.root-servers.net.", Ipv4Addr::new(202, 12, 27, 33)),
+];
+
+/// Maximum amount of referrals followed before giving up
+const MAX_REFERRALS: usize = 16;
+
+/// Maximum amount of servers of a zone tried before giving up
+const MAX_ATTEMPTS: usize = 3;
+
+/// A query made during iterative resolution
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// Zone the queried server is a name server of, empty for the root
+    pub zone: String,
+    pub server_name: String,
+    pub server: SocketAddr,
+    pub response: Message,
+    /// Size of the response as it was received
+    pub size: usize,
+    pub elapsed: Duration,
+}
+
+impl TraceStep {
+    /// NS records delegating the question to a zone below [`TraceStep::zone`], empty when the
+    /// response is not a referral
+    pub fn referral(&self) -> Vec<&Record> {
+        let Some(question) = self.response.questions().first() else {
+            return vec![];
+        };
+        if !self.response.answers().is_empty() || self.response.header().rcode != RCode::NoError {
+            return vec![];
+        }
+
+        // Referrals to the zone itself or upwards would never end
+        let delegates = |record: &&Record| {
+            matches!(record.rdata, RData::NS(_))
+                && is_subdomain(&question.qname, &record.name)
+                && is_subdomain(&record.name, &self.zone)
+                && !names_eq(&record.name, &self.zone)
+        };
+
+        let mut records: Vec<&Record> =
+            self.response.authority().iter().filter(delegates).collect();
+        // A referral is for a single zone
+        if let Some(zone) = records.first().map(|record| record.name.clone()) {
+            records.retain(|record| names_eq(&record.name, &zone));
+        }
+        records
+    }
+
+    /// Addresses of the name servers of the referral given in the additional section
+    pub fn glue(&self) -> Vec<&Record> {
+        let referral = self.referral();
+
+        self.response
+            .additional()
+            .iter()
+            .filter(|record| matches!(record.rdata, RData::A(_) | RData::AAAA(_)))
+            .filter(|record| {
+                referral
+                    .iter()
+                    .any(|ns| matches!(&ns.rdata, RData::NS(name) if names_eq(name, &record.name)))
+            })
+            .collect()
+    }
+
+    /// Name servers to query next in the order they should be tried, spreading the load over the name servers using the rng of `resolver`
+    ///
+    /// Name servers without glue are looked up using `resolver`, as dig does.
+    fn next_servers<R: Rng + Send>(&self, resolver: &Resolver<R>) -> Vec<(String, IpAddr)> {
+        let mut referral = self.referral();
+        resolver.rng.lock().unwrap().shuffle(&mut referral);
+        let glue = self.glue();
+
+        let addresses = |name: &str, records: Vec<IpRecord>| {
+            let mut records = records;
+            sort_addresses(&mut records);
+            records
+                .into_iter()
+                .map(|record| (String::from(name), record.addr))
+                .collect::<Vec<_>>()
+        };
+
+        let mut servers = vec![];
+        for ns in referral.iter() {
+            let RData::NS(name) = &ns.rdata else {
+                continue;
+            };
+            let records = glue
+                .iter()
+                .filter(|record| names_eq(&record.name, name))
+                .filter_map(|record| match record.rdata {
+                    RData::A(addr) => Some(IpAddr::V4(addr)),
+                    RData::AAAA(addr) => Some(IpAddr::V6(addr)),
+                    _ => None,
+                })
+                .map(|addr| IpRecord { addr, ttl: 0 })
+                .collect();
+            servers.extend(addresses(name, records));
+        }
+
+        if servers.is_empty() {
+            for ns in referral.iter() {
+                let RData::NS(name) = &ns.rdata else {
+                    continue;
+                };
+                if let Ok(records) = resolver.lookup_ip(name) {
+                    servers.extend(addresses(name, records));
+                }
+                if !servers.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        servers
+    }
+}
+
+impl<R: Rng + Send> Resolver<R> {
+    /// Resolve `name` iteratively starting at the root servers, returning every query made
+    ///
+    /// The last step holds the final response, every step before it a referral. Queries are sent
+    /// over the protocol of the resolver without recursion desired, servers and query IDs are
+    /// picked using its rng. Only the addresses of name servers without glue are looked up
+    /// through its server.
+    pub fn trace(&self, name: &str, qtype: QType) -> crate::Result<Vec<TraceStep>> {
+        let mut zone = String::new();
+        // Start at a random root server to spread the load
+        let offset = self.rng.lock().unwrap().below(ROOT_SERVERS.len() as u32) as usize;
+        let mut servers: Vec<(String, IpAddr)> = ROOT_SERVERS
+            .iter()
+            .cycle()
+            .skip(offset)
+            .take(ROOT_SERVERS.len())
+            .map(|(name, addr)| (String::from(*name), IpAddr::V4(*addr)))
+            .collect();
+
+        let mut steps = vec![];
+        for _ in 0..MAX_REFERRALS {
+            let step = self.query_zone(&zone, &servers, name, qtype)?;
+
+            let referral = step.referral();
+            let Some(next_zone) = referral.first().map(|record| record.name.clone()) else {
+                steps.push(step);
+                return Ok(steps);
+            };
+
+            servers = step.next_servers(self);
+            if servers.is_empty() {
+                return Err(DnsError::ParseError("No address for any name server"));
+            }
+            zone = next_zone;
+            steps.push(step);
+        }
+
+        Err(DnsError::ParseError("Too many referrals"))
+    }
+
+    /// Query the name servers of `zone` until one of them responds
+    fn query_zone(
+        &self,
+        zone: &str,
+        servers: &[(String, IpAddr)],
+        name: &str,
+        qtype: QType,
+    ) -> crate::Result<TraceStep> {
+        let mut error = DnsError::ParseError("No name servers");
+
+        for (server_name, addr) in servers.iter().take(MAX_ATTEMPTS) {
+            let query = Message::builder()
+                .id(self.rng.lock().unwrap().next_u16())
+                .question(Question {
+                    qname: String::from(name),
+                    qtype,
+                    qclass: QClass::IN,
+                })
+                .build();
+            let server = SocketAddr::new(*addr, 53);
+
+            let start = Instant::now();
+            match Resolver::exchange_with_size(&query, server, self.protocol) {
+                Ok((response, size)) => {
+                    return Ok(TraceStep {
+                        zone: String::from(zone),
+                        server_name: server_name.clone(),
+                        server,
+                        response,
+                        size,
+                        elapsed: start.elapsed(),
+                    })
+                }
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{Ipv6Addr, TcpListener},
+        thread,
+    };
+
+    use rand::FixedSequence;
+
+    use crate::{
+        message::{Class, Type},
+        resolver::{Protocol, DEFAULT_SERVER},
+    };
+
+    use super::*;
+
+    fn record(name: &str, r#type: Type, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl: 172800,
+            rdata,
+        }
+    }
+
+    fn ns(zone: &str, name: &str) -> Record {
+        record(zone, Type::NS, RData::NS(String::from(name)))
+    }
+
+    fn step(zone: &str, response: Message) -> TraceStep {
+        TraceStep {
+            zone: String::from(zone),
+            server_name: String::from("a.root-servers.net."),
+            server: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(198, 41, 0, 4)), 53),
+            response,
+            size: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn referral() -> Message {
+        Message::builder()
+            .response(true)
+            .question(Question {
+                qname: String::from("www.example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .authority(ns("com.", "a.gtld-servers.net."))
+            .authority(ns("com.", "b.gtld-servers.net."))
+            .additional(record(
+                "a.gtld-servers.net.",
+                Type::A,
+                RData::A(Ipv4Addr::new(192, 5, 6, 30)),
+            ))
+            .additional(record(
+                "A.GTLD-servers.net.",
+                Type::AAAA,
+                RData::AAAA(Ipv6Addr::new(0x2001, 0x503, 0xa83e, 0, 0, 0, 2, 0x30)),
+            ))
+            .additional(record(
+                "unrelated.example.",
+                Type::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            ))
+            .build()
+    }
+
+    #[test]
+    fn referral_and_glue_work() {
+        let step = step("", referral());
+
+        assert_eq!(
+            step.referral(),
+            vec![
+                &ns("com.", "a.gtld-servers.net."),
+                &ns("com.", "b.gtld-servers.net.")
+            ]
+        );
+        assert_eq!(step.glue().len(), 2);
+
+        let resolver = Resolver::with_rng(DEFAULT_SERVER, FixedSequence::new([0]));
+        let servers = step.next_servers(&resolver);
+        assert_eq!(servers.len(), 2);
+        assert!(servers
+            .iter()
+            .all(|(name, _)| name == "a.gtld-servers.net."));
+    }
+
+    #[test]
+    fn glueless_name_servers_are_looked_up_with_the_resolver() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Answers the A and AAAA queries for the name server and returns their IDs
+        let responder = thread::spawn(move || {
+            let mut ids = vec![];
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut length = [0; 2];
+                stream.read_exact(&mut length).unwrap();
+                let mut query = vec![0; u16::from_be_bytes(length) as usize];
+                stream.read_exact(&mut query).unwrap();
+                let query = Message::from_bytes(&query).unwrap();
+                ids.push(query.header().id);
+
+                let mut response = Message::response_to(&query);
+                if query.questions()[0].qtype == QType::A {
+                    response = response.answer(record(
+                        "ns.example.net.",
+                        Type::A,
+                        RData::A(Ipv4Addr::new(192, 0, 2, 53)),
+                    ));
+                }
+                let bytes = response.build().as_bytes().unwrap();
+                stream
+                    .write_all(&(bytes.len() as u16).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&bytes).unwrap();
+            }
+            ids
+        });
+
+        let response = Message::builder()
+            .response(true)
+            .question(Question {
+                qname: String::from("www.example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .authority(ns("com.", "ns.example.net."))
+            .build();
+        let resolver = Resolver::with_rng(addr, FixedSequence::new([7])).protocol(Protocol::Tcp);
+        let servers = step("", response).next_servers(&resolver);
+
+        // Both query IDs come from the generator of the resolver
+        assert_eq!(responder.join().unwrap(), [7, 7]);
+        assert_eq!(
+            servers,
+            [(
+                String::from("ns.example.net."),
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53))
+            )]
+        );
+    }
+
+    #[test]
+    fn referral_must_be_below_the_zone() {
+        // A server for com. pointing back at com. or the root is lame
+        assert!(step("com.", referral()).referral().is_empty());
+        assert!(step("example.com.", referral()).referral().is_empty());
+    }
+
+    #[test]
+    fn answers_are_not_referrals() {
+        let response = Message::builder()
+            .response(true)
+            .question(Question {
+                qname: String::from("www.example.com."),
+                qtype: QType::A,
+                qclass: QClass::IN,
+            })
+            .answer(record(
+                "www.example.com.",
+                Type::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            ))
+            .authority(ns("example.com.", "ns.example.com."))
+            .build();
+
+        assert!(step("com.", response).referral().is_empty());
+    }
+}
@@ -0,0 +1,876 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::{OsRng, Rng};
+
+use crate::{
+    dnssec::{
+        matches_ds, prove_denial, prove_expansion, supports_algorithm, supports_digest,
+        verify_rrset, Denial, Security, ValidationError, RSASHA256, SHA256,
+    },
+    message::{
+        names_eq, parent_name, rrsets, Class, Edns, Message, QClass, QType, Question, RCode, RData,
+        Record, Type, DS, RRSIG,
+    },
+    util::from_hex,
+};
+
+use super::{follow_cnames, Protocol, Resolver, DEFAULT_SERVER};
+
+/// Key tags and SHA-256 digests of the root key signing keys: https://data.iana.org/root-anchors/root-anchors.xml
+const ROOT_ANCHORS: [(u16, &str); 2] = [
+    (
+        20326,
+        "E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D",
+    ),
+    (
+        38696,
+        "683D2D0ACB8C9B712A1948B27F741219298D0A450D612C483AF444A4C0FB2B16",
+    ),
+];
+
+/// DS records of the root zone published by IANA
+pub fn root_trust_anchors() -> Vec<Record> {
+    ROOT_ANCHORS
+        .iter()
+        .map(|(key_tag, digest)| Record {
+            name: String::new(),
+            r#type: Type::DS,
+            class: Class::IN,
+            ttl: 0,
+            rdata: RData::DS(DS {
+                key_tag: *key_tag,
+                algorithm: RSASHA256,
+                digest_type: SHA256,
+                digest: from_hex(digest).expect("Root anchor digests are valid hex"),
+            }),
+        })
+        .collect()
+}
+
+/// A response with the outcome of its validation
+#[derive(Debug, Clone)]
+pub struct ValidatedResponse {
+    /// The AD bit is only set when the response is secure
+    pub response: Message,
+    pub security: Security,
+    /// Size of the response as it was received
+    pub size: usize,
+}
+
+/// Resolver validating the responses of a recursive server itself, following the chain of
+/// DS and DNSKEY records down from its trust anchors: https://datatracker.ietf.org/doc/html/rfc4035#section-5
+///
+/// The server is asked not to validate, so bogus responses are returned rather than replaced by a
+/// server failure.
+pub struct ValidatingResolver<R = OsRng> {
+    server: SocketAddr,
+    protocol: Protocol,
+    trust_anchors: Vec<Record>,
+    /// Picks the message IDs
+    rng: Mutex<R>,
+}
+
+impl Default for ValidatingResolver {
+    fn default() -> Self {
+        ValidatingResolver::new(DEFAULT_SERVER)
+    }
+}
+
+impl ValidatingResolver {
+    /// Resolver querying `server` and trusting the root keys
+    pub fn new(server: SocketAddr) -> ValidatingResolver {
+        ValidatingResolver::with_rng(server, OsRng)
+    }
+}
+
+impl<R> ValidatingResolver<R> {
+    /// Like [`ValidatingResolver::new`], picking message IDs using `rng`
+    pub fn with_rng(server: SocketAddr, rng: R) -> ValidatingResolver<R> {
+        ValidatingResolver {
+            server,
+            protocol: Protocol::Udp,
+            trust_anchors: root_trust_anchors(),
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Send queries over `protocol`, UDP by default
+    pub fn protocol(mut self, protocol: Protocol) -> ValidatingResolver<R> {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Replace the trust anchors with other DS records, names are validated from the closest one above them
+    pub fn trust_anchors(mut self, trust_anchors: Vec<Record>) -> ValidatingResolver<R> {
+        self.trust_anchors = trust_anchors;
+        self
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+}
+
+impl<R: Rng> ValidatingResolver<R> {
+    /// Query `qtype` records of `name` and validate the response at the current time
+    pub fn query(&self, name: &str, qtype: QType) -> crate::Result<ValidatedResponse> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as u32);
+
+        let (mut response, size) = self.fetch(name, qtype)?;
+        let mut validator = Validator::new(&self.trust_anchors, now, |name: &str, qtype| {
+            self.fetch(name, qtype).map(|(response, _)| response)
+        });
+        let security = validator.validate(&response)?;
+
+        response.set_authenticated_data(security == Security::Secure);
+        Ok(ValidatedResponse {
+            response,
+            security,
+            size,
+        })
+    }
+
+    /// Query with DNSSEC records requested and validation by the server disabled, returning the
+    /// response and its size
+    fn fetch(&self, name: &str, qtype: QType) -> crate::Result<(Message, usize)> {
+        let query = Message::builder()
+            .id(self.rng.lock().unwrap().next_u16())
+            .recursion_desired(true)
+            .checking_disabled(true)
+            .question(Question {
+                qname: String::from(name),
+                qtype,
+                qclass: QClass::IN,
+            })
+            .edns(Edns {
+                dnssec_ok: true,
+                ..Default::default()
+            })
+            .build();
+
+        Resolver::exchange_with_size(&query, self.server, self.protocol)
+    }
+}
+
+/// Validated DNSKEY records of the zone a name is in, or the security of everything in it when
+/// there are none
+#[derive(Clone)]
+enum ZoneKeys {
+    Trusted { zone: String, dnskeys: Vec<Record> },
+    Untrusted(Security),
+}
+
+/// What the DS records of a name say about the zone below it
+enum Delegation {
+    /// Validated DS records of the child zone
+    Secure(Vec<Record>),
+    /// The name is in the same zone as its parent
+    NotACut,
+    Untrusted(Security),
+}
+
+/// Records of `records` owned by `name` with type `r#type`
+fn rrset(records: &[Record], name: &str, r#type: Type) -> Vec<Record> {
+    records
+        .iter()
+        .filter(|record| names_eq(&record.name, name) && record.r#type == r#type)
+        .cloned()
+        .collect()
+}
+
+/// Closest encloser of `owner` when `rrsig` signed the wildcard its records were expanded from: https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.2
+fn wildcard_encloser<'a>(owner: &'a str, rrsig: &RRSIG) -> Option<&'a str> {
+    let labels = owner.split('.').filter(|label| !label.is_empty()).count();
+    let signed = rrsig.labels as usize;
+    // The asterisk label of a wildcard owner itself is not counted either
+    if labels <= signed || (owner.starts_with("*.") && labels == signed + 1) {
+        return None;
+    }
+
+    let mut encloser = owner;
+    for _ in signed..labels {
+        encloser = parent_name(encloser)?;
+    }
+    Some(encloser)
+}
+
+/// Validation of responses, with the records of the chain of trust fetched by `fetch`
+struct Validator<'a, F> {
+    trust_anchors: &'a [Record],
+    /// Seconds since the epoch, signatures have to be valid at that time
+    now: u32,
+    fetch: F,
+    /// Keys of the zone each name is in, by lowercased name
+    zones: HashMap<String, ZoneKeys>,
+}
+
+impl<'a, F> Validator<'a, F>
+where
+    F: FnMut(&str, QType) -> crate::Result<Message>,
+{
+    fn new(trust_anchors: &'a [Record], now: u32, fetch: F) -> Validator<'a, F> {
+        Validator {
+            trust_anchors,
+            now,
+            fetch,
+            zones: HashMap::new(),
+        }
+    }
+
+    /// Security of `response`, the weakest one of its answers or of the proof there are none
+    fn validate(&mut self, response: &Message) -> crate::Result<Security> {
+        let Some(question) = response.questions().first() else {
+            return Ok(Security::Indeterminate);
+        };
+        let answers = response.answers();
+
+        let mut security = Security::Secure;
+        for rrset in rrsets(answers) {
+            if rrset[0].r#type == Type::RRSIG {
+                continue;
+            }
+            let rrset: Vec<Record> = rrset.into_iter().cloned().collect();
+            security = security.min(self.validate_rrset(&rrset, response)?);
+        }
+
+        let (target, _) = follow_cnames(&question.qname, answers);
+        let answered = answers.iter().any(|record| {
+            names_eq(&record.name, &target)
+                && (question.qtype == QType::ALL || record.r#type as u16 == question.qtype as u16)
+        });
+        if !answered {
+            security = security.min(self.validate_denial(&target, question.qtype, response)?);
+        }
+
+        Ok(security)
+    }
+
+    /// Security of `rrset` from the answer section of `response`
+    fn validate_rrset(&mut self, rrset: &[Record], response: &Message) -> crate::Result<Security> {
+        let owner = &rrset[0].name;
+        // DS records are signed by the parent zone
+        let name = match rrset[0].r#type {
+            Type::DS => parent_name(owner).unwrap_or(owner),
+            _ => owner,
+        };
+        let (zone, dnskeys) = match self.zone_keys(name)? {
+            ZoneKeys::Trusted { zone, dnskeys } => (zone, dnskeys),
+            ZoneKeys::Untrusted(security) => return Ok(security),
+        };
+
+        let rrsig = match verify_rrset(rrset, response.answers(), &dnskeys, self.now) {
+            Ok(rrsig) => rrsig,
+            Err(e) => return Ok(Security::Bogus(e)),
+        };
+        // Records expanded from a wildcard need proof that no closer name exists: https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.4
+        let Some(encloser) = wildcard_encloser(owner, rrsig) else {
+            return Ok(Security::Secure);
+        };
+        Ok(match self.proofs(&dnskeys, response) {
+            Ok(proofs) => match prove_expansion(owner, encloser, &zone, &proofs) {
+                Some(Denial::NoName) => Security::Secure,
+                Some(Denial::TooManyIterations) => Security::Insecure,
+                _ => Security::Bogus(ValidationError::MissingDenial),
+            },
+            Err(e) => Security::Bogus(e),
+        })
+    }
+
+    /// Security of the absence of `qtype` records at `name` in `response`
+    fn validate_denial(
+        &mut self,
+        name: &str,
+        qtype: QType,
+        response: &Message,
+    ) -> crate::Result<Security> {
+        let signer = match qtype {
+            QType::DS => parent_name(name).unwrap_or(name),
+            _ => name,
+        };
+        let (zone, dnskeys) = match self.zone_keys(signer)? {
+            ZoneKeys::Trusted { zone, dnskeys } => (zone, dnskeys),
+            ZoneKeys::Untrusted(security) => return Ok(security),
+        };
+
+        Ok(match self.deny(name, &zone, &dnskeys, response) {
+            Ok(Denial::Types(types))
+                if response.header().rcode == RCode::NameError
+                    || types.contains(&(qtype as u16))
+                    || types.contains(&(Type::CNAME as u16)) =>
+            {
+                Security::Bogus(ValidationError::MissingDenial)
+            }
+            Ok(Denial::OptOut | Denial::TooManyIterations) => Security::Insecure,
+            Ok(_) => Security::Secure,
+            Err(e) => Security::Bogus(e),
+        })
+    }
+
+    /// What the NSEC and NSEC3 records of `response` signed by `dnskeys` prove about `name` in `zone`
+    fn deny(
+        &self,
+        name: &str,
+        zone: &str,
+        dnskeys: &[Record],
+        response: &Message,
+    ) -> Result<Denial, ValidationError> {
+        let proofs = self.proofs(dnskeys, response)?;
+        prove_denial(name, zone, &proofs).ok_or(ValidationError::MissingDenial)
+    }
+
+    /// NSEC and NSEC3 records of the authority section of `response`, once they are all signed by `dnskeys`
+    fn proofs(
+        &self,
+        dnskeys: &[Record],
+        response: &Message,
+    ) -> Result<Vec<Record>, ValidationError> {
+        let authority = response.authority();
+
+        let mut proofs = vec![];
+        for rrset in rrsets(authority) {
+            if !matches!(rrset[0].r#type, Type::NSEC | Type::NSEC3) {
+                continue;
+            }
+            let rrset: Vec<Record> = rrset.into_iter().cloned().collect();
+            verify_rrset(&rrset, authority, dnskeys, self.now)?;
+            proofs.extend(rrset);
+        }
+        Ok(proofs)
+    }
+
+    /// Keys of the zone `name` is in, checking for a zone cut at every label below the closest
+    /// trust anchor
+    fn zone_keys(&mut self, name: &str) -> crate::Result<ZoneKeys> {
+        let key = name.trim_end_matches('.').to_ascii_lowercase();
+        if let Some(keys) = self.zones.get(&key) {
+            return Ok(keys.clone());
+        }
+
+        let anchors = rrset(self.trust_anchors, name, Type::DS);
+        let keys = if !anchors.is_empty() {
+            self.dnskeys(name, &anchors)?
+        } else {
+            match parent_name(name) {
+                None => ZoneKeys::Untrusted(Security::Indeterminate),
+                Some(parent) => match self.zone_keys(parent)? {
+                    ZoneKeys::Trusted { zone, dnskeys } => {
+                        match self.delegation(name, &zone, &dnskeys)? {
+                            Delegation::Secure(ds) => self.dnskeys(name, &ds)?,
+                            Delegation::NotACut => ZoneKeys::Trusted { zone, dnskeys },
+                            Delegation::Untrusted(security) => ZoneKeys::Untrusted(security),
+                        }
+                    }
+                    untrusted => untrusted,
+                },
+            }
+        };
+
+        self.zones.insert(key, keys.clone());
+        Ok(keys)
+    }
+
+    /// DNSKEY records of `zone` once one of them matches `ds_records` and signs them all: https://datatracker.ietf.org/doc/html/rfc4035#section-5.2
+    fn dnskeys(&mut self, zone: &str, ds_records: &[Record]) -> crate::Result<ZoneKeys> {
+        let ds: Vec<&DS> = ds_records
+            .iter()
+            .filter_map(|record| match &record.rdata {
+                RData::DS(ds)
+                    if supports_algorithm(ds.algorithm) && supports_digest(ds.digest_type) =>
+                {
+                    Some(ds)
+                }
+                _ => None,
+            })
+            .collect();
+        // Zones only signed with unknown algorithms are treated as unsigned
+        if ds.is_empty() {
+            return Ok(ZoneKeys::Untrusted(Security::Insecure));
+        }
+
+        let response = (self.fetch)(zone, QType::DNSKEY)?;
+        let dnskeys = rrset(response.answers(), zone, Type::DNSKEY);
+        let entry_points: Vec<Record> = dnskeys
+            .iter()
+            .filter(|dnskey| ds.iter().any(|ds| matches_ds(dnskey, ds)))
+            .cloned()
+            .collect();
+        if entry_points.is_empty() {
+            return Ok(ZoneKeys::Untrusted(Security::Bogus(
+                ValidationError::UnmatchedDS,
+            )));
+        }
+
+        Ok(
+            match verify_rrset(&dnskeys, response.answers(), &entry_points, self.now) {
+                Ok(_) => ZoneKeys::Trusted {
+                    zone: String::from(zone),
+                    dnskeys,
+                },
+                Err(e) => ZoneKeys::Untrusted(Security::Bogus(e)),
+            },
+        )
+    }
+
+    /// Whether `name` is the apex of a zone below `zone`, signed DS records or their signed
+    /// absence tell: https://datatracker.ietf.org/doc/html/rfc4035#section-5.2
+    fn delegation(
+        &mut self,
+        name: &str,
+        zone: &str,
+        dnskeys: &[Record],
+    ) -> crate::Result<Delegation> {
+        let response = (self.fetch)(name, QType::DS)?;
+
+        let ds = rrset(response.answers(), name, Type::DS);
+        if !ds.is_empty() {
+            return Ok(
+                match verify_rrset(&ds, response.answers(), dnskeys, self.now) {
+                    Ok(_) => Delegation::Secure(ds),
+                    Err(e) => Delegation::Untrusted(Security::Bogus(e)),
+                },
+            );
+        }
+
+        let has = |types: &[u16], r#type: Type| types.contains(&(r#type as u16));
+        Ok(match self.deny(name, zone, dnskeys, &response) {
+            Ok(Denial::Types(types)) if has(&types, Type::DS) => {
+                Delegation::Untrusted(Security::Bogus(ValidationError::MissingDenial))
+            }
+            Ok(Denial::Types(types)) if has(&types, Type::NS) && !has(&types, Type::SOA) => {
+                Delegation::Untrusted(Security::Insecure)
+            }
+            Ok(Denial::OptOut | Denial::TooManyIterations) => {
+                Delegation::Untrusted(Security::Insecure)
+            }
+            Ok(_) => Delegation::NotACut,
+            Err(e) => Delegation::Untrusted(Security::Bogus(e)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, UdpSocket},
+        thread,
+    };
+
+    use rand::FixedSequence;
+
+    use crate::{
+        dnssec::{ds_digest, sign_rrset, ED25519},
+        message::{cmp_names, is_subdomain, DNSKEY, NSEC},
+    };
+
+    use super::*;
+
+    const NOW: u32 = 1_800_000_000;
+
+    fn record(name: &str, r#type: Type, rdata: RData) -> Record {
+        Record {
+            name: String::from(name),
+            r#type,
+            class: Class::IN,
+            ttl: 3600,
+            rdata,
+        }
+    }
+
+    fn a(name: &str, octet: u8) -> Record {
+        record(name, Type::A, RData::A(Ipv4Addr::new(192, 0, 2, octet)))
+    }
+
+    fn nsec(name: &str, next: &str, types: &[Type]) -> Record {
+        record(
+            name,
+            Type::NSEC,
+            RData::NSEC(NSEC {
+                next_domain_name: String::from(next),
+                types: types.iter().map(|r#type| *r#type as u16).collect(),
+            }),
+        )
+    }
+
+    /// A zone served by the test server, signed with an Ed25519 key derived from `seed` unless unsigned
+    struct Zone {
+        name: String,
+        secret_key: [u8; 32],
+        dnskey: Record,
+        records: Vec<Record>,
+    }
+
+    impl Zone {
+        fn new(name: &str, seed: u8) -> Zone {
+            let secret_key = [seed; 32];
+            let dnskey = record(
+                name,
+                Type::DNSKEY,
+                RData::DNSKEY(DNSKEY {
+                    flags: DNSKEY::ZONE_KEY | DNSKEY::SECURE_ENTRY_POINT,
+                    protocol: 3,
+                    algorithm: ED25519,
+                    public_key: crypto::ed25519::public_key(&secret_key),
+                }),
+            );
+            Zone {
+                name: String::from(name),
+                secret_key,
+                dnskey,
+                records: vec![],
+            }
+        }
+
+        fn ds(&self) -> Record {
+            let RData::DNSKEY(key) = &self.dnskey.rdata else {
+                unreachable!()
+            };
+            record(
+                &self.name,
+                Type::DS,
+                RData::DS(DS {
+                    key_tag: key.key_tag(),
+                    algorithm: ED25519,
+                    digest_type: SHA256,
+                    digest: ds_digest(&self.name, key, SHA256).unwrap(),
+                }),
+            )
+        }
+
+        /// Add `rrset` with its signature valid around `now`
+        fn sign(&mut self, rrset: Vec<Record>, now: u32) {
+            let rrsig = sign_rrset(
+                &rrset,
+                &self.dnskey,
+                &self.secret_key,
+                now - 3600,
+                now + 86400,
+            )
+            .unwrap();
+            self.records.extend(rrset);
+            self.records.push(rrsig);
+        }
+
+        /// Remove the NSEC record owned by `owner` with its signature
+        fn remove_nsec(&mut self, owner: &str) {
+            self.records.retain(|record| {
+                !names_eq(&record.name, owner)
+                    || !matches!(&record.rdata, RData::NSEC(_))
+                        && !matches!(&record.rdata, RData::RRSIG(rrsig) if rrsig.type_covered == Type::NSEC as u16)
+            });
+        }
+    }
+
+    /// A signed root delegating to the signed `example.`, with a wildcard below `wild.example.`, and
+    /// the unsigned `insecure.`
+    fn zones(now: u32) -> Vec<Zone> {
+        let mut root = Zone::new("", 1);
+        let mut example = Zone::new("example.", 2);
+        let mut insecure = Zone::new("insecure.", 3);
+
+        root.sign(vec![root.dnskey.clone()], now);
+        root.sign(vec![example.ds()], now);
+        let apex = [Type::NS, Type::SOA, Type::RRSIG, Type::NSEC, Type::DNSKEY];
+        root.sign(vec![nsec("", "example.", &apex)], now);
+        root.sign(
+            vec![nsec(
+                "example.",
+                "insecure.",
+                &[Type::NS, Type::DS, Type::RRSIG, Type::NSEC],
+            )],
+            now,
+        );
+        root.sign(
+            vec![nsec("insecure.", "", &[Type::NS, Type::RRSIG, Type::NSEC])],
+            now,
+        );
+
+        example.sign(vec![example.dnskey.clone()], now);
+        example.sign(vec![a("www.example.", 1)], now);
+        example.sign(vec![a("bad.example.", 2)], now);
+        let tampered = example
+            .records
+            .iter_mut()
+            .find(|record| record.name == "bad.example." && record.r#type == Type::A)
+            .unwrap();
+        tampered.rdata = RData::A(Ipv4Addr::new(192, 0, 2, 3));
+        example.sign(vec![a("*.wild.example.", 5)], now);
+        example.sign(vec![nsec("example.", "bad.example.", &apex)], now);
+        let leaf = [Type::A, Type::RRSIG, Type::NSEC];
+        example.sign(vec![nsec("bad.example.", "*.wild.example.", &leaf)], now);
+        example.sign(vec![nsec("*.wild.example.", "www.example.", &leaf)], now);
+        example.sign(vec![nsec("www.example.", "example.", &leaf)], now);
+
+        insecure.records.push(a("www.insecure.", 4));
+
+        vec![root, example, insecure]
+    }
+
+    /// Answer like a recursive server would from the zone closest to `name`, DS records come
+    /// from the parent side of a cut and missing names are synthesized from wildcards: https://datatracker.ietf.org/doc/html/rfc4592#section-3.3.1
+    fn respond(zones: &[Zone], name: &str, qtype: QType) -> Message {
+        let zone = zones
+            .iter()
+            .filter(|zone| {
+                is_subdomain(name, &zone.name)
+                    && !(qtype == QType::DS && names_eq(name, &zone.name))
+            })
+            .max_by(|a, b| cmp_names(&a.name, &b.name))
+            .unwrap();
+        let covered = |record: &Record| match &record.rdata {
+            RData::RRSIG(rrsig) => rrsig.type_covered,
+            _ => record.r#type as u16,
+        };
+
+        let mut response = Message::builder()
+            .response(true)
+            .recursion_available(true)
+            .question(Question {
+                qname: String::from(name),
+                qtype,
+                qclass: QClass::IN,
+            });
+        let exists = |name: &str| {
+            zone.records
+                .iter()
+                .any(|record| is_subdomain(&record.name, name))
+        };
+        let answers = |owner: &str| {
+            zone.records
+                .iter()
+                .filter(|record| names_eq(&record.name, owner) && covered(record) == qtype as u16)
+                .map(|record| Record {
+                    name: String::from(name),
+                    ..record.clone()
+                })
+                .collect::<Vec<Record>>()
+        };
+
+        let mut expanded = false;
+        let mut records = answers(name);
+        if !exists(name) {
+            let mut encloser = parent_name(name).unwrap();
+            while !exists(encloser) {
+                encloser = parent_name(encloser).unwrap();
+            }
+            let wildcard = format!("*.{encloser}");
+            if exists(&wildcard) {
+                expanded = true;
+                records = answers(&wildcard);
+            } else {
+                response = response.rcode(RCode::NameError);
+            }
+        }
+        for record in &records {
+            response = response.answer(record.clone());
+        }
+
+        if records.is_empty() || expanded {
+            let nsecs = zone
+                .records
+                .iter()
+                .filter(|record| covered(record) == Type::NSEC as u16);
+            for record in nsecs {
+                response = response.authority(record.clone());
+            }
+        }
+
+        response.build()
+    }
+
+    fn validate(zones: &[Zone], anchors: &[Record], name: &str, qtype: QType) -> Security {
+        let mut validator = Validator::new(anchors, NOW, |name: &str, qtype| {
+            Ok(respond(zones, name, qtype))
+        });
+        validator.validate(&respond(zones, name, qtype)).unwrap()
+    }
+
+    #[test]
+    fn chain_of_trust_is_followed() {
+        let zones = zones(NOW);
+        let anchors = [zones[0].ds()];
+
+        assert_eq!(
+            validate(&zones, &anchors, "www.example.", QType::A),
+            Security::Secure
+        );
+        assert_eq!(
+            validate(&zones, &anchors, "example.", QType::DNSKEY),
+            Security::Secure
+        );
+        assert_eq!(
+            validate(&zones, &anchors, "bad.example.", QType::A),
+            Security::Bogus(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn negative_answers_need_proof() {
+        let mut zones = zones(NOW);
+        let anchors = [zones[0].ds()];
+
+        assert_eq!(
+            validate(&zones, &anchors, "www.example.", QType::AAAA),
+            Security::Secure
+        );
+        assert_eq!(
+            validate(&zones, &anchors, "missing.example.", QType::A),
+            Security::Secure
+        );
+
+        zones[1]
+            .records
+            .retain(|record| !names_eq(&record.name, "bad.example."));
+        assert_eq!(
+            validate(&zones, &anchors, "missing.example.", QType::A),
+            Security::Bogus(ValidationError::MissingDenial)
+        );
+    }
+
+    #[test]
+    fn name_errors_need_the_wildcard_denial() {
+        let mut zones = zones(NOW);
+        let anchors = [zones[0].ds()];
+
+        // `missing.example.` is still covered, but not `*.example.`
+        zones[1].remove_nsec("example.");
+        assert_eq!(
+            validate(&zones, &anchors, "missing.example.", QType::A),
+            Security::Bogus(ValidationError::MissingDenial)
+        );
+    }
+
+    #[test]
+    fn wildcard_answers_need_proof() {
+        let mut zones = zones(NOW);
+        let anchors = [zones[0].ds()];
+
+        assert_eq!(
+            validate(&zones, &anchors, "host.wild.example.", QType::A),
+            Security::Secure
+        );
+        assert_eq!(
+            validate(&zones, &anchors, "*.wild.example.", QType::A),
+            Security::Secure
+        );
+
+        // Without the NSEC record covering `host.wild.example.` the name could exist
+        zones[1].remove_nsec("*.wild.example.");
+        assert_eq!(
+            validate(&zones, &anchors, "host.wild.example.", QType::A),
+            Security::Bogus(ValidationError::MissingDenial)
+        );
+    }
+
+    #[test]
+    fn unsigned_delegations_are_insecure() {
+        let mut zones = zones(NOW);
+        let anchors = [zones[0].ds()];
+
+        assert_eq!(
+            validate(&zones, &anchors, "www.insecure.", QType::A),
+            Security::Insecure
+        );
+
+        // Without the NSEC record the missing DS could have been stripped by an attacker
+        zones[0]
+            .records
+            .retain(|record| !names_eq(&record.name, "insecure."));
+        assert_eq!(
+            validate(&zones, &anchors, "www.insecure.", QType::A),
+            Security::Bogus(ValidationError::MissingDenial)
+        );
+    }
+
+    #[test]
+    fn trust_anchors_are_required() {
+        let zones = zones(NOW);
+
+        // Names outside the anchored zone can't be validated
+        let anchors = [zones[1].ds()];
+        assert_eq!(
+            validate(&zones, &anchors, "www.example.", QType::A),
+            Security::Secure
+        );
+        assert_eq!(
+            validate(&zones, &anchors, "www.insecure.", QType::A),
+            Security::Indeterminate
+        );
+
+        let mut wrong = zones[0].ds();
+        if let RData::DS(ds) = &mut wrong.rdata {
+            ds.digest[0] ^= 1;
+        }
+        assert_eq!(
+            validate(&zones, &[wrong], "www.example.", QType::A),
+            Security::Bogus(ValidationError::UnmatchedDS)
+        );
+    }
+
+    #[test]
+    fn signatures_expire() {
+        let zones = zones(NOW - 2 * 86400);
+        let anchors = [zones[0].ds()];
+
+        assert_eq!(
+            validate(&zones, &anchors, "www.example.", QType::A),
+            Security::Bogus(ValidationError::Expired)
+        );
+    }
+
+    #[test]
+    fn query_sets_authenticated_data() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        let zones = zones(now);
+        let anchors = vec![zones[0].ds()];
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver =
+            ValidatingResolver::with_rng(server.local_addr().unwrap(), FixedSequence::new([42]))
+                .trust_anchors(anchors);
+        // Serves until the test ends
+        thread::spawn(move || {
+            let mut buffer = vec![0; 512];
+            while let Ok((length, client)) = server.recv_from(&mut buffer) {
+                let query = Message::from_bytes(&buffer[..length]).unwrap();
+                assert_eq!(query.header().id, 42);
+                assert!(query.header().checking_disabled);
+                assert!(query.edns().is_some_and(|edns| edns.dnssec_ok));
+
+                let question = &query.questions()[0];
+                let response = respond(&zones, &question.qname, question.qtype);
+                let mut builder = Message::response_to(&query).rcode(response.header().rcode);
+                for record in response.answers() {
+                    builder = builder.answer(record.clone());
+                }
+                for record in response.authority() {
+                    builder = builder.authority(record.clone());
+                }
+                let response = builder.build();
+                server
+                    .send_to(&response.as_bytes().unwrap(), client)
+                    .unwrap();
+            }
+        });
+
+        let secure = resolver.query("www.example.", QType::A).unwrap();
+        assert_eq!(secure.security, Security::Secure);
+        assert!(secure.response.header().authenticated_data);
+
+        let bogus = resolver.query("bad.example.", QType::A).unwrap();
+        assert_eq!(
+            bogus.security,
+            Security::Bogus(ValidationError::InvalidSignature)
+        );
+        assert!(!bogus.response.header().authenticated_data);
+    }
+}
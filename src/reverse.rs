@@ -0,0 +1,115 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const IPV4_SUFFIX: &str = "in-addr.arpa.";
+const IPV6_SUFFIX: &str = "ip6.arpa.";
+
+/// Return the name used for reverse lookups of `addr`
+///
+/// IPv4 addresses use https://datatracker.ietf.org/doc/html/rfc1035#section-3.5 and IPv6 addresses
+/// the nibble format of https://datatracker.ietf.org/doc/html/rfc3596#section-2.5
+pub fn reverse_name(addr: IpAddr) -> String {
+    let mut name = String::new();
+
+    match addr {
+        IpAddr::V4(addr) => {
+            for octet in addr.octets().iter().rev() {
+                name.push_str(&format!("{octet}."));
+            }
+            name.push_str(IPV4_SUFFIX);
+        }
+        IpAddr::V6(addr) => {
+            for octet in addr.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", octet & 0xf, octet >> 4));
+            }
+            name.push_str(IPV6_SUFFIX);
+        }
+    }
+
+    name
+}
+
+/// Return the address a reverse lookup name refers to, if it is a complete in-addr.arpa or ip6.arpa name
+pub fn from_reverse_name(name: &str) -> Option<IpAddr> {
+    let mut name = name.to_ascii_lowercase();
+    if !name.ends_with('.') {
+        name.push('.');
+    }
+    // The suffix has to start at a label boundary
+    let labels = |suffix: &str| name.strip_suffix(suffix)?.strip_suffix('.');
+
+    if let Some(labels) = labels(IPV4_SUFFIX) {
+        let mut octets = [0; 4];
+        let mut labels = labels.split('.').rev();
+        for octet in octets.iter_mut() {
+            let label = labels
+                .next()
+                .filter(|label| label.bytes().all(|b| b.is_ascii_digit()))?;
+            *octet = label.parse().ok()?;
+        }
+        if labels.next().is_some() {
+            return None;
+        }
+
+        return Some(IpAddr::V4(Ipv4Addr::from(octets)));
+    }
+
+    if let Some(labels) = labels(IPV6_SUFFIX) {
+        let mut value: u128 = 0;
+        let mut count = 0;
+        for label in labels.split('.').rev() {
+            if label.len() != 1 {
+                return None;
+            }
+            value = (value << 4) | u128::from_str_radix(label, 16).ok()?;
+            count += 1;
+        }
+        if count != 32 {
+            return None;
+        }
+
+        return Some(IpAddr::V6(Ipv6Addr::from(value)));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_name_works() {
+        assert_eq!(
+            reverse_name(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))),
+            "1.2.0.192.in-addr.arpa."
+        );
+        assert_eq!(
+            reverse_name("2001:db8::567:89ab".parse().unwrap()),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+    }
+
+    #[test]
+    fn from_reverse_name_works() {
+        for addr in ["192.0.2.1", "2001:db8::567:89ab", "::1"] {
+            let addr: IpAddr = addr.parse().unwrap();
+            assert_eq!(from_reverse_name(&reverse_name(addr)), Some(addr));
+        }
+
+        assert_eq!(
+            from_reverse_name("1.2.0.192.IN-ADDR.ARPA"),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))
+        );
+        assert_eq!(from_reverse_name("2.0.192.in-addr.arpa."), None);
+        assert_eq!(from_reverse_name("256.2.0.192.in-addr.arpa."), None);
+        assert_eq!(from_reverse_name("+1.2.0.192.in-addr.arpa."), None);
+        assert_eq!(from_reverse_name("1.2.0.192in-addr.arpa."), None);
+        assert_eq!(from_reverse_name("1.2.0.192.xin-addr.arpa."), None);
+        assert_eq!(from_reverse_name("8.b.d.0.1.0.0.2.ip6.arpa."), None);
+        assert_eq!(
+            from_reverse_name(&reverse_name("::1".parse().unwrap()).replace(".ip6", "ip6")),
+            None
+        );
+        assert_eq!(from_reverse_name("example.com."), None);
+    }
+}
@@ -20,3 +20,169 @@ impl<'a, 'b> fmt::Write for UpperCaseFormatter<'a, 'b> {
         Ok(())
     }
 }
+
+/// Formats bytes as uppercase hexadecimal, as used in the presentation format of RDATA
+pub struct Hex<'a>(pub &'a [u8]);
+impl<'a> fmt::Display for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02X}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse hexadecimal in either case into bytes, the inverse of [`Hex`]
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Formats bytes as padded base64, used for keys and signatures: https://datatracker.ietf.org/doc/html/rfc4648#section-4
+pub struct Base64<'a>(pub &'a [u8]);
+impl<'a> fmt::Display for Base64<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.0.chunks(3) {
+            let mut group = [0; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (bits >> (18 - 6 * i)) & 0x3f;
+                    write!(f, "{}", BASE64[index as usize] as char)?;
+                } else {
+                    write!(f, "=")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decode `text` of `bits_per_character` bits per character, `value` maps a character to its bits
+fn decode_bits(
+    text: &str,
+    bits_per_character: u32,
+    value: impl Fn(u8) -> Option<u32>,
+) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut bits = 0u32;
+    let mut count = 0;
+
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        bits = (bits << bits_per_character) | value(c)?;
+        count += bits_per_character;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+
+    // Leftover bits only pad the last byte and have to be zero
+    (bits == 0 && count < bits_per_character).then_some(bytes)
+}
+
+/// Parse base64 with or without padding, ignoring whitespace, the inverse of [`Base64`]
+pub fn from_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches(|c: char| c == '=' || c.is_ascii_whitespace());
+    decode_bits(text, 6, |c| {
+        BASE64
+            .iter()
+            .position(|&b| b == c)
+            .map(|position| position as u32)
+    })
+}
+
+const BASE32HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+/// Formats bytes as base32 with the extended hex alphabet and without padding, used for NSEC3 hashes: https://datatracker.ietf.org/doc/html/rfc5155#section-3.3
+pub struct Base32Hex<'a>(pub &'a [u8]);
+impl<'a> fmt::Display for Base32Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bits = 0u32;
+        let mut count = 0;
+        for &byte in self.0 {
+            bits = (bits << 8) | byte as u32;
+            count += 8;
+            while count >= 5 {
+                count -= 5;
+                write!(f, "{}", BASE32HEX[(bits >> count) as usize & 0x1f] as char)?;
+            }
+            bits &= (1 << count) - 1;
+        }
+        if count > 0 {
+            write!(
+                f,
+                "{}",
+                BASE32HEX[(bits << (5 - count)) as usize & 0x1f] as char
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse base32hex in either case without padding, the inverse of [`Base32Hex`]
+pub fn from_base32hex(text: &str) -> Option<Vec<u8>> {
+    decode_bits(text, 5, |c| {
+        BASE32HEX
+            .iter()
+            .position(|&b| b == c.to_ascii_uppercase())
+            .map(|position| position as u32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://datatracker.ietf.org/doc/html/rfc4648#section-10
+    const VECTORS: [&str; 7] = ["", "f", "fo", "foo", "foob", "fooba", "foobar"];
+
+    #[test]
+    fn base64_works() {
+        let encoded = [
+            "", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy",
+        ];
+
+        for (input, expected) in VECTORS.iter().zip(encoded) {
+            assert_eq!(Base64(input.as_bytes()).to_string(), expected);
+            assert_eq!(from_base64(expected).unwrap(), input.as_bytes());
+        }
+        assert_eq!(from_base64("Zm9v\n YmFy").unwrap(), b"foobar");
+        assert!(from_base64("Zm9v!").is_none());
+        assert!(from_base64("Zh==").is_none());
+    }
+
+    #[test]
+    fn base32hex_works() {
+        let encoded = [
+            "",
+            "CO",
+            "CPNG",
+            "CPNMU",
+            "CPNMUOG",
+            "CPNMUOJ1",
+            "CPNMUOJ1E8",
+        ];
+
+        for (input, expected) in VECTORS.iter().zip(encoded) {
+            assert_eq!(Base32Hex(input.as_bytes()).to_string(), expected);
+            assert_eq!(from_base32hex(expected).unwrap(), input.as_bytes());
+        }
+        assert_eq!(from_base32hex("cpnmuoj1e8").unwrap(), b"foobar");
+        assert!(from_base32hex("CPNMUOJ1EW").is_none());
+    }
+}